@@ -7,7 +7,7 @@ pub struct AiModel {
     #[max_len(50)]
     pub name: String,
     pub framework: u8,
-    pub license: u8,
+    pub license: LicenseType,
     pub type1: u8,
     pub type2: u8,
     #[max_len(128)]
@@ -20,3 +20,92 @@ impl AiModel {
     pub const NAME_MAX_LENGTH: usize = 50;
     pub const TAGS_MAX_LENGTH: usize = 128;
 }
+
+/// License of a model. The discriminants match the `u8` values stored before the enum existed.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq, InitSpace)]
+pub enum LicenseType {
+    Open,
+    NonCommercial,
+    PaidPerCall,
+    PaidSubscription,
+}
+
+impl LicenseType {
+    pub fn is_paid(&self) -> bool {
+        matches!(self, LicenseType::PaidPerCall | LicenseType::PaidSubscription)
+    }
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct ModelPrice {
+    pub ai_model: Pubkey,
+    pub mint: Pubkey,
+    /// Price per call for per-call licenses, or per subscription period otherwise.
+    pub price: u64,
+    /// Seconds covered by one subscription period.
+    pub subscription_period: i64,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct LicenseReceipt {
+    pub ai_model: Pubkey,
+    pub buyer: Pubkey,
+    pub license: LicenseType,
+    pub mint: Pubkey,
+    pub total_paid: u64,
+    pub calls_remaining: u64,
+    pub expire_time: i64,
+    pub purchase_time: i64,
+}
+
+impl LicenseReceipt {
+    /// Returns whether the receipt currently grants use of the model.
+    pub fn is_valid(&self, license: LicenseType, now: i64) -> bool {
+        if self.license != license {
+            return false;
+        }
+        match license {
+            LicenseType::PaidPerCall => self.calls_remaining > 0,
+            LicenseType::PaidSubscription => now < self.expire_time,
+            LicenseType::Open | LicenseType::NonCommercial => true,
+        }
+    }
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct ModelVersion {
+    pub ai_model: Pubkey,
+    pub major: u16,
+    pub minor: u16,
+    pub patch: u16,
+    pub content_hash: [u8; 32],
+    #[max_len(200)]
+    pub uri: String,
+    pub size: u64,
+    #[max_len(512)]
+    pub changelog: String,
+    pub create_time: i64,
+}
+
+impl ModelVersion {
+    pub const URI_MAX_LENGTH: usize = 200;
+    pub const CHANGELOG_MAX_LENGTH: usize = 512;
+
+    pub fn version(&self) -> String {
+        format!("{}.{}.{}", self.major, self.minor, self.patch)
+    }
+
+    /// Parses a `major.minor.patch` version string.
+    pub fn parse_version(version: &str) -> Option<(u16, u16, u16)> {
+        let mut parts = version.split('.').map(|part| part.parse::<u16>().ok());
+        match (parts.next(), parts.next(), parts.next(), parts.next()) {
+            (Some(Some(major)), Some(Some(minor)), Some(Some(patch)), None) => {
+                Some((major, minor, patch))
+            }
+            _ => None,
+        }
+    }
+}
@@ -1,2 +1,3 @@
 pub mod pattern_recognition;
 pub mod neural_network;
+pub mod ai_model;
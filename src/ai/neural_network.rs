@@ -1,6 +1,8 @@
 use rand::Rng;
 use serde::{Serialize, Deserialize};
+use solana_sdk::hash::hash;
 use log::{info, error};
+use crate::ai::ai_model::ModelVersion;
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct NeuralPrediction {
@@ -8,28 +10,127 @@ pub struct NeuralPrediction {
     pub confidence: f64,
 }
 
-pub struct NeuralNetwork;
+/// Trend model stored as JSON in a model artifact. The first rule whose keyword appears in the
+/// input decides the predicted trend.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct TrendModel {
+    pub rules: Vec<TrendRule>,
+}
 
-impl NeuralNetwork {
-    pub fn run_neural_network(data: &str) -> Result<NeuralPrediction, String> {
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct TrendRule {
+    pub keyword: String,
+    pub trend: String,
+}
+
+impl TrendModel {
+    /// Model used when no model version is pinned.
+    pub fn builtin() -> Self {
+        TrendModel {
+            rules: vec![
+                TrendRule {
+                    keyword: "bullish trend".to_string(),
+                    trend: "Bullish".to_string(),
+                },
+                TrendRule {
+                    keyword: "bearish trend".to_string(),
+                    trend: "Bearish".to_string(),
+                },
+            ],
+        }
+    }
+
+    pub fn from_artifact(artifact: &[u8]) -> Result<Self, String> {
+        serde_json::from_slice(artifact).map_err(|e| format!("Invalid model artifact: {}", e))
+    }
+
+    pub fn predict(&self, data: &str) -> Result<NeuralPrediction, String> {
         info!("Running neural network on data: {}", data);
 
         let mut rng = rand::thread_rng();
         let confidence: f64 = rng.gen_range(0.7..1.0);
 
-        if data.contains("bullish trend") {
-            Ok(NeuralPrediction {
-                predicted_trend: "Bullish".to_string(),
-                confidence,
-            })
-        } else if data.contains("bearish trend") {
-            Ok(NeuralPrediction {
-                predicted_trend: "Bearish".to_string(),
+        match self.rules.iter().find(|rule| data.contains(&rule.keyword)) {
+            Some(rule) => Ok(NeuralPrediction {
+                predicted_trend: rule.trend.clone(),
                 confidence,
-            })
-        } else {
-            error!("Could not predict trend for data: {}", data);
-            Err("Unable to predict trend".to_string())
+            }),
+            None => {
+                error!("Could not predict trend for data: {}", data);
+                Err("Unable to predict trend".to_string())
+            }
+        }
+    }
+}
+
+pub struct NeuralNetwork;
+
+impl NeuralNetwork {
+    /// Downloads the weights of a pinned model version and verifies them against the chain.
+    ///
+    /// The body is streamed and the download is aborted as soon as it exceeds the on-chain size.
+    pub async fn load_artifact(version: &ModelVersion) -> Result<Vec<u8>, String> {
+        info!("Downloading model version {} from {}", version.version(), version.uri);
+
+        let mut response = reqwest::get(version.uri.as_str())
+            .await
+            .map_err(|e| format!("Error downloading model artifact: {}", e))?;
+        if response.content_length().map_or(false, |length| length > version.size) {
+            return Err(format!(
+                "Artifact is larger than on-chain size {}",
+                version.size
+            ));
+        }
+
+        let mut artifact = Vec::with_capacity(version.size.min(64 * 1024 * 1024) as usize);
+        while let Some(chunk) = response
+            .chunk()
+            .await
+            .map_err(|e| format!("Error reading model artifact: {}", e))?
+        {
+            if (artifact.len() + chunk.len()) as u64 > version.size {
+                error!("Model artifact for version {} exceeds its size", version.version());
+                return Err(format!(
+                    "Artifact is larger than on-chain size {}",
+                    version.size
+                ));
+            }
+            artifact.extend_from_slice(&chunk);
+        }
+
+        Self::verify_artifact(&artifact, version)?;
+        Ok(artifact)
+    }
+
+    /// Checks that an artifact matches the size and sha256 recorded for its model version.
+    pub fn verify_artifact(artifact: &[u8], version: &ModelVersion) -> Result<(), String> {
+        if artifact.len() as u64 != version.size {
+            error!("Model artifact size mismatch for version {}", version.version());
+            return Err(format!(
+                "Artifact size {} does not match on-chain size {}",
+                artifact.len(),
+                version.size
+            ));
         }
+        if hash(artifact).to_bytes() != version.content_hash {
+            error!("Model artifact hash mismatch for version {}", version.version());
+            return Err("Artifact hash does not match on-chain content hash".to_string());
+        }
+        Ok(())
+    }
+
+    /// Runs inference with the model in a pinned version's artifact, refusing artifacts that fail
+    /// verification.
+    pub fn run_verified(
+        artifact: &[u8],
+        version: &ModelVersion,
+        data: &str,
+    ) -> Result<NeuralPrediction, String> {
+        Self::verify_artifact(artifact, version)?;
+        TrendModel::from_artifact(artifact)?.predict(data)
+    }
+
+    pub fn run_neural_network(data: &str) -> Result<NeuralPrediction, String> {
+        TrendModel::builtin().predict(data)
     }
 }
@@ -1,9 +1,15 @@
 use actix_web::{web, HttpResponse};
 use crate::ai::pattern_recognition::PatternRecognition;
+use crate::ai::ai_model::ModelVersion;
 use crate::ai::neural_network::NeuralNetwork;
-use crate::api::models::{AnalyzeRequest, AnalyzeResponse, PatternResponse, MarketResponse};
+use crate::api::models::{AnalyzeRequest, AnalyzeResponse, PatternResponse, MarketResponse, InvokeModelRequest, ErrorResponse};
 use crate::services::market::MarketAnalysis;
 use crate::services::market::{TradeVolume, WhaleTransaction};
+use crate::services::licensing::{LicenseChecker, LicenseError};
+use crate::services::machine_index::{MachineIndex, MachineQuery};
+use crate::services::model_registry::{ModelQuery, ModelRegistry};
+use solana_sdk::pubkey::Pubkey;
+use std::str::FromStr;
 use uuid::Uuid;
 use rand::Rng;
 
@@ -101,3 +107,73 @@ pub async fn market_handler() -> impl Responder {
 
     HttpResponse::Ok().json(response)
 }
+
+pub async fn models_handler(
+    registry: web::Data<ModelRegistry>,
+    query: web::Query<ModelQuery>,
+) -> HttpResponse {
+    HttpResponse::Ok().json(registry.search(&query))
+}
+
+pub async fn machines_handler(
+    index: web::Data<MachineIndex>,
+    query: web::Query<MachineQuery>,
+) -> HttpResponse {
+    HttpResponse::Ok().json(index.search(&query, chrono::Utc::now().timestamp()))
+}
+
+pub async fn invoke_model_handler(
+    checker: web::Data<LicenseChecker>,
+    path: web::Path<String>,
+    req: web::Json<InvokeModelRequest>,
+) -> HttpResponse {
+    let ai_model = match Pubkey::from_str(&path.into_inner()) {
+        Ok(ai_model) => ai_model,
+        Err(_) => return HttpResponse::BadRequest().json(ErrorResponse::new("InvalidRequest", "Invalid model address")),
+    };
+    let version = match ModelVersion::parse_version(&req.version) {
+        Some(version) => version,
+        None => return HttpResponse::BadRequest().json(ErrorResponse::new("InvalidRequest", "Invalid model version")),
+    };
+    let now = chrono::Utc::now().timestamp();
+    let wallet = match checker.authenticate(&ai_model, &req.wallet, req.timestamp, &req.signature, now) {
+        Ok(wallet) => wallet,
+        Err(LicenseError::InvalidRequest(err)) => return HttpResponse::BadRequest().json(ErrorResponse::new("InvalidRequest", err)),
+        Err(_) => return HttpResponse::Unauthorized().json(ErrorResponse::new("Unauthenticated", "Invalid or expired signature")),
+    };
+
+    // The pinned version is looked up before a per-call license is charged
+    let checked = web::block(move || -> Result<ModelVersion, LicenseError> {
+        let model_version = checker.fetch_version(&ai_model, version)?;
+        checker.check(&ai_model, &wallet, now)?;
+        Ok(model_version)
+    })
+    .await;
+    let model_version = match checked {
+        Ok(Ok(model_version)) => model_version,
+        Ok(Err(LicenseError::ModelNotFound)) => return HttpResponse::NotFound().json(ErrorResponse::new("ModelNotFound", "Model not found")),
+        Ok(Err(LicenseError::VersionNotFound)) => {
+            return HttpResponse::NotFound().json(ErrorResponse::new("VersionNotFound", "Model version not found"))
+        }
+        Ok(Err(LicenseError::LicenseRequired)) => {
+            return HttpResponse::Forbidden().json(ErrorResponse::new(
+                "LicenseRequired",
+                "A valid license is required to invoke this model",
+            ))
+        }
+        Ok(Err(LicenseError::Metering(err))) => return HttpResponse::ServiceUnavailable().json(ErrorResponse::new("MeteringFailed", err)),
+        Ok(Err(LicenseError::Transaction(failure))) => return HttpResponse::ServiceUnavailable().json(ErrorResponse::from(&failure)),
+        Ok(Err(err)) => return HttpResponse::InternalServerError().json(ErrorResponse::new("Internal", format!("{:?}", err))),
+        Err(err) => return HttpResponse::InternalServerError().json(ErrorResponse::new("Internal", err.to_string())),
+    };
+
+    // Artifacts that do not match the on-chain size and hash are never run
+    let artifact = match NeuralNetwork::load_artifact(&model_version).await {
+        Ok(artifact) => artifact,
+        Err(err) => return HttpResponse::BadGateway().json(ErrorResponse::new("InvalidArtifact", err)),
+    };
+    match NeuralNetwork::run_verified(&artifact, &model_version, &req.data) {
+        Ok(prediction) => HttpResponse::Ok().json(prediction),
+        Err(err) => HttpResponse::InternalServerError().json(ErrorResponse::new("Internal", err)),
+    }
+}
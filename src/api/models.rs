@@ -1,4 +1,5 @@
 use serde::{Deserialize, Serialize};
+use crate::client::errors::TransactionFailure;
 
 #[derive(Serialize, Deserialize)]
 pub struct AnalyzeRequest {
@@ -24,3 +25,41 @@ pub struct MarketResponse {
     pub volume: f64,
     pub significant_address: String,
 }
+
+#[derive(Serialize, Deserialize)]
+pub struct InvokeModelRequest {
+    pub wallet: String,
+    pub timestamp: i64,
+    pub signature: String,
+    /// Model version to run, as `major.minor.patch`.
+    pub version: String,
+    pub data: String,
+}
+
+/// Body of every error response. `code` is set when the error came from the program.
+#[derive(Serialize, Deserialize)]
+pub struct ErrorResponse {
+    pub code: Option<u32>,
+    pub error: String,
+    pub message: String,
+}
+
+impl ErrorResponse {
+    pub fn new(error: &str, message: impl Into<String>) -> Self {
+        ErrorResponse {
+            code: None,
+            error: error.to_string(),
+            message: message.into(),
+        }
+    }
+}
+
+impl From<&TransactionFailure> for ErrorResponse {
+    fn from(failure: &TransactionFailure) -> Self {
+        ErrorResponse {
+            code: failure.code(),
+            error: failure.name().unwrap_or_else(|| "TransactionFailed".to_string()),
+            message: failure.to_string(),
+        }
+    }
+}
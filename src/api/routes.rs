@@ -1,6 +1,7 @@
 use actix_web::web;
 use crate::api::handlers::{
-    analyze_handler, pattern_handler, market_handler,
+    analyze_handler, pattern_handler, market_handler, models_handler, invoke_model_handler,
+    machines_handler,
 };
 
 pub fn configure_routes(cfg: &mut web::ServiceConfig) {
@@ -8,6 +9,9 @@ pub fn configure_routes(cfg: &mut web::ServiceConfig) {
         web::scope("/api")
             .route("/analyze", web::post().to(analyze_handler))
             .route("/pattern", web::get().to(pattern_handler))
-            .route("/market", web::get().to(market_handler)),
+            .route("/market", web::get().to(market_handler))
+            .route("/v1/models", web::get().to(models_handler))
+            .route("/v1/models/{address}/invoke", web::post().to(invoke_model_handler))
+            .route("/v1/machines", web::get().to(machines_handler)),
     );
 }
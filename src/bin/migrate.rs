@@ -0,0 +1,142 @@
+//! Runs a program data migration step, see `migration.md`.
+//!
+//! ```text
+//! migrate <machine-new|machine-rename|order-new|order-rename>
+//!     [--url <rpc url>] [--keypair <path>] [--concurrency <n>] [--checkpoint <path>] [--dry-run]
+//!     [--allow-status-change]
+//! ```
+//!
+//! Against a local validator: `migrate machine-new --url http://127.0.0.1:8899 --dry-run`.
+
+use log::error;
+use lyzerai_core::client::migration::{
+    run, Checkpoint, MachineNewMigration, MachineRenameMigration, MigrationStep,
+    OrderNewMigration, OrderRenameMigration, RunOptions,
+};
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::signature::read_keypair_file;
+use std::env;
+use std::path::PathBuf;
+use std::process;
+
+struct Args {
+    step: MigrationStep,
+    url: String,
+    keypair: PathBuf,
+    options: RunOptions,
+}
+
+fn parse_args() -> Result<Args, String> {
+    let mut args = env::args().skip(1);
+    let step: MigrationStep = args
+        .next()
+        .ok_or("Missing migration step")?
+        .parse()?;
+    let home = env::var("HOME").unwrap_or_else(|_| ".".to_string());
+    let mut url = env::var("SOLANA_RPC_URL").unwrap_or_else(|_| "http://127.0.0.1:8899".to_string());
+    let mut keypair = PathBuf::from(format!("{}/.config/solana/id.json", home));
+    let mut options = RunOptions {
+        dry_run: false,
+        allow_status_change: false,
+        concurrency: 8,
+        checkpoint: PathBuf::from(format!("migration-{}.json", step.name())),
+    };
+
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or(format!("Missing value for {}", arg));
+        match arg.as_str() {
+            "--url" => url = value()?,
+            "--keypair" => keypair = PathBuf::from(value()?),
+            "--checkpoint" => options.checkpoint = PathBuf::from(value()?),
+            "--concurrency" => {
+                options.concurrency = value()?
+                    .parse()
+                    .map_err(|e| format!("Invalid concurrency: {}", e))?
+            }
+            "--dry-run" => options.dry_run = true,
+            "--allow-status-change" => options.allow_status_change = true,
+            _ => return Err(format!("Unknown argument: {}", arg)),
+        }
+    }
+    Ok(Args {
+        step,
+        url,
+        keypair,
+        options,
+    })
+}
+
+fn main() {
+    env_logger::init();
+    let args = match parse_args() {
+        Ok(args) => args,
+        Err(e) => {
+            eprintln!("{}", e);
+            eprintln!(
+                "Usage: migrate <machine-new|machine-rename|order-new|order-rename> \
+                 [--url <url>] [--keypair <path>] [--concurrency <n>] [--checkpoint <path>] [--dry-run] [--allow-status-change]"
+            );
+            process::exit(2);
+        }
+    };
+    let payer = match read_keypair_file(&args.keypair) {
+        Ok(payer) => payer,
+        Err(e) => {
+            error!("Error reading keypair {}: {}", args.keypair.display(), e);
+            process::exit(1);
+        }
+    };
+    let client = RpcClient::new_with_commitment(args.url.clone(), CommitmentConfig::confirmed());
+
+    let result = Checkpoint::load(&args.options.checkpoint, args.step).and_then(|mut checkpoint| {
+        match args.step {
+            MigrationStep::MachineNew => {
+                run::<MachineNewMigration>(&client, &payer, &args.options, &mut checkpoint)
+            }
+            MigrationStep::MachineRename => {
+                run::<MachineRenameMigration>(&client, &payer, &args.options, &mut checkpoint)
+            }
+            MigrationStep::OrderNew => {
+                run::<OrderNewMigration>(&client, &payer, &args.options, &mut checkpoint)
+            }
+            MigrationStep::OrderRename => {
+                run::<OrderRenameMigration>(&client, &payer, &args.options, &mut checkpoint)
+            }
+        }
+    });
+
+    let report = match result {
+        Ok(report) => report,
+        Err(e) => {
+            error!("{}", e);
+            process::exit(1);
+        }
+    };
+
+    for (pubkey, diffs) in &report.planned {
+        if diffs.is_empty() {
+            println!("{}: unchanged", pubkey);
+            continue;
+        }
+        println!("{}:", pubkey);
+        for diff in diffs {
+            println!("  {}: {} -> {}", diff.field, diff.before, diff.after);
+        }
+    }
+    println!(
+        "{}: {} accounts, {} already migrated, {} migrated, {} failed{}",
+        args.step.name(),
+        report.total,
+        report.skipped,
+        report.migrated,
+        report.failed.len(),
+        if args.options.dry_run { " (dry run)" } else { "" }
+    );
+    for (pubkey, e) in &report.failed {
+        println!("  {}: {}", pubkey, e);
+    }
+    if !report.failed.is_empty() {
+        process::exit(1);
+    }
+}
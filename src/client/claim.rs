@@ -0,0 +1,122 @@
+use anchor_lang::AnchorSerialize;
+use anchor_spl::associated_token::{self, get_associated_token_address};
+use anchor_spl::token;
+use solana_sdk::hash::hash;
+use solana_sdk::instruction::{AccountMeta, Instruction};
+use solana_sdk::message::Message;
+use solana_sdk::packet::PACKET_DATA_SIZE;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::system_program;
+use crate::client::protocol_config::protocol_config_pda;
+use crate::client::reward::{reward_ledger_pda, reward_pool_pda};
+use crate::client::vesting::vesting_config_pda;
+use crate::utils::reward::ClaimEntry;
+
+/// A machine reward to claim for one period.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClaimTarget {
+    pub period: u32,
+    pub machine: Pubkey,
+    pub machine_id: [u8; 16],
+}
+
+pub fn reward_period_pda(period: u32) -> Pubkey {
+    Pubkey::find_program_address(&[b"reward-period", period.to_le_bytes().as_ref()], &crate::ID).0
+}
+
+pub fn reward_work_pda(period: u32, owner: &Pubkey, machine_id: &[u8; 16]) -> Pubkey {
+    Pubkey::find_program_address(
+        &[b"reward-work", period.to_le_bytes().as_ref(), owner.as_ref(), machine_id.as_ref()],
+        &crate::ID,
+    )
+    .0
+}
+
+/// Builds a `claim_batch` instruction. Targets must be sorted by period, with at most `u8::MAX`
+/// machines per period; `pack_claims` splits larger claims across instructions.
+pub fn claim_batch_instruction(owner: &Pubkey, mint: &Pubkey, targets: &[ClaimTarget]) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new_readonly(protocol_config_pda(), false),
+        AccountMeta::new(reward_ledger_pda(), false),
+        AccountMeta::new_readonly(vesting_config_pda(), false),
+        AccountMeta::new(*owner, true),
+        AccountMeta::new(get_associated_token_address(owner, mint), false),
+        AccountMeta::new(reward_pool_pda(mint), false),
+        AccountMeta::new_readonly(*mint, false),
+        AccountMeta::new_readonly(token::ID, false),
+        AccountMeta::new_readonly(associated_token::ID, false),
+        AccountMeta::new_readonly(system_program::ID, false),
+    ];
+    let mut entries: Vec<ClaimEntry> = Vec::new();
+    for target in targets {
+        match entries.last_mut() {
+            Some(entry) if entry.period == target.period => {
+                entry.machines = entry
+                    .machines
+                    .checked_add(1)
+                    .expect("A claim_batch instruction holds at most 255 machines per period");
+            }
+            _ => {
+                entries.push(ClaimEntry {
+                    period: target.period,
+                    machines: 1,
+                });
+                accounts.push(AccountMeta::new(reward_period_pda(target.period), false));
+            }
+        }
+        accounts.push(AccountMeta::new(target.machine, false));
+        accounts.push(AccountMeta::new(
+            reward_work_pda(target.period, owner, &target.machine_id),
+            false,
+        ));
+    }
+
+    let mut data = hash(b"global:claim_batch").to_bytes()[..8].to_vec();
+    entries
+        .serialize(&mut data)
+        .expect("Serializing claim entries cannot fail");
+    Instruction {
+        program_id: crate::ID,
+        accounts,
+        data,
+    }
+}
+
+/// Size in bytes of a transaction signed by `owner` alone that carries `instruction`.
+pub fn transaction_size(owner: &Pubkey, instruction: &Instruction) -> usize {
+    let message = Message::new(&[instruction.clone()], Some(owner));
+    // Compact signature count followed by a single signature.
+    1 + 64 + message.serialize().len()
+}
+
+/// Splits claim targets into `claim_batch` instructions that each fit in one transaction.
+///
+/// Targets are sorted by period and deduplicated; each returned instruction carries as many
+/// consecutive targets as fit within the transaction size limit, and at most `u8::MAX` machines
+/// of any one period, since `claim_batch` takes each period once.
+pub fn pack_claims(owner: &Pubkey, mint: &Pubkey, targets: &[ClaimTarget]) -> Vec<Instruction> {
+    let mut targets = targets.to_vec();
+    targets.sort_by(|a, b| (a.period, a.machine).cmp(&(b.period, b.machine)));
+    targets.dedup();
+
+    let mut instructions = Vec::new();
+    let mut batch: Vec<ClaimTarget> = Vec::new();
+    for target in targets {
+        let period_machines = batch.iter().filter(|batched| batched.period == target.period).count();
+        if period_machines == u8::MAX as usize {
+            instructions.push(claim_batch_instruction(owner, mint, &batch));
+            batch.clear();
+        }
+        batch.push(target);
+        let instruction = claim_batch_instruction(owner, mint, &batch);
+        if transaction_size(owner, &instruction) > PACKET_DATA_SIZE && batch.len() > 1 {
+            let target = batch.pop().expect("Batch holds at least two targets");
+            instructions.push(claim_batch_instruction(owner, mint, &batch));
+            batch = vec![target];
+        }
+    }
+    if !batch.is_empty() {
+        instructions.push(claim_batch_instruction(owner, mint, &batch));
+    }
+    instructions
+}
@@ -0,0 +1,130 @@
+use anchor_lang::error::ERROR_CODE_OFFSET;
+use solana_client::client_error::ClientError;
+use solana_sdk::instruction::InstructionError;
+use solana_sdk::transaction::TransactionError;
+use std::fmt;
+use crate::errors::DistriAIError;
+
+/// Every program error in declaration order, so the error with code `c` is at
+/// `c - ERROR_CODE_OFFSET`. New variants of `DistriAIError` must be appended here as well, which
+/// `tests/program_errors.rs` checks against `src/errors.rs`.
+pub const PROGRAM_ERRORS: [DistriAIError; 38] = [
+    DistriAIError::StringTooLong,
+    DistriAIError::IncorrectStatus,
+    DistriAIError::DurationTooMuch,
+    DistriAIError::InvalidPeriod,
+    DistriAIError::RepeatClaim,
+    DistriAIError::InsufficientStake,
+    DistriAIError::StakeLocked,
+    DistriAIError::Unauthorized,
+    DistriAIError::QueueNotEmpty,
+    DistriAIError::NotQueueHead,
+    DistriAIError::QueueExpired,
+    DistriAIError::MintMismatch,
+    DistriAIError::MintNotAccepted,
+    DistriAIError::ResultHashMismatch,
+    DistriAIError::NoRewardWork,
+    DistriAIError::PeriodNotClosed,
+    DistriAIError::PeriodClosed,
+    DistriAIError::PeriodNotExpired,
+    DistriAIError::ClaimExpired,
+    DistriAIError::PeriodPoolExhausted,
+    DistriAIError::VestingRequired,
+    DistriAIError::VestingDisabled,
+    DistriAIError::NothingVested,
+    DistriAIError::AccelerationNotEligible,
+    DistriAIError::InvalidBatch,
+    DistriAIError::InvalidVersion,
+    DistriAIError::LicenseNotPaid,
+    DistriAIError::InvalidQuantity,
+    DistriAIError::MathOverflow,
+    DistriAIError::StatusChangeRefused,
+    DistriAIError::MachineOffline,
+    DistriAIError::InvalidConfig,
+    DistriAIError::Paused,
+    DistriAIError::OrderNotCancelable,
+    DistriAIError::DurationUsedUp,
+    DistriAIError::OrderNotEnded,
+    DistriAIError::QueueNotExpired,
+    DistriAIError::InvalidScore,
+];
+
+/// Returns the program error with the given custom error code.
+pub fn program_error(code: u32) -> Option<DistriAIError> {
+    let index = code.checked_sub(ERROR_CODE_OFFSET)?;
+    PROGRAM_ERRORS.get(index as usize).copied()
+}
+
+/// Reason a transaction sent to the program failed.
+#[derive(Debug, Clone)]
+pub enum TransactionFailure {
+    /// An instruction failed with one of the program's own errors.
+    Program { instruction: u8, error: DistriAIError },
+    /// An instruction failed with another custom code, such as an Anchor constraint error.
+    Custom { instruction: u8, code: u32 },
+    /// The transaction failed for any other reason.
+    Other(String),
+}
+
+impl TransactionFailure {
+    pub fn from_transaction_error(error: &TransactionError) -> Self {
+        match error {
+            TransactionError::InstructionError(instruction, InstructionError::Custom(code)) => {
+                match program_error(*code) {
+                    Some(error) => TransactionFailure::Program {
+                        instruction: *instruction,
+                        error,
+                    },
+                    None => TransactionFailure::Custom {
+                        instruction: *instruction,
+                        code: *code,
+                    },
+                }
+            }
+            error => TransactionFailure::Other(error.to_string()),
+        }
+    }
+
+    pub fn from_client_error(error: &ClientError) -> Self {
+        match error.get_transaction_error() {
+            Some(transaction_error) => Self::from_transaction_error(&transaction_error),
+            None => TransactionFailure::Other(error.to_string()),
+        }
+    }
+
+    /// Custom error code of the failed instruction, if any.
+    pub fn code(&self) -> Option<u32> {
+        match self {
+            TransactionFailure::Program { error, .. } => Some(u32::from(*error)),
+            TransactionFailure::Custom { code, .. } => Some(*code),
+            TransactionFailure::Other(_) => None,
+        }
+    }
+
+    /// Name of the program error, e.g. `OrderNotCancelable`.
+    pub fn name(&self) -> Option<String> {
+        match self {
+            TransactionFailure::Program { error, .. } => Some(format!("{:?}", error)),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for TransactionFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TransactionFailure::Program { instruction, error } => write!(
+                f,
+                "Instruction {} failed with {:?} ({}): {}",
+                instruction,
+                error,
+                u32::from(*error),
+                error
+            ),
+            TransactionFailure::Custom { instruction, code } => {
+                write!(f, "Instruction {} failed with custom error {}", instruction, code)
+            }
+            TransactionFailure::Other(error) => write!(f, "{}", error),
+        }
+    }
+}
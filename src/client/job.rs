@@ -0,0 +1,55 @@
+use anchor_lang::{AccountDeserialize, Discriminator};
+use solana_client::rpc_client::RpcClient;
+use solana_client::rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig};
+use solana_client::rpc_filter::{Memcmp, RpcFilterType};
+use solana_sdk::pubkey::Pubkey;
+use crate::state::job::JobBinding;
+
+/// PDA binding an order or task account to a model.
+pub fn job_binding_pda(job: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"job-binding", job.as_ref()], &crate::ID).0
+}
+
+/// Returns the model binding of an order or task, or `None` if it runs no registered model.
+pub fn fetch_job_binding(client: &RpcClient, job: &Pubkey) -> Result<Option<JobBinding>, String> {
+    let account = match client
+        .get_account_with_commitment(&job_binding_pda(job), client.commitment())
+        .map_err(|e| format!("Error fetching job binding: {}", e))?
+        .value
+    {
+        Some(account) => account,
+        None => return Ok(None),
+    };
+    JobBinding::try_deserialize(&mut account.data.as_slice())
+        .map(Some)
+        .map_err(|e| format!("Invalid job binding account: {}", e))
+}
+
+/// Returns every job run for a model, most recent first.
+pub fn jobs_for_model(client: &RpcClient, ai_model: &Pubkey) -> Result<Vec<JobBinding>, String> {
+    let config = RpcProgramAccountsConfig {
+        filters: Some(vec![
+            RpcFilterType::Memcmp(Memcmp::new_base58_encoded(0, &JobBinding::DISCRIMINATOR)),
+            RpcFilterType::Memcmp(Memcmp::new_base58_encoded(
+                JobBinding::AI_MODEL_OFFSET,
+                ai_model.as_ref(),
+            )),
+        ]),
+        account_config: RpcAccountInfoConfig {
+            commitment: Some(client.commitment()),
+            ..RpcAccountInfoConfig::default()
+        },
+        ..RpcProgramAccountsConfig::default()
+    };
+    let accounts = client
+        .get_program_accounts_with_config(&crate::ID, config)
+        .map_err(|e| format!("Error fetching jobs: {}", e))?;
+    let mut jobs = Vec::with_capacity(accounts.len());
+    for (_, account) in accounts {
+        let job = JobBinding::try_deserialize(&mut account.data.as_slice())
+            .map_err(|e| format!("Invalid job binding account: {}", e))?;
+        jobs.push(job);
+    }
+    jobs.sort_by_key(|job| std::cmp::Reverse(job.bind_time));
+    Ok(jobs)
+}
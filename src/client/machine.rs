@@ -0,0 +1,215 @@
+use anchor_lang::{AccountDeserialize, AnchorSerialize, Discriminator};
+use solana_client::rpc_client::RpcClient;
+use solana_client::rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig};
+use solana_client::rpc_filter::{Memcmp, RpcFilterType};
+use solana_sdk::hash::hash;
+use solana_sdk::instruction::{AccountMeta, Instruction};
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::system_program;
+use crate::client::protocol_config::protocol_config_pda;
+use crate::client::queue::queue_pda;
+use crate::state::machine::Machine;
+use crate::state::machine_spec::HardwareSpec;
+use crate::state::payment::MachinePrice;
+
+pub fn machine_pda(owner: &Pubkey, uuid: &[u8; 16]) -> Pubkey {
+    Pubkey::find_program_address(&[b"machine", owner.as_ref(), uuid.as_ref()], &crate::ID).0
+}
+
+pub fn stake_pda(owner: &Pubkey, uuid: &[u8; 16]) -> Pubkey {
+    Pubkey::find_program_address(&[b"stake", owner.as_ref(), uuid.as_ref()], &crate::ID).0
+}
+
+pub fn machine_spec_pda(machine: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"machine-spec", machine.as_ref()], &crate::ID).0
+}
+
+pub fn heartbeat_pda(machine: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"heartbeat", machine.as_ref()], &crate::ID).0
+}
+
+pub fn machine_history_pda(machine: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"machine-history", machine.as_ref()], &crate::ID).0
+}
+
+pub fn machine_price_pda(machine: &Pubkey, mint: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[b"machine-price", machine.as_ref(), mint.as_ref()],
+        &crate::ID,
+    )
+    .0
+}
+
+fn instruction(name: &str, args: impl AnchorSerialize, accounts: Vec<AccountMeta>) -> Instruction {
+    let mut data = hash(format!("global:{}", name).as_bytes()).to_bytes()[..8].to_vec();
+    args.serialize(&mut data)
+        .expect("Serializing instruction arguments cannot fail");
+    Instruction {
+        program_id: crate::ID,
+        accounts,
+        data,
+    }
+}
+
+/// Builds an `add_machine` instruction registering a machine under `owner`.
+pub fn add_machine_instruction(owner: &Pubkey, uuid: [u8; 16], metadata: String) -> Instruction {
+    instruction(
+        "add_machine",
+        (uuid, metadata),
+        vec![
+            AccountMeta::new(machine_pda(owner, &uuid), false),
+            AccountMeta::new(*owner, true),
+            AccountMeta::new_readonly(system_program::ID, false),
+        ],
+    )
+}
+
+/// Builds an `update_machine` instruction replacing the machine metadata.
+pub fn update_machine_instruction(owner: &Pubkey, uuid: &[u8; 16], metadata: String) -> Instruction {
+    instruction(
+        "update_machine",
+        metadata,
+        vec![
+            AccountMeta::new(machine_pda(owner, uuid), false),
+            AccountMeta::new_readonly(*owner, true),
+        ],
+    )
+}
+
+/// Builds a `make_offer` instruction putting the machine up for rent.
+pub fn make_offer_instruction(
+    owner: &Pubkey,
+    uuid: &[u8; 16],
+    price: u64,
+    max_duration: u32,
+    disk: u32,
+) -> Instruction {
+    instruction(
+        "make_offer",
+        (price, max_duration, disk),
+        vec![
+            AccountMeta::new(machine_pda(owner, uuid), false),
+            AccountMeta::new_readonly(stake_pda(owner, uuid), false),
+            AccountMeta::new_readonly(*owner, true),
+        ],
+    )
+}
+
+/// Builds a `cancel_offer` instruction taking the machine off the market.
+pub fn cancel_offer_instruction(owner: &Pubkey, uuid: &[u8; 16]) -> Instruction {
+    instruction(
+        "cancel_offer",
+        (),
+        vec![
+            AccountMeta::new(machine_pda(owner, uuid), false),
+            AccountMeta::new_readonly(*owner, true),
+        ],
+    )
+}
+
+/// Builds a `remove_machine` instruction closing an idle, unstaked machine with an empty queue
+/// together with its spec, heartbeat and the prices of the given payment mints, see
+/// `machine_price_mints`.
+pub fn remove_machine_instruction(owner: &Pubkey, uuid: &[u8; 16], price_mints: &[Pubkey]) -> Instruction {
+    let machine = machine_pda(owner, uuid);
+    let mut accounts = vec![
+        AccountMeta::new(machine, false),
+        AccountMeta::new_readonly(stake_pda(owner, uuid), false),
+        AccountMeta::new(machine_spec_pda(&machine), false),
+        AccountMeta::new(heartbeat_pda(&machine), false),
+        AccountMeta::new_readonly(queue_pda(&machine), false),
+        AccountMeta::new(*owner, true),
+    ];
+    accounts.extend(
+        price_mints
+            .iter()
+            .map(|mint| AccountMeta::new(machine_price_pda(&machine, mint), false)),
+    );
+    instruction("remove_machine", (), accounts)
+}
+
+/// Builds a `set_machine_spec` instruction publishing the hardware spec of a machine.
+pub fn set_machine_spec_instruction(owner: &Pubkey, uuid: &[u8; 16], spec: HardwareSpec) -> Instruction {
+    let machine = machine_pda(owner, uuid);
+    instruction(
+        "set_machine_spec",
+        spec,
+        vec![
+            AccountMeta::new_readonly(machine, false),
+            AccountMeta::new(machine_spec_pda(&machine), false),
+            AccountMeta::new(*owner, true),
+            AccountMeta::new_readonly(system_program::ID, false),
+        ],
+    )
+}
+
+/// Builds a `heartbeat` instruction reporting the machine as alive.
+pub fn heartbeat_instruction(owner: &Pubkey, uuid: &[u8; 16]) -> Instruction {
+    let machine = machine_pda(owner, uuid);
+    instruction(
+        "heartbeat",
+        (),
+        vec![
+            AccountMeta::new_readonly(protocol_config_pda(), false),
+            AccountMeta::new_readonly(machine, false),
+            AccountMeta::new(heartbeat_pda(&machine), false),
+            AccountMeta::new(*owner, true),
+            AccountMeta::new_readonly(system_program::ID, false),
+        ],
+    )
+}
+
+/// Returns the machines registered by `owner`.
+pub fn machines_of(client: &RpcClient, owner: &Pubkey) -> Result<Vec<(Pubkey, Machine)>, String> {
+    let config = RpcProgramAccountsConfig {
+        filters: Some(vec![
+            RpcFilterType::Memcmp(Memcmp::new_base58_encoded(0, &Machine::DISCRIMINATOR)),
+            RpcFilterType::Memcmp(Memcmp::new_base58_encoded(8, owner.as_ref())),
+        ]),
+        account_config: RpcAccountInfoConfig {
+            commitment: Some(client.commitment()),
+            ..RpcAccountInfoConfig::default()
+        },
+        ..RpcProgramAccountsConfig::default()
+    };
+    let accounts = client
+        .get_program_accounts_with_config(&crate::ID, config)
+        .map_err(|e| format!("Error fetching machines: {}", e))?;
+    let mut machines = Vec::with_capacity(accounts.len());
+    for (pubkey, account) in accounts {
+        let machine = Machine::try_deserialize(&mut account.data.as_slice())
+            .map_err(|e| format!("Invalid machine account: {}", e))?;
+        machines.push((pubkey, machine));
+    }
+    Ok(machines)
+}
+
+/// Returns the payment mints accepted by a machine.
+pub fn machine_price_mints(
+    client: &RpcClient,
+    owner: &Pubkey,
+    uuid: &[u8; 16],
+) -> Result<Vec<Pubkey>, String> {
+    let config = RpcProgramAccountsConfig {
+        filters: Some(vec![
+            RpcFilterType::Memcmp(Memcmp::new_base58_encoded(0, &MachinePrice::DISCRIMINATOR)),
+            RpcFilterType::Memcmp(Memcmp::new_base58_encoded(8, owner.as_ref())),
+            RpcFilterType::Memcmp(Memcmp::new_base58_encoded(40, uuid.as_ref())),
+        ]),
+        account_config: RpcAccountInfoConfig {
+            commitment: Some(client.commitment()),
+            ..RpcAccountInfoConfig::default()
+        },
+        ..RpcProgramAccountsConfig::default()
+    };
+    let accounts = client
+        .get_program_accounts_with_config(&crate::ID, config)
+        .map_err(|e| format!("Error fetching machine prices: {}", e))?;
+    let mut mints = Vec::with_capacity(accounts.len());
+    for (_, account) in accounts {
+        let machine_price = MachinePrice::try_deserialize(&mut account.data.as_slice())
+            .map_err(|e| format!("Invalid machine price account: {}", e))?;
+        mints.push(machine_price.mint);
+    }
+    Ok(mints)
+}
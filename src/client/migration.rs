@@ -0,0 +1,557 @@
+use anchor_lang::{AccountDeserialize, AccountSerialize, Discriminator};
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use solana_client::rpc_client::RpcClient;
+use solana_client::rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig};
+use solana_client::rpc_filter::{Memcmp, RpcFilterType};
+use solana_sdk::hash::hash;
+use solana_sdk::instruction::{AccountMeta, Instruction};
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signer};
+use solana_sdk::system_program;
+use solana_sdk::transaction::Transaction;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use crate::client::errors::TransactionFailure;
+use crate::client::protocol_config::protocol_config_pda;
+use crate::migrate_account;
+use crate::migration::order::order_new_status;
+use crate::state::machine::{Machine, MachineNew};
+use crate::state::order::{Order, OrderNew};
+
+/// A field whose value differs between two versions of an account.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldDiff {
+    pub field: &'static str,
+    pub before: String,
+    pub after: String,
+}
+
+/// Compares the listed fields of two values that share field names.
+macro_rules! diff_fields {
+    ($before:expr, $after:expr, [$($field:ident),* $(,)?]) => {{
+        let mut diffs = Vec::new();
+        $(
+            let before = format!("{:?}", $before.$field);
+            let after = format!("{:?}", $after.$field);
+            if before != after {
+                diffs.push(FieldDiff {
+                    field: stringify!($field),
+                    before,
+                    after,
+                });
+            }
+        )*
+        diffs
+    }};
+}
+
+/// One hop of an account migration, mirroring an on-chain `migrate_*` instruction.
+pub trait AccountMigration {
+    type Before: AccountDeserialize + AccountSerialize + Discriminator;
+    type After: AccountDeserialize;
+
+    /// Name of the program instruction, in snake case.
+    const INSTRUCTION: &'static str;
+
+    /// Whether the instruction takes the admin `allow_status_change` flag.
+    const STATUS_CHANGE_FLAG: bool = false;
+
+    /// Address the migrated account is written to.
+    fn target(before: &Self::Before) -> Pubkey;
+
+    /// The account the instruction is expected to produce.
+    fn expected(before: &Self::Before) -> Self::After;
+
+    /// Fields that the migration changes.
+    fn diff(before: &Self::Before, after: &Self::After) -> Vec<FieldDiff>;
+
+    /// Fields of the migrated account that differ from what was expected.
+    fn verify(expected: &Self::After, actual: &Self::After) -> Vec<FieldDiff>;
+}
+
+/// Passes the fields shared by every version of `Machine` to the given macro.
+macro_rules! machine_fields {
+    ($mac:ident!($($args:tt)*)) => {
+        $mac!($($args)*, [
+            owner, uuid, metadata, status, price, max_duration, disk, completed_count,
+            failed_count, score, claimed_periodic_rewards, claimed_task_rewards, order_pda,
+        ])
+    };
+}
+
+/// Passes the fields shared by every version of `Order` to the given macro.
+macro_rules! order_fields {
+    ($mac:ident!($($args:tt)*)) => {
+        $mac!($($args)*, [
+            order_id, buyer, seller, machine_id, price, duration, total, metadata, status,
+            order_time, start_time, refund_time,
+        ])
+    };
+}
+
+pub struct MachineNewMigration;
+
+impl AccountMigration for MachineNewMigration {
+    type Before = Machine;
+    type After = MachineNew;
+    const INSTRUCTION: &'static str = "migrate_machine_new";
+
+    fn target(before: &Machine) -> Pubkey {
+        pda(&[b"machine-new", before.owner.as_ref(), before.uuid.as_ref()])
+    }
+
+    fn expected(before: &Machine) -> MachineNew {
+        migrate_account!(before, Machine => MachineNew {
+            copy {
+                owner, uuid, metadata, status, price, max_duration, disk, completed_count,
+                failed_count, score, claimed_periodic_rewards, claimed_task_rewards, order_pda,
+            },
+        })
+    }
+
+    fn diff(before: &Machine, after: &MachineNew) -> Vec<FieldDiff> {
+        machine_fields!(diff_fields!(before, after))
+    }
+
+    fn verify(expected: &MachineNew, actual: &MachineNew) -> Vec<FieldDiff> {
+        machine_fields!(diff_fields!(expected, actual))
+    }
+}
+
+pub struct MachineRenameMigration;
+
+impl AccountMigration for MachineRenameMigration {
+    type Before = MachineNew;
+    type After = Machine;
+    const INSTRUCTION: &'static str = "migrate_machine_rename";
+
+    fn target(before: &MachineNew) -> Pubkey {
+        pda(&[b"machine", before.owner.as_ref(), before.uuid.as_ref()])
+    }
+
+    fn expected(before: &MachineNew) -> Machine {
+        migrate_account!(before, MachineNew => Machine {
+            copy {
+                owner, uuid, metadata, status, price, max_duration, disk, completed_count,
+                failed_count, score, claimed_periodic_rewards, claimed_task_rewards, order_pda,
+            },
+        })
+    }
+
+    fn diff(before: &MachineNew, after: &Machine) -> Vec<FieldDiff> {
+        machine_fields!(diff_fields!(before, after))
+    }
+
+    fn verify(expected: &Machine, actual: &Machine) -> Vec<FieldDiff> {
+        machine_fields!(diff_fields!(expected, actual))
+    }
+}
+
+pub struct OrderNewMigration;
+
+impl AccountMigration for OrderNewMigration {
+    type Before = Order;
+    type After = OrderNew;
+    const INSTRUCTION: &'static str = "migrate_order_new";
+    const STATUS_CHANGE_FLAG: bool = true;
+
+    fn target(before: &Order) -> Pubkey {
+        pda(&[b"order-new", before.buyer.as_ref(), before.order_id.as_ref()])
+    }
+
+    fn expected(before: &Order) -> OrderNew {
+        migrate_account!(before, Order => OrderNew {
+            copy {
+                order_id, buyer, seller, machine_id, price, duration, total, metadata,
+                order_time, start_time, refund_time,
+            },
+            map {
+                status: order_new_status(&status),
+            },
+        })
+    }
+
+    fn diff(before: &Order, after: &OrderNew) -> Vec<FieldDiff> {
+        order_fields!(diff_fields!(before, after))
+    }
+
+    fn verify(expected: &OrderNew, actual: &OrderNew) -> Vec<FieldDiff> {
+        order_fields!(diff_fields!(expected, actual))
+    }
+}
+
+pub struct OrderRenameMigration;
+
+impl AccountMigration for OrderRenameMigration {
+    type Before = OrderNew;
+    type After = Order;
+    const INSTRUCTION: &'static str = "migrate_order_rename";
+
+    fn target(before: &OrderNew) -> Pubkey {
+        pda(&[b"order", before.buyer.as_ref(), before.order_id.as_ref()])
+    }
+
+    fn expected(before: &OrderNew) -> Order {
+        migrate_account!(before, OrderNew => Order {
+            copy {
+                order_id, buyer, seller, machine_id, price, duration, total, metadata, status,
+                order_time, start_time, refund_time,
+            },
+        })
+    }
+
+    fn diff(before: &OrderNew, after: &Order) -> Vec<FieldDiff> {
+        order_fields!(diff_fields!(before, after))
+    }
+
+    fn verify(expected: &Order, actual: &Order) -> Vec<FieldDiff> {
+        order_fields!(diff_fields!(expected, actual))
+    }
+}
+
+fn pda(seeds: &[&[u8]]) -> Pubkey {
+    Pubkey::find_program_address(seeds, &crate::ID).0
+}
+
+/// The migration steps, in the order they have to be run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MigrationStep {
+    MachineNew,
+    MachineRename,
+    OrderNew,
+    OrderRename,
+}
+
+impl MigrationStep {
+    pub fn name(&self) -> &'static str {
+        match self {
+            MigrationStep::MachineNew => "machine-new",
+            MigrationStep::MachineRename => "machine-rename",
+            MigrationStep::OrderNew => "order-new",
+            MigrationStep::OrderRename => "order-rename",
+        }
+    }
+}
+
+impl FromStr for MigrationStep {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "machine-new" => Ok(MigrationStep::MachineNew),
+            "machine-rename" => Ok(MigrationStep::MachineRename),
+            "order-new" => Ok(MigrationStep::OrderNew),
+            "order-rename" => Ok(MigrationStep::OrderRename),
+            _ => Err(format!("Unknown migration step: {}", s)),
+        }
+    }
+}
+
+/// Accounts already migrated by a step, persisted so an interrupted run can resume.
+#[derive(Serialize, Deserialize, Debug, Default, PartialEq)]
+pub struct Checkpoint {
+    pub step: String,
+    pub completed: BTreeSet<String>,
+    /// Accounts whose migration landed but could not be read back, by source address. The source
+    /// is closed by the migration, so its data is kept here to verify the target on the next run.
+    #[serde(default)]
+    pub unverified: BTreeMap<String, UnverifiedAccount>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct UnverifiedAccount {
+    pub target: String,
+    /// Source account data, discriminator included.
+    pub before: Vec<u8>,
+}
+
+impl Checkpoint {
+    /// Loads the checkpoint of `step`, starting fresh if the file is missing or for another step.
+    pub fn load(path: &Path, step: MigrationStep) -> Result<Self, String> {
+        let fresh = Checkpoint {
+            step: step.name().to_string(),
+            ..Checkpoint::default()
+        };
+        if !path.exists() {
+            return Ok(fresh);
+        }
+        let data = fs::read_to_string(path)
+            .map_err(|e| format!("Error reading checkpoint {}: {}", path.display(), e))?;
+        let checkpoint: Checkpoint = serde_json::from_str(&data)
+            .map_err(|e| format!("Invalid checkpoint {}: {}", path.display(), e))?;
+        if checkpoint.step != step.name() {
+            warn!(
+                "Checkpoint {} belongs to step {}, starting {} from scratch",
+                path.display(),
+                checkpoint.step,
+                step.name()
+            );
+            return Ok(fresh);
+        }
+        Ok(checkpoint)
+    }
+
+    /// Writes the checkpoint through a temporary file so a crash never leaves it truncated.
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        let data = serde_json::to_string_pretty(self)
+            .map_err(|e| format!("Error serializing checkpoint: {}", e))?;
+        let tmp = PathBuf::from(format!("{}.tmp", path.display()));
+        fs::write(&tmp, data)
+            .map_err(|e| format!("Error writing checkpoint {}: {}", tmp.display(), e))?;
+        fs::rename(&tmp, path)
+            .map_err(|e| format!("Error replacing checkpoint {}: {}", path.display(), e))
+    }
+}
+
+pub struct RunOptions {
+    pub dry_run: bool,
+    /// Lets the admin apply declared status rewrites; must be signed by the admin key.
+    pub allow_status_change: bool,
+    pub concurrency: usize,
+    pub checkpoint: PathBuf,
+}
+
+#[derive(Debug, Default)]
+pub struct MigrationReport {
+    pub total: usize,
+    pub skipped: usize,
+    pub migrated: usize,
+    pub failed: Vec<(Pubkey, String)>,
+    /// Planned changes per account, filled on dry runs.
+    pub planned: Vec<(Pubkey, Vec<FieldDiff>)>,
+}
+
+/// Returns every account of type `T` owned by the program.
+pub fn fetch_accounts<T: AccountDeserialize + Discriminator>(
+    client: &RpcClient,
+) -> Result<Vec<(Pubkey, T)>, String> {
+    let config = RpcProgramAccountsConfig {
+        filters: Some(vec![RpcFilterType::Memcmp(Memcmp::new_base58_encoded(
+            0,
+            &T::DISCRIMINATOR,
+        ))]),
+        account_config: RpcAccountInfoConfig {
+            commitment: Some(client.commitment()),
+            ..RpcAccountInfoConfig::default()
+        },
+        ..RpcProgramAccountsConfig::default()
+    };
+    let accounts = client
+        .get_program_accounts_with_config(&crate::ID, config)
+        .map_err(|e| format!("Error fetching accounts: {}", e))?;
+    accounts
+        .into_iter()
+        .map(|(pubkey, account)| {
+            T::try_deserialize(&mut account.data.as_slice())
+                .map(|value| (pubkey, value))
+                .map_err(|e| format!("Invalid account {}: {}", pubkey, e))
+        })
+        .collect()
+}
+
+pub fn migration_instruction<M: AccountMigration>(
+    source: &Pubkey,
+    before: &M::Before,
+    signer: &Pubkey,
+    allow_status_change: bool,
+) -> Instruction {
+    let preimage = format!("global:{}", M::INSTRUCTION);
+    let mut data = hash(preimage.as_bytes()).to_bytes()[..8].to_vec();
+    if M::STATUS_CHANGE_FLAG {
+        data.push(allow_status_change as u8);
+    }
+    // Migrations are signed by the config authority.
+    let accounts = vec![
+        AccountMeta::new(*source, false),
+        AccountMeta::new(M::target(before), false),
+        AccountMeta::new(*signer, true),
+        AccountMeta::new_readonly(system_program::ID, false),
+        AccountMeta::new_readonly(protocol_config_pda(), false),
+    ];
+    Instruction {
+        program_id: crate::ID,
+        accounts,
+        data,
+    }
+}
+
+/// Why an account could not be migrated.
+enum MigrationFailure {
+    /// The transaction did not land, the source account is untouched.
+    NotSent(String),
+    /// The transaction landed but the target could not be verified.
+    Unverified(String),
+}
+
+fn migrate_one<M: AccountMigration>(
+    client: &RpcClient,
+    payer: &Keypair,
+    options: &RunOptions,
+    source: &Pubkey,
+    before: &M::Before,
+) -> Result<(), MigrationFailure> {
+    let instruction =
+        migration_instruction::<M>(source, before, &payer.pubkey(), options.allow_status_change);
+    let blockhash = client
+        .get_latest_blockhash()
+        .map_err(|e| MigrationFailure::NotSent(format!("Error fetching blockhash: {}", e)))?;
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction],
+        Some(&payer.pubkey()),
+        &[payer],
+        blockhash,
+    );
+    client.send_and_confirm_transaction(&transaction).map_err(|e| {
+        let failure = TransactionFailure::from_client_error(&e);
+        MigrationFailure::NotSent(format!("Error sending migration: {}", failure))
+    })?;
+
+    verify_one::<M>(client, &M::target(before), before).map_err(MigrationFailure::Unverified)
+}
+
+/// Reads a migrated account back and compares it with the value expected from its source.
+fn verify_one<M: AccountMigration>(
+    client: &RpcClient,
+    target: &Pubkey,
+    before: &M::Before,
+) -> Result<(), String> {
+    let account = client
+        .get_account(target)
+        .map_err(|e| format!("Error fetching migrated account {}: {}", target, e))?;
+    let actual = M::After::try_deserialize(&mut account.data.as_slice())
+        .map_err(|e| format!("Invalid migrated account {}: {}", target, e))?;
+    let mismatches = M::verify(&M::expected(before), &actual);
+    if !mismatches.is_empty() {
+        return Err(format!("Migrated account {} does not match: {:?}", target, mismatches));
+    }
+    Ok(())
+}
+
+/// Verifies again the accounts of the checkpoint whose read-back failed in a previous run.
+fn retry_unverified<M: AccountMigration>(
+    client: &RpcClient,
+    checkpoint: &mut Checkpoint,
+    report: &mut MigrationReport,
+) -> Result<(), String> {
+    let unverified = std::mem::take(&mut checkpoint.unverified);
+    for (source, account) in unverified {
+        let target = Pubkey::from_str(&account.target)
+            .map_err(|e| format!("Invalid target {} in checkpoint: {}", account.target, e))?;
+        let before = M::Before::try_deserialize(&mut account.before.as_slice())
+            .map_err(|e| format!("Invalid source data of {} in checkpoint: {}", source, e))?;
+        match verify_one::<M>(client, &target, &before) {
+            Ok(()) => {
+                checkpoint.completed.insert(source);
+                report.migrated += 1;
+            }
+            Err(e) => {
+                warn!("Migrated account {} is still unverified: {}", target, e);
+                let source_key = Pubkey::from_str(&source)
+                    .map_err(|e| format!("Invalid source {} in checkpoint: {}", source, e))?;
+                report.failed.push((source_key, e));
+                checkpoint.unverified.insert(source, account);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Runs one migration step over every pending account.
+///
+/// Accounts are migrated in batches of `concurrency` parallel transactions. Each migrated
+/// account is read back and compared field by field with the expected value, and the
+/// checkpoint is saved after every batch so a rerun only picks up what is left. Accounts that
+/// were migrated but failed the read-back are recorded with their target and verified again
+/// at the start of the next run.
+pub fn run<M: AccountMigration>(
+    client: &RpcClient,
+    payer: &Keypair,
+    options: &RunOptions,
+    checkpoint: &mut Checkpoint,
+) -> Result<MigrationReport, String>
+where
+    M::Before: Sync,
+{
+    let accounts = fetch_accounts::<M::Before>(client)?;
+    let mut report = MigrationReport {
+        total: accounts.len(),
+        ..MigrationReport::default()
+    };
+    let pending: Vec<(Pubkey, M::Before)> = accounts
+        .into_iter()
+        .filter(|(pubkey, _)| {
+            let key = pubkey.to_string();
+            !checkpoint.completed.contains(&key) && !checkpoint.unverified.contains_key(&key)
+        })
+        .collect();
+    report.skipped = report.total - pending.len();
+
+    if options.dry_run {
+        for (pubkey, before) in &pending {
+            report.planned.push((*pubkey, M::diff(before, &M::expected(before))));
+        }
+        return Ok(report);
+    }
+
+    if !checkpoint.unverified.is_empty() {
+        retry_unverified::<M>(client, checkpoint, &mut report)?;
+        checkpoint.save(&options.checkpoint)?;
+    }
+
+    for batch in pending.chunks(options.concurrency.max(1)) {
+        let results: Vec<(Pubkey, Result<(), MigrationFailure>)> = std::thread::scope(|scope| {
+            let handles: Vec<_> = batch
+                .iter()
+                .map(|(pubkey, before)| {
+                    scope.spawn(move || {
+                        (*pubkey, migrate_one::<M>(client, payer, options, pubkey, before))
+                    })
+                })
+                .collect();
+            handles
+                .into_iter()
+                .map(|handle| handle.join().expect("Migration thread panicked"))
+                .collect()
+        });
+
+        for ((pubkey, result), (_, before)) in results.into_iter().zip(batch) {
+            match result {
+                Ok(()) => {
+                    checkpoint.completed.insert(pubkey.to_string());
+                    report.migrated += 1;
+                }
+                Err(MigrationFailure::NotSent(e)) => {
+                    warn!("Failed to migrate {}: {}", pubkey, e);
+                    report.failed.push((pubkey, e));
+                }
+                Err(MigrationFailure::Unverified(e)) => {
+                    warn!("Migrated {} but could not verify it: {}", pubkey, e);
+                    let mut data = Vec::new();
+                    before
+                        .try_serialize(&mut data)
+                        .map_err(|e| format!("Error serializing {}: {}", pubkey, e))?;
+                    checkpoint.unverified.insert(
+                        pubkey.to_string(),
+                        UnverifiedAccount {
+                            target: M::target(before).to_string(),
+                            before: data,
+                        },
+                    );
+                    report.failed.push((pubkey, e));
+                }
+            }
+        }
+        checkpoint.save(&options.checkpoint)?;
+        info!(
+            "{}: {}/{} migrated, {} failed",
+            M::INSTRUCTION,
+            report.migrated + report.skipped,
+            report.total,
+            report.failed.len()
+        );
+    }
+    Ok(report)
+}
@@ -0,0 +1,11 @@
+pub mod claim;
+pub mod errors;
+pub mod job;
+pub mod machine;
+pub mod migration;
+pub mod model;
+pub mod order;
+pub mod protocol_config;
+pub mod queue;
+pub mod reward;
+pub mod vesting;
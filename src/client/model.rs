@@ -0,0 +1,83 @@
+use anchor_lang::{AccountDeserialize, AnchorSerialize};
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::hash::hash;
+use solana_sdk::instruction::{AccountMeta, Instruction};
+use solana_sdk::pubkey::Pubkey;
+use crate::ai::ai_model::{AiModel, ModelVersion};
+use crate::client::protocol_config::protocol_config_pda;
+
+/// Address of a model version. `model_create_time` is the `create_time` of the model, which keeps
+/// the versions of a removed model from being picked up by a model recreated under its name.
+pub fn model_version_pda(
+    ai_model: &Pubkey,
+    model_create_time: i64,
+    major: u16,
+    minor: u16,
+    patch: u16,
+) -> Pubkey {
+    Pubkey::find_program_address(
+        &[
+            b"model-version",
+            ai_model.as_ref(),
+            model_create_time.to_le_bytes().as_ref(),
+            major.to_le_bytes().as_ref(),
+            minor.to_le_bytes().as_ref(),
+            patch.to_le_bytes().as_ref(),
+        ],
+        &crate::ID,
+    )
+    .0
+}
+
+/// Returns the on-chain record of a pinned model version.
+pub fn fetch_model_version(
+    client: &RpcClient,
+    ai_model: &Pubkey,
+    major: u16,
+    minor: u16,
+    patch: u16,
+) -> Result<ModelVersion, String> {
+    let model = client
+        .get_account(ai_model)
+        .map_err(|e| format!("Error fetching AI model {}: {}", ai_model, e))?;
+    let model = AiModel::try_deserialize(&mut model.data.as_slice())
+        .map_err(|e| format!("Invalid AI model account: {}", e))?;
+    let pda = model_version_pda(ai_model, model.create_time, major, minor, patch);
+    let account = client
+        .get_account(&pda)
+        .map_err(|e| format!("Error fetching model version {}.{}.{}: {}", major, minor, patch, e))?;
+    ModelVersion::try_deserialize(&mut account.data.as_slice())
+        .map_err(|e| format!("Invalid model version account: {}", e))
+}
+
+pub fn license_receipt_pda(ai_model: &Pubkey, buyer: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[b"license-receipt", ai_model.as_ref(), buyer.as_ref()],
+        &crate::ID,
+    )
+    .0
+}
+
+/// Builds a `consume_license_calls` instruction charging `calls` to a buyer's per-call license,
+/// signed by the model owner or the metering authority.
+pub fn consume_license_calls_instruction(
+    ai_model: &Pubkey,
+    buyer: &Pubkey,
+    meter: &Pubkey,
+    calls: u64,
+) -> Instruction {
+    let mut data = hash(b"global:consume_license_calls").to_bytes()[..8].to_vec();
+    calls
+        .serialize(&mut data)
+        .expect("Serializing the call count cannot fail");
+    Instruction {
+        program_id: crate::ID,
+        accounts: vec![
+            AccountMeta::new_readonly(protocol_config_pda(), false),
+            AccountMeta::new_readonly(*ai_model, false),
+            AccountMeta::new(license_receipt_pda(ai_model, buyer), false),
+            AccountMeta::new_readonly(*meter, true),
+        ],
+        data,
+    }
+}
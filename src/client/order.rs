@@ -0,0 +1,63 @@
+use anchor_lang::AnchorSerialize;
+use anchor_spl::associated_token::{self, get_associated_token_address};
+use anchor_spl::token;
+use solana_sdk::hash::hash;
+use solana_sdk::instruction::{AccountMeta, Instruction};
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::system_program;
+use crate::client::claim::{reward_period_pda, reward_work_pda};
+use crate::client::job::job_binding_pda;
+use crate::client::machine::{machine_history_pda, machine_pda, stake_pda};
+use crate::client::protocol_config::protocol_config_pda;
+
+pub fn order_pda(buyer: &Pubkey, order_id: &[u8; 16]) -> Pubkey {
+    Pubkey::find_program_address(&[b"order", buyer.as_ref(), order_id.as_ref()], &crate::ID).0
+}
+
+pub fn order_payment_pda(order: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"order-payment", order.as_ref()], &crate::ID).0
+}
+
+/// Builds an `order_completed` instruction settling `order` to `seller` in reward `period`.
+pub fn order_completed_instruction(
+    seller: &Pubkey,
+    order: &Pubkey,
+    machine_id: &[u8; 16],
+    mint: &Pubkey,
+    metadata: String,
+    period: u32,
+    output_version: Option<Pubkey>,
+) -> Instruction {
+    let machine = machine_pda(seller, machine_id);
+    let mut data = hash(b"global:order_completed").to_bytes()[..8].to_vec();
+    (metadata, period)
+        .serialize(&mut data)
+        .expect("Serializing instruction arguments cannot fail");
+    Instruction {
+        program_id: crate::ID,
+        accounts: vec![
+            AccountMeta::new_readonly(protocol_config_pda(), false),
+            AccountMeta::new(machine, false),
+            AccountMeta::new(*order, false),
+            AccountMeta::new_readonly(order_payment_pda(order), false),
+            AccountMeta::new(stake_pda(seller, machine_id), false),
+            AccountMeta::new(reward_period_pda(period), false),
+            AccountMeta::new(reward_work_pda(period, seller, machine_id), false),
+            AccountMeta::new(machine_history_pda(&machine), false),
+            AccountMeta::new(job_binding_pda(order), false),
+            // Anchor reads the program id as an absent optional account
+            AccountMeta::new_readonly(output_version.unwrap_or(crate::ID), false),
+            AccountMeta::new(*seller, true),
+            AccountMeta::new(get_associated_token_address(seller, mint), false),
+            AccountMeta::new(
+                Pubkey::find_program_address(&[b"vault", mint.as_ref()], &crate::ID).0,
+                false,
+            ),
+            AccountMeta::new_readonly(*mint, false),
+            AccountMeta::new_readonly(token::ID, false),
+            AccountMeta::new_readonly(associated_token::ID, false),
+            AccountMeta::new_readonly(system_program::ID, false),
+        ],
+        data,
+    }
+}
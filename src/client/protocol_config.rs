@@ -0,0 +1,35 @@
+use anchor_lang::{AccountDeserialize, AnchorSerialize};
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::hash::hash;
+use solana_sdk::instruction::{AccountMeta, Instruction};
+use solana_sdk::pubkey::Pubkey;
+use crate::state::protocol_config::ProtocolConfig;
+
+pub fn protocol_config_pda() -> Pubkey {
+    Pubkey::find_program_address(&[b"protocol-config"], &crate::ID).0
+}
+
+/// Returns the protocol config, which every deployment creates with `initialize_config`.
+pub fn fetch_protocol_config(client: &RpcClient) -> Result<ProtocolConfig, String> {
+    let account = client
+        .get_account(&protocol_config_pda())
+        .map_err(|e| format!("Error fetching protocol config: {}", e))?;
+    ProtocolConfig::try_deserialize(&mut account.data.as_slice())
+        .map_err(|e| format!("Invalid protocol config account: {}", e))
+}
+
+/// Builds a `set_paused` instruction replacing the paused flags, signed by the guardian.
+pub fn set_paused_instruction(guardian: &Pubkey, paused: u64) -> Instruction {
+    let mut data = hash(b"global:set_paused").to_bytes()[..8].to_vec();
+    paused
+        .serialize(&mut data)
+        .expect("Serializing paused flags cannot fail");
+    Instruction {
+        program_id: crate::ID,
+        accounts: vec![
+            AccountMeta::new(protocol_config_pda(), false),
+            AccountMeta::new_readonly(*guardian, true),
+        ],
+        data,
+    }
+}
@@ -0,0 +1,60 @@
+use anchor_lang::AccountDeserialize;
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::pubkey::Pubkey;
+use crate::state::queue::{MachineQueue, QueuedOrder, QueuedOrderStatus};
+
+pub fn queue_pda(machine: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"queue", machine.as_ref()], &crate::ID).0
+}
+
+pub fn queued_order_pda(machine: &Pubkey, position: u64) -> Pubkey {
+    Pubkey::find_program_address(
+        &[b"queued-order", machine.as_ref(), position.to_le_bytes().as_ref()],
+        &crate::ID,
+    )
+    .0
+}
+
+/// Returns the live queued orders of a machine, ordered from the head of the queue.
+pub fn fetch_queue(client: &RpcClient, machine: &Pubkey) -> Result<Vec<QueuedOrder>, String> {
+    let account = match client
+        .get_account_with_commitment(&queue_pda(machine), client.commitment())
+        .map_err(|e| format!("Error fetching queue: {}", e))?
+        .value
+    {
+        Some(account) => account,
+        None => return Ok(Vec::new()),
+    };
+    let queue = MachineQueue::try_deserialize(&mut account.data.as_slice())
+        .map_err(|e| format!("Invalid queue account: {}", e))?;
+
+    let pdas: Vec<Pubkey> = (queue.head..queue.tail)
+        .map(|position| queued_order_pda(machine, position))
+        .collect();
+    let mut queued_orders = Vec::with_capacity(pdas.len());
+    for chunk in pdas.chunks(100) {
+        let accounts = client
+            .get_multiple_accounts(chunk)
+            .map_err(|e| format!("Error fetching queued orders: {}", e))?;
+        for account in accounts.into_iter().flatten() {
+            let queued_order = QueuedOrder::try_deserialize(&mut account.data.as_slice())
+                .map_err(|e| format!("Invalid queued order account: {}", e))?;
+            if queued_order.status == QueuedOrderStatus::Queued {
+                queued_orders.push(queued_order);
+            }
+        }
+    }
+    Ok(queued_orders)
+}
+
+/// Returns how many live orders are ahead of the given order, or `None` if it is not queued.
+pub fn queue_position(
+    client: &RpcClient,
+    machine: &Pubkey,
+    order_id: &[u8; 16],
+) -> Result<Option<usize>, String> {
+    let queued_orders = fetch_queue(client, machine)?;
+    Ok(queued_orders
+        .iter()
+        .position(|queued_order| &queued_order.order_id == order_id))
+}
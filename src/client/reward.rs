@@ -0,0 +1,96 @@
+use anchor_lang::{AccountDeserialize, Discriminator};
+use solana_client::rpc_client::RpcClient;
+use solana_client::rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig};
+use solana_client::rpc_filter::{Memcmp, RpcFilterType};
+use solana_sdk::pubkey::Pubkey;
+use crate::state::reward_period::{RewardLedger, RewardPeriod};
+
+pub fn reward_pool_pda(mint: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"reward-pool", mint.as_ref()], &crate::ID).0
+}
+
+pub fn reward_ledger_pda() -> Pubkey {
+    Pubkey::find_program_address(&[b"reward-ledger"], &crate::ID).0
+}
+
+#[derive(Debug, Clone)]
+pub struct PeriodReserve {
+    pub period: u32,
+    pub reserved: u64,
+    pub claimed: u64,
+    pub remaining: u64,
+    pub expire_time: i64,
+}
+
+#[derive(Debug, Clone)]
+pub struct SolvencyReport {
+    /// Token balance of the reward pool.
+    pub pool_balance: u64,
+    /// Amount reserved for closed periods and not yet claimed or expired.
+    pub outstanding: u64,
+    /// Reserves of every closed period that can still be claimed.
+    pub periods: Vec<PeriodReserve>,
+    pub solvent: bool,
+}
+
+/// Compares the reward pool balance against the rewards still owed for closed periods.
+pub fn solvency_report(client: &RpcClient, mint: &Pubkey) -> Result<SolvencyReport, String> {
+    let balance = client
+        .get_token_account_balance(&reward_pool_pda(mint))
+        .map_err(|e| format!("Error fetching reward pool balance: {}", e))?;
+    let pool_balance = balance
+        .amount
+        .parse::<u64>()
+        .map_err(|e| format!("Invalid reward pool balance: {}", e))?;
+
+    let outstanding = match client
+        .get_account_with_commitment(&reward_ledger_pda(), client.commitment())
+        .map_err(|e| format!("Error fetching reward ledger: {}", e))?
+        .value
+    {
+        Some(account) => {
+            RewardLedger::try_deserialize(&mut account.data.as_slice())
+                .map_err(|e| format!("Invalid reward ledger account: {}", e))?
+                .outstanding
+        }
+        None => 0,
+    };
+
+    let config = RpcProgramAccountsConfig {
+        filters: Some(vec![RpcFilterType::Memcmp(Memcmp::new_base58_encoded(
+            0,
+            &RewardPeriod::DISCRIMINATOR,
+        ))]),
+        account_config: RpcAccountInfoConfig {
+            commitment: Some(client.commitment()),
+            ..RpcAccountInfoConfig::default()
+        },
+        ..RpcProgramAccountsConfig::default()
+    };
+    let accounts = client
+        .get_program_accounts_with_config(&crate::ID, config)
+        .map_err(|e| format!("Error fetching reward periods: {}", e))?;
+    let mut periods = Vec::new();
+    for (_, account) in accounts {
+        let reward_period = RewardPeriod::try_deserialize(&mut account.data.as_slice())
+            .map_err(|e| format!("Invalid reward period account: {}", e))?;
+        if !reward_period.closed || reward_period.expired {
+            continue;
+        }
+        periods.push(PeriodReserve {
+            period: reward_period.period,
+            reserved: reward_period.reserved,
+            claimed: reward_period.claimed,
+            remaining: reward_period.remaining(),
+            expire_time: reward_period.expire_time(),
+        });
+    }
+    periods.sort_by_key(|reserve| reserve.period);
+
+    Ok(SolvencyReport {
+        pool_balance,
+        outstanding,
+        periods,
+        solvent: pool_balance >= outstanding,
+    })
+}
@@ -0,0 +1,70 @@
+use anchor_lang::{AccountDeserialize, Discriminator};
+use solana_client::rpc_client::RpcClient;
+use solana_client::rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig};
+use solana_client::rpc_filter::{Memcmp, RpcFilterType};
+use solana_sdk::pubkey::Pubkey;
+use crate::state::vesting::{VestingConfig, VestingSchedule};
+
+pub fn vesting_config_pda() -> Pubkey {
+    Pubkey::find_program_address(&[b"vesting-config"], &crate::ID).0
+}
+
+pub fn vesting_schedule_pda(period: u32, owner: &Pubkey, machine_id: &[u8; 16]) -> Pubkey {
+    Pubkey::find_program_address(
+        &[b"vesting", period.to_le_bytes().as_ref(), owner.as_ref(), machine_id.as_ref()],
+        &crate::ID,
+    )
+    .0
+}
+
+/// Returns the vesting config, or `None` if vesting has never been configured.
+pub fn fetch_vesting_config(client: &RpcClient) -> Result<Option<VestingConfig>, String> {
+    let account = match client
+        .get_account_with_commitment(&vesting_config_pda(), client.commitment())
+        .map_err(|e| format!("Error fetching vesting config: {}", e))?
+        .value
+    {
+        Some(account) => account,
+        None => return Ok(None),
+    };
+    VestingConfig::try_deserialize(&mut account.data.as_slice())
+        .map(Some)
+        .map_err(|e| format!("Invalid vesting config account: {}", e))
+}
+
+/// Returns the vesting schedules of an owner, ordered by period.
+pub fn fetch_vesting_schedules(
+    client: &RpcClient,
+    owner: &Pubkey,
+) -> Result<Vec<(Pubkey, VestingSchedule)>, String> {
+    let config = RpcProgramAccountsConfig {
+        filters: Some(vec![
+            RpcFilterType::Memcmp(Memcmp::new_base58_encoded(0, &VestingSchedule::DISCRIMINATOR)),
+            RpcFilterType::Memcmp(Memcmp::new_base58_encoded(8, owner.as_ref())),
+        ]),
+        account_config: RpcAccountInfoConfig {
+            commitment: Some(client.commitment()),
+            ..RpcAccountInfoConfig::default()
+        },
+        ..RpcProgramAccountsConfig::default()
+    };
+    let accounts = client
+        .get_program_accounts_with_config(&crate::ID, config)
+        .map_err(|e| format!("Error fetching vesting schedules: {}", e))?;
+    let mut schedules = Vec::with_capacity(accounts.len());
+    for (pubkey, account) in accounts {
+        let schedule = VestingSchedule::try_deserialize(&mut account.data.as_slice())
+            .map_err(|e| format!("Invalid vesting schedule account: {}", e))?;
+        schedules.push((pubkey, schedule));
+    }
+    schedules.sort_by_key(|(_, schedule)| schedule.period);
+    Ok(schedules)
+}
+
+/// Returns the total amount an owner can withdraw across all schedules at `now`.
+pub fn withdrawable_total(schedules: &[(Pubkey, VestingSchedule)], now: i64) -> u64 {
+    schedules
+        .iter()
+        .map(|(_, schedule)| schedule.withdrawable(now))
+        .fold(0u64, |total, amount| total.saturating_add(amount))
+}
@@ -2,14 +2,80 @@ use anchor_lang::error_code;
 
 #[error_code]
 pub enum DistriAIError {
-    /// A string is too long.
+    #[msg("A string is too long")]
     StringTooLong,
-    /// The machine/order status is not the expected status.
+    #[msg("The machine/order status is not the expected status")]
     IncorrectStatus,
-    /// The duration of the order exceeds the maximum available duration of the machine.
+    #[msg("The duration of the order exceeds the maximum available duration of the machine")]
     DurationTooMuch,
-    /// Period is invalid.
+    #[msg("Period is invalid")]
     InvalidPeriod,
-    /// Reward has been claimed.
+    #[msg("Reward has been claimed")]
     RepeatClaim,
+    #[msg("The machine stake is below the required minimum")]
+    InsufficientStake,
+    #[msg("The stake has active orders or is still in its cooldown")]
+    StakeLocked,
+    #[msg("The signer is not allowed to perform this action")]
+    Unauthorized,
+    #[msg("The machine has queued orders waiting to be activated")]
+    QueueNotEmpty,
+    #[msg("The queued order is not at the head of the queue")]
+    NotQueueHead,
+    #[msg("The queued order has expired")]
+    QueueExpired,
+    #[msg("The mint does not match the payment mint of the order")]
+    MintMismatch,
+    #[msg("The mint is not accepted as payment")]
+    MintNotAccepted,
+    #[msg("The result hash does not match the committed task result")]
+    ResultHashMismatch,
+    #[msg("The machine has no rewarded work in the period")]
+    NoRewardWork,
+    #[msg("The reward period has not been closed yet")]
+    PeriodNotClosed,
+    #[msg("The reward period has already been closed")]
+    PeriodClosed,
+    #[msg("The claim window of the reward period has not ended yet")]
+    PeriodNotExpired,
+    #[msg("The claim window of the reward period has ended")]
+    ClaimExpired,
+    #[msg("The reserved pool of the reward period is exhausted")]
+    PeriodPoolExhausted,
+    #[msg("Rewards must be claimed into a vesting schedule")]
+    VestingRequired,
+    #[msg("Vesting is not enabled")]
+    VestingDisabled,
+    #[msg("No vested rewards are available to withdraw")]
+    NothingVested,
+    #[msg("The machine has not completed enough orders to accelerate vesting")]
+    AccelerationNotEligible,
+    #[msg("The accounts of a batch claim do not match its entries")]
+    InvalidBatch,
+    #[msg("The model version or its content hash is invalid")]
+    InvalidVersion,
+    #[msg("The model is not under a paid license")]
+    LicenseNotPaid,
+    #[msg("The requested quantity is invalid")]
+    InvalidQuantity,
+    #[msg("An amount overflowed")]
+    MathOverflow,
+    #[msg("The migration would change the status of the account")]
+    StatusChangeRefused,
+    #[msg("The machine has missed too many heartbeats to take orders")]
+    MachineOffline,
+    #[msg("The protocol parameters are invalid")]
+    InvalidConfig,
+    #[msg("The instruction is paused by the guardian")]
+    Paused,
+    #[msg("The order cannot be refunded before its cancel window ends")]
+    OrderNotCancelable,
+    #[msg("The paid duration of the order has already been used")]
+    DurationUsedUp,
+    #[msg("The order has not reached its end time")]
+    OrderNotEnded,
+    #[msg("The queued order has not expired yet")]
+    QueueNotExpired,
+    #[msg("The score is out of range")]
+    InvalidScore,
 }
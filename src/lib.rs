@@ -1,3 +1,12 @@
+use anchor_lang::prelude::*;
+
+pub mod ai;
 pub mod api;
+pub mod client;
+pub mod errors;
+pub mod migration;
 pub mod services;
+pub mod state;
 pub mod utils;
+
+declare_id!("6yFTDdiS1W9T9yg6YejkwKggkEE4NYqdSSzVqQvuLn16");
@@ -1,6 +1,14 @@
 use actix_web::{web, App, HttpServer, Responder};
 use dotenv::dotenv;
+use log::{error, warn};
+use lyzerai_core::api::routes::configure_routes;
+use lyzerai_core::services::licensing::LicenseChecker;
+use lyzerai_core::services::machine_index::MachineIndex;
+use lyzerai_core::services::model_registry::ModelRegistry;
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::signature::read_keypair_file;
 use std::env;
+use std::time::Duration;
 
 async fn health_check() -> impl Responder {
     "LyzerAI-Core API is running"
@@ -14,11 +22,51 @@ async fn main() -> std::io::Result<()> {
     let port = env::var("PORT").unwrap_or_else(|_| "8080".to_string());
     let address = format!("{}:{}", host, port);
 
+    let registry = web::Data::new(ModelRegistry::default());
+    let rpc_url = env::var("SOLANA_RPC_URL")
+        .unwrap_or_else(|_| "https://api.mainnet-beta.solana.com".to_string());
+    // Key that charges calls to per-call licenses. Without it, per-call models cannot be invoked.
+    let meter = match env::var("METERING_KEYPAIR") {
+        Ok(path) => match read_keypair_file(&path) {
+            Ok(meter) => Some(meter),
+            Err(e) => {
+                error!("Failed to read metering keypair {}: {}", path, e);
+                None
+            }
+        },
+        Err(_) => {
+            warn!("METERING_KEYPAIR is not set, per-call models are disabled");
+            None
+        }
+    };
+    let checker = web::Data::new(LicenseChecker::new(&rpc_url, meter));
+    let machines = web::Data::new(MachineIndex::default());
+    let loader = registry.clone();
+    let machine_loader = machines.clone();
+    std::thread::spawn(move || {
+        let client = RpcClient::new(rpc_url);
+        // Models are registered and machine status changes with every order, so both indexes
+        // are reloaded periodically.
+        loop {
+            if let Err(e) = loader.load(&client) {
+                error!("Failed to index AI models: {}", e);
+            }
+            if let Err(e) = machine_loader.load(&client) {
+                error!("Failed to index machines: {}", e);
+            }
+            std::thread::sleep(Duration::from_secs(60));
+        }
+    });
+
     println!("Starting LyzerAI-Core API at http://{}/", address);
 
-    HttpServer::new(|| {
+    HttpServer::new(move || {
         App::new()
+            .app_data(registry.clone())
+            .app_data(checker.clone())
+            .app_data(machines.clone())
             .route("/health", web::get().to(health_check))
+            .configure(configure_routes)
     })
     .bind(address)?
     .run()
@@ -1,44 +1,32 @@
 use anchor_lang::prelude::*;
+use crate::errors::DistriAIError;
+use crate::migrate_account;
 use crate::state::machine::*;
+use crate::state::protocol_config::*;
 
 // Define a public function to handle the migration of machine data to a new structure
 pub fn migrate_machine_new(ctx: Context<MigrationMachineNew>) -> Result<()> {
-    let machine_before = &mut ctx.accounts.machine_before;
-    let machine_after = &mut ctx.accounts.machine_after;
-    machine_after.owner = machine_before.owner;
-    machine_after.uuid = machine_before.uuid;
-    machine_after.metadata = machine_before.metadata.clone();
-    machine_after.status = machine_before.status.clone();
-    machine_after.price = machine_before.price;
-    machine_after.max_duration = machine_before.max_duration;
-    machine_after.disk = machine_before.disk;
-    machine_after.completed_count = machine_before.completed_count;
-    machine_after.failed_count = machine_before.failed_count;
-    machine_after.score = machine_before.score;
-    machine_after.claimed_periodic_rewards = machine_before.claimed_periodic_rewards;
-    machine_after.claimed_task_rewards = machine_before.claimed_task_rewards;
+    let machine_after = migrate_account!(&ctx.accounts.machine_before, Machine => MachineNew {
+        copy {
+            owner, uuid, metadata, status, price, max_duration, disk, completed_count,
+            failed_count, score, claimed_periodic_rewards, claimed_task_rewards, order_pda,
+        },
+    });
+    ctx.accounts.machine_after.set_inner(machine_after);
 
     Ok(())
 }
 
 // Public function to handle the migration of a machine's data with a name change
 pub fn migrate_machine_rename(ctx: Context<MigrationMachineRename>) -> Result<()> {
-    let machine_before = &mut ctx.accounts.machine_before;
-    let machine_after = &mut ctx.accounts.machine_after;
-    machine_after.owner = machine_before.owner;
-    machine_after.uuid = machine_before.uuid;
-    machine_after.metadata = machine_before.metadata.clone();
-    machine_after.status = machine_before.status.clone();
-    machine_after.price = machine_before.price;
-    machine_after.max_duration = machine_before.max_duration;
-    machine_after.disk = machine_before.disk;
-    machine_after.completed_count = machine_before.completed_count;
-    machine_after.failed_count = machine_before.failed_count;
-    machine_after.score = machine_before.score;
-    machine_after.claimed_periodic_rewards = machine_before.claimed_periodic_rewards;
-    machine_after.claimed_task_rewards = machine_before.claimed_task_rewards;
-    machine_after.order_pda = machine_before.order_pda;
-    
+    let machine_after = migrate_account!(&ctx.accounts.machine_before, MachineNew => Machine {
+        copy {
+            owner, uuid, metadata, status, price, max_duration, disk, completed_count,
+            failed_count, score, claimed_periodic_rewards, claimed_task_rewards, order_pda,
+        },
+    });
+    ctx.accounts.machine_after.set_inner(machine_after);
+
     Ok(())
 }
 
@@ -69,6 +57,13 @@ pub struct MigrationMachineNew<'info> {
     pub signer: Signer<'info>,
 
     pub system_program: Program<'info, System>,
+
+    #[account(
+        seeds = [b"protocol-config"],
+        bump,
+        constraint = config.authority == signer.key() @ DistriAIError::Unauthorized
+    )]
+    pub config: Box<Account<'info, ProtocolConfig>>,
 }
 
 // Define a structure for the accounts involved in the migration process to rename a machine
@@ -93,4 +88,11 @@ pub struct MigrationMachineRename<'info> {
     pub signer: Signer<'info>,
 
     pub system_program: Program<'info, System>,
+
+    #[account(
+        seeds = [b"protocol-config"],
+        bump,
+        constraint = config.authority == signer.key() @ DistriAIError::Unauthorized
+    )]
+    pub config: Box<Account<'info, ProtocolConfig>>,
 }
@@ -1,27 +1,52 @@
 use anchor_lang::prelude::*;
+use crate::errors::DistriAIError;
+use crate::{migrate_account, status_table};
 use crate::state::order::*;
+use crate::state::protocol_config::*;
+
+status_table! {
+    /// Status rewrite applied by `migrate_order_new`.
+    ///
+    /// A schema migration must not move live orders through their lifecycle, so every status is
+    /// kept. Any remap declared here needs `allow_status_change` from the admin to be applied.
+    pub fn order_new_status(OrderStatus) {
+        Preparing => Preparing,
+        Training => Training,
+        Completed => Completed,
+        Failed => Failed,
+        Refunded => Refunded,
+    }
+}
 
 // Define a public function `migrate_order_new` that migrates order information from one account to another.
-pub fn migrate_order_new(ctx: Context<MigrationOrderNew>) -> Result<()> {
-    let order_before = &mut ctx.accounts.order_before;
-    let order_after = &mut ctx.accounts.order_after;
-    order_after.order_id = order_before.order_id;
-    order_after.buyer = order_before.buyer;
-    order_after.seller = order_before.seller;
-    order_after.machine_id = order_before.machine_id;
-    order_after.price = order_before.price;
-    order_after.duration = order_before.duration;
-    order_after.total = order_before.total;
-    order_after.metadata = order_before.metadata.clone();
-    order_after.status = match order_before.status {
-        OrderStatus::Preparing => OrderStatus::Training,
-        OrderStatus::Training => OrderStatus::Completed,
-        OrderStatus::Completed => OrderStatus::Failed,
-        _ => OrderStatus::Refunded,
-    };
-    order_after.order_time = order_before.order_time;
-    order_after.start_time = order_before.order_time;
-    order_after.refund_time = order_before.refund_time;
+//
+// Orders whose status would change under `order_new_status` are refused unless the config
+// authority passes `allow_status_change`.
+pub fn migrate_order_new(ctx: Context<MigrationOrderNew>, allow_status_change: bool) -> Result<()> {
+    let order_after = migrate_account!(&ctx.accounts.order_before, Order => OrderNew {
+        copy {
+            order_id, buyer, seller, machine_id, price, duration, total, metadata,
+            order_time, start_time, refund_time,
+        },
+        map {
+            status: order_new_status(&status),
+        },
+    });
+
+    let old_status = ctx.accounts.order_before.status.clone();
+    let new_status = order_after.status.clone();
+    if new_status != old_status {
+        require!(allow_status_change, DistriAIError::StatusChangeRefused);
+    }
+
+    emit!(MigrationEvent {
+        account: ctx.accounts.order_before.key(),
+        migrated: ctx.accounts.order_after.key(),
+        order_id: order_after.order_id,
+        old_status,
+        new_status,
+    });
+    ctx.accounts.order_after.set_inner(order_after);
 
     Ok(())
 }
@@ -29,20 +54,13 @@ pub fn migrate_order_new(ctx: Context<MigrationOrderNew>) -> Result<()> {
 // Define a public function `migrate_order_rename` that handles the migration of an order record
 // by renaming it in the context of a Solana program.
 pub fn migrate_order_rename(ctx: Context<MigrationOrderRename>) -> Result<()> {
-    let order_before = &mut ctx.accounts.order_before;
-    let order_after = &mut ctx.accounts.order_after;
-    order_after.order_id = order_before.order_id;
-    order_after.buyer = order_before.buyer;
-    order_after.seller = order_before.seller;
-    order_after.machine_id = order_before.machine_id;
-    order_after.price = order_before.price;
-    order_after.duration = order_before.duration;
-    order_after.total = order_before.total;
-    order_after.metadata = order_before.metadata.clone();
-    order_after.status = order_before.status.clone();
-    order_after.order_time = order_before.order_time;
-    order_after.start_time = order_before.start_time;
-    order_after.refund_time = order_before.refund_time;
+    let order_after = migrate_account!(&ctx.accounts.order_before, OrderNew => Order {
+        copy {
+            order_id, buyer, seller, machine_id, price, duration, total, metadata, status,
+            order_time, start_time, refund_time,
+        },
+    });
+    ctx.accounts.order_after.set_inner(order_after);
 
     Ok(())
 }
@@ -69,6 +87,13 @@ pub struct MigrationOrderNew<'info> {
     pub signer: Signer<'info>,
 
     pub system_program: Program<'info, System>,
+
+    #[account(
+        seeds = [b"protocol-config"],
+        bump,
+        constraint = config.authority == signer.key() @ DistriAIError::Unauthorized
+    )]
+    pub config: Box<Account<'info, ProtocolConfig>>,
 }
 
 #[derive(Accounts)]
@@ -92,4 +117,20 @@ pub struct MigrationOrderRename<'info> {
     pub signer: Signer<'info>,
 
     pub system_program: Program<'info, System>,
+
+    #[account(
+        seeds = [b"protocol-config"],
+        bump,
+        constraint = config.authority == signer.key() @ DistriAIError::Unauthorized
+    )]
+    pub config: Box<Account<'info, ProtocolConfig>>,
+}
+
+#[event]
+pub struct MigrationEvent {
+    pub account: Pubkey,
+    pub migrated: Pubkey,
+    pub order_id: [u8; 16],
+    pub old_status: OrderStatus,
+    pub new_status: OrderStatus,
 }
@@ -0,0 +1,112 @@
+use anchor_lang::{AnchorDeserialize, Discriminator};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use log::warn;
+use solana_sdk::pubkey::Pubkey;
+use crate::utils::order::{
+    OrderCompletedEvent, OrderFailedEvent, OrderPlacedEvent, OrderRefundedEvent,
+    OrderRemovedEvent, OrderStartedEvent, RenewEvent,
+};
+
+const PROGRAM_DATA: &str = "Program data: ";
+
+#[derive(Debug, Clone)]
+pub enum OrderLifecycleEvent {
+    Placed(OrderPlacedEvent),
+    Started(OrderStartedEvent),
+    Renewed(RenewEvent),
+    Refunded(OrderRefundedEvent),
+    Completed(OrderCompletedEvent),
+    Failed(OrderFailedEvent),
+    Removed(OrderRemovedEvent),
+}
+
+impl OrderLifecycleEvent {
+    /// Decodes a discriminator-prefixed event payload, returning `None` for other events.
+    pub fn decode(data: &[u8]) -> Option<Self> {
+        if data.len() < 8 {
+            return None;
+        }
+        let (discriminator, mut payload) = data.split_at(8);
+
+        macro_rules! decode_as {
+            ($($event:ident => $variant:ident),* $(,)?) => {
+                $(
+                    if discriminator == $event::DISCRIMINATOR {
+                        return $event::deserialize(&mut payload)
+                            .map_err(|e| warn!("Malformed {}: {}", stringify!($event), e))
+                            .ok()
+                            .map(OrderLifecycleEvent::$variant);
+                    }
+                )*
+            };
+        }
+
+        decode_as!(
+            OrderPlacedEvent => Placed,
+            OrderStartedEvent => Started,
+            RenewEvent => Renewed,
+            OrderRefundedEvent => Refunded,
+            OrderCompletedEvent => Completed,
+            OrderFailedEvent => Failed,
+            OrderRemovedEvent => Removed,
+        );
+        None
+    }
+
+    pub fn order_id(&self) -> [u8; 16] {
+        match self {
+            OrderLifecycleEvent::Placed(event) => event.order_id,
+            OrderLifecycleEvent::Started(event) => event.order_id,
+            OrderLifecycleEvent::Renewed(event) => event.order_id,
+            OrderLifecycleEvent::Refunded(event) => event.order_id,
+            OrderLifecycleEvent::Completed(event) => event.order_id,
+            OrderLifecycleEvent::Failed(event) => event.order_id,
+            OrderLifecycleEvent::Removed(event) => event.order_id,
+        }
+    }
+}
+
+/// Extracts the order lifecycle events emitted by `program_id` from transaction log messages.
+pub fn parse_order_events(program_id: &Pubkey, logs: &[String]) -> Vec<OrderLifecycleEvent> {
+    program_data(program_id, logs)
+        .iter()
+        .filter_map(|data| OrderLifecycleEvent::decode(data))
+        .collect()
+}
+
+/// Returns the decoded `Program data:` payloads logged by `program_id`.
+///
+/// Only lines logged while `program_id` is the innermost running program are decoded, so
+/// events from other programs invoked in the same transaction are ignored.
+pub fn program_data(program_id: &Pubkey, logs: &[String]) -> Vec<Vec<u8>> {
+    let program = program_id.to_string();
+    let mut call_stack: Vec<&str> = Vec::new();
+    let mut payloads = Vec::new();
+
+    for log in logs {
+        if let Some(data) = log.strip_prefix(PROGRAM_DATA) {
+            if call_stack.last() != Some(&program.as_str()) {
+                continue;
+            }
+            match STANDARD.decode(data) {
+                Ok(bytes) => payloads.push(bytes),
+                Err(e) => warn!("Invalid program data in logs: {}", e),
+            }
+            continue;
+        }
+
+        let mut parts = log.split_whitespace();
+        if parts.next() != Some("Program") {
+            continue;
+        }
+        match (parts.next(), parts.next()) {
+            (Some(id), Some("invoke")) => call_stack.push(id),
+            (Some(_), Some("success")) | (Some(_), Some("failed:")) => {
+                call_stack.pop();
+            }
+            _ => {}
+        }
+    }
+
+    payloads
+}
@@ -0,0 +1,181 @@
+use anchor_lang::AccountDeserialize;
+use log::warn;
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signature, Signer};
+use solana_sdk::transaction::Transaction;
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::Mutex;
+use crate::ai::ai_model::{AiModel, LicenseReceipt, LicenseType, ModelVersion};
+use crate::client::errors::TransactionFailure;
+use crate::client::model::{consume_license_calls_instruction, license_receipt_pda, model_version_pda};
+use crate::errors::DistriAIError;
+
+/// Seconds an invocation signature stays valid.
+pub const SIGNATURE_MAX_AGE: i64 = 300;
+
+/// Message a wallet signs to invoke a model.
+pub fn invocation_message(ai_model: &Pubkey, timestamp: i64) -> String {
+    format!("lyzerai:invoke:{}:{}", ai_model, timestamp)
+}
+
+#[derive(Debug)]
+pub enum LicenseError {
+    InvalidRequest(String),
+    Unauthenticated,
+    ModelNotFound,
+    VersionNotFound,
+    LicenseRequired,
+    Rpc(String),
+    /// Per-call licenses cannot be charged, e.g. without a metering key.
+    Metering(String),
+    /// The transaction charging a per-call license failed.
+    Transaction(TransactionFailure),
+}
+
+/// Invocation signatures already used, kept until they are too old to be accepted again.
+#[derive(Default)]
+pub struct SignatureLog {
+    used: Mutex<HashMap<Signature, i64>>,
+}
+
+impl SignatureLog {
+    /// Records a signature made at `timestamp`, returning false if it was already used.
+    pub fn record(&self, signature: Signature, timestamp: i64, now: i64) -> bool {
+        let mut used = self.used.lock().unwrap();
+        used.retain(|_, used_timestamp| now - *used_timestamp <= SIGNATURE_MAX_AGE);
+        used.insert(signature, timestamp).is_none()
+    }
+}
+
+/// Checks on-chain license receipts before paid models are invoked.
+///
+/// Calls under a per-call license are charged on-chain with `consume_license_calls`, signed by
+/// the metering authority of the protocol config.
+pub struct LicenseChecker {
+    client: RpcClient,
+    meter: Option<Keypair>,
+    signatures: SignatureLog,
+}
+
+impl LicenseChecker {
+    pub fn new(rpc_url: &str, meter: Option<Keypair>) -> Self {
+        LicenseChecker {
+            client: RpcClient::new(rpc_url.to_string()),
+            meter,
+            signatures: SignatureLog::default(),
+        }
+    }
+
+    /// Verifies that `signature` is the wallet's signature of the invocation message and that it
+    /// has not been used before.
+    pub fn authenticate(
+        &self,
+        ai_model: &Pubkey,
+        wallet: &str,
+        timestamp: i64,
+        signature: &str,
+        now: i64,
+    ) -> Result<Pubkey, LicenseError> {
+        let wallet = Pubkey::from_str(wallet)
+            .map_err(|e| LicenseError::InvalidRequest(format!("Invalid wallet: {}", e)))?;
+        let signature = Signature::from_str(signature)
+            .map_err(|e| LicenseError::InvalidRequest(format!("Invalid signature: {}", e)))?;
+        if (now - timestamp).abs() > SIGNATURE_MAX_AGE {
+            return Err(LicenseError::Unauthenticated);
+        }
+        let message = invocation_message(ai_model, timestamp);
+        if !signature.verify(wallet.as_ref(), message.as_bytes()) {
+            return Err(LicenseError::Unauthenticated);
+        }
+        if !self.signatures.record(signature, timestamp, now) {
+            return Err(LicenseError::Unauthenticated);
+        }
+        Ok(wallet)
+    }
+
+    /// Allows open models to anyone and paid models only to wallets with a valid receipt.
+    ///
+    /// A per-call license is charged one call before the model is allowed to run.
+    pub fn check(&self, ai_model: &Pubkey, wallet: &Pubkey, now: i64) -> Result<(), LicenseError> {
+        let model = self
+            .fetch::<AiModel>(ai_model)?
+            .ok_or(LicenseError::ModelNotFound)?;
+        if !model.license.is_paid() {
+            return Ok(());
+        }
+        let receipt = self
+            .fetch::<LicenseReceipt>(&license_receipt_pda(ai_model, wallet))?
+            .ok_or(LicenseError::LicenseRequired)?;
+        if !receipt.is_valid(model.license, now) {
+            return Err(LicenseError::LicenseRequired);
+        }
+        if model.license == LicenseType::PaidPerCall {
+            self.consume_call(ai_model, wallet)?;
+        }
+        Ok(())
+    }
+
+    /// Returns the on-chain record of the model version pinned by an invocation.
+    pub fn fetch_version(
+        &self,
+        ai_model: &Pubkey,
+        (major, minor, patch): (u16, u16, u16),
+    ) -> Result<ModelVersion, LicenseError> {
+        let model = self
+            .fetch::<AiModel>(ai_model)?
+            .ok_or(LicenseError::ModelNotFound)?;
+        let pda = model_version_pda(ai_model, model.create_time, major, minor, patch);
+        self.fetch::<ModelVersion>(&pda)?
+            .ok_or(LicenseError::VersionNotFound)
+    }
+
+    fn consume_call(&self, ai_model: &Pubkey, wallet: &Pubkey) -> Result<(), LicenseError> {
+        let meter = self
+            .meter
+            .as_ref()
+            .ok_or_else(|| LicenseError::Metering("No metering key configured".to_string()))?;
+        let instruction = consume_license_calls_instruction(ai_model, wallet, &meter.pubkey(), 1);
+        let blockhash = self
+            .client
+            .get_latest_blockhash()
+            .map_err(|e| LicenseError::Rpc(format!("Error fetching blockhash: {}", e)))?;
+        let transaction = Transaction::new_signed_with_payer(
+            &[instruction],
+            Some(&meter.pubkey()),
+            &[meter],
+            blockhash,
+        );
+        match self.client.send_and_confirm_transaction(&transaction) {
+            Ok(_) => Ok(()),
+            Err(e) => match TransactionFailure::from_client_error(&e) {
+                // Another invocation used the last call first.
+                TransactionFailure::Program {
+                    error: DistriAIError::InvalidQuantity,
+                    ..
+                } => Err(LicenseError::LicenseRequired),
+                failure => Err(LicenseError::Transaction(failure)),
+            },
+        }
+    }
+
+    fn fetch<T: AccountDeserialize>(&self, address: &Pubkey) -> Result<Option<T>, LicenseError> {
+        let account = match self
+            .client
+            .get_account_with_commitment(address, self.client.commitment())
+            .map_err(|e| LicenseError::Rpc(format!("Error fetching {}: {}", address, e)))?
+            .value
+        {
+            Some(account) => account,
+            None => return Ok(None),
+        };
+        if account.owner != crate::ID {
+            warn!("Account {} is not owned by the program", address);
+            return Ok(None);
+        }
+        T::try_deserialize(&mut account.data.as_slice())
+            .map(Some)
+            .map_err(|e| LicenseError::Rpc(format!("Invalid account {}: {}", address, e)))
+    }
+}
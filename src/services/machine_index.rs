@@ -0,0 +1,423 @@
+use anchor_lang::{AccountDeserialize, AnchorDeserialize, Discriminator};
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use solana_client::rpc_client::RpcClient;
+use solana_client::rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig};
+use solana_client::rpc_filter::{Memcmp, RpcFilterType};
+use solana_sdk::pubkey::Pubkey;
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::sync::RwLock;
+use uuid::Uuid;
+use crate::client::machine::machine_pda;
+use crate::client::protocol_config::fetch_protocol_config;
+use crate::services::events::program_data;
+use crate::state::heartbeat::MachineHeartbeat;
+use crate::state::machine::{Machine, MachineStatus};
+use crate::state::machine_spec::{HardwareSpec, MachineSpec};
+use crate::state::protocol_config::ProtocolParams;
+use crate::utils::machine::{
+    HeartbeatEvent, MachineEvent, MachineRemovedEvent, MachineSpecEvent, MachineSpecRemovedEvent,
+};
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SpecRecord {
+    pub gpu_model: String,
+    pub gpu_count: u8,
+    pub vram_gb: u32,
+    pub cpu_cores: u16,
+    pub ram_gb: u32,
+    pub disk_gb: u32,
+    pub region: String,
+}
+
+impl From<&HardwareSpec> for SpecRecord {
+    fn from(spec: &HardwareSpec) -> Self {
+        SpecRecord {
+            gpu_model: spec.gpu_model.clone(),
+            gpu_count: spec.gpu_count,
+            vram_gb: spec.vram_gb,
+            cpu_cores: spec.cpu_cores,
+            ram_gb: spec.ram_gb,
+            disk_gb: spec.disk_gb,
+            region: spec.region.clone(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct HeartbeatRecord {
+    pub first_seen: i64,
+    pub last_seen: i64,
+    pub beats: u64,
+}
+
+impl HeartbeatRecord {
+    fn heartbeat(&self) -> MachineHeartbeat {
+        MachineHeartbeat {
+            machine: Pubkey::default(),
+            first_seen: self.first_seen,
+            last_seen: self.last_seen,
+            beats: self.beats,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct MachineListing {
+    pub address: String,
+    pub owner: String,
+    pub uuid: String,
+    pub status: String,
+    pub price: u64,
+    pub max_duration: u32,
+    pub disk: u32,
+    pub score: u8,
+    pub completed_count: u32,
+    pub failed_count: u32,
+    pub spec: Option<SpecRecord>,
+    pub heartbeat: Option<HeartbeatRecord>,
+    /// Derived from `heartbeat` when the listing is served.
+    pub online: bool,
+    pub uptime_bps: u16,
+}
+
+impl MachineListing {
+    pub fn from_machine(address: &Pubkey, machine: &Machine) -> Self {
+        MachineListing {
+            address: address.to_string(),
+            owner: machine.owner.to_string(),
+            uuid: Uuid::from_bytes(machine.uuid).simple().to_string(),
+            status: status_name(&machine.status).to_string(),
+            price: machine.price,
+            max_duration: machine.max_duration,
+            disk: machine.disk,
+            score: machine.score,
+            completed_count: machine.completed_count,
+            failed_count: machine.failed_count,
+            spec: None,
+            heartbeat: None,
+            online: false,
+            uptime_bps: 0,
+        }
+    }
+
+    /// Fills in `online` and `uptime_bps` as of `now`, under the heartbeat parameters of the
+    /// protocol config.
+    pub fn refresh_liveness(&mut self, now: i64, params: &ProtocolParams) {
+        let heartbeat = self.heartbeat.map(|record| record.heartbeat());
+        self.online = heartbeat
+            .as_ref()
+            .map_or(false, |heartbeat| heartbeat.is_online(now, params));
+        self.uptime_bps = heartbeat.map_or(0, |heartbeat| heartbeat.uptime_bps(now, params));
+    }
+}
+
+pub fn status_name(status: &MachineStatus) -> &'static str {
+    match status {
+        MachineStatus::Idle => "Idle",
+        MachineStatus::ForRent => "ForRent",
+        MachineStatus::Renting => "Renting",
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum MachineSort {
+    PriceAsc,
+    PriceDesc,
+    ScoreAsc,
+    ScoreDesc,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct MachineQuery {
+    pub status: Option<String>,
+    pub owner: Option<String>,
+    pub gpu: Option<String>,
+    pub min_gpu_count: Option<u8>,
+    pub min_vram_gb: Option<u32>,
+    pub min_cpu_cores: Option<u16>,
+    pub min_ram_gb: Option<u32>,
+    pub min_disk_gb: Option<u32>,
+    pub region: Option<String>,
+    pub max_price: Option<u64>,
+    pub online: Option<bool>,
+    pub min_uptime_bps: Option<u16>,
+    pub sort: Option<MachineSort>,
+    pub page: Option<usize>,
+    pub page_size: Option<usize>,
+}
+
+impl MachineQuery {
+    pub const DEFAULT_PAGE_SIZE: usize = 20;
+    pub const MAX_PAGE_SIZE: usize = 100;
+
+    /// Hardware filters only match machines that have published a spec.
+    pub fn matches(&self, listing: &MachineListing) -> bool {
+        let filters_spec = self.gpu.is_some()
+            || self.min_gpu_count.is_some()
+            || self.min_vram_gb.is_some()
+            || self.min_cpu_cores.is_some()
+            || self.min_ram_gb.is_some()
+            || self.min_disk_gb.is_some()
+            || self.region.is_some();
+        let spec_matches = match &listing.spec {
+            Some(spec) => {
+                self.gpu.as_ref().map_or(true, |gpu| {
+                    spec.gpu_model.to_lowercase().contains(&gpu.trim().to_lowercase())
+                }) && self.min_gpu_count.map_or(true, |count| spec.gpu_count >= count)
+                    && self.min_vram_gb.map_or(true, |vram| spec.vram_gb >= vram)
+                    && self.min_cpu_cores.map_or(true, |cores| spec.cpu_cores >= cores)
+                    && self.min_ram_gb.map_or(true, |ram| spec.ram_gb >= ram)
+                    && self.min_disk_gb.map_or(true, |disk| spec.disk_gb >= disk)
+                    && self.region.as_ref().map_or(true, |region| {
+                        spec.region.eq_ignore_ascii_case(region.trim())
+                    })
+            }
+            None => !filters_spec,
+        };
+        spec_matches
+            && self.status.as_ref().map_or(true, |status| listing.status.eq_ignore_ascii_case(status))
+            && self.owner.as_ref().map_or(true, |owner| &listing.owner == owner)
+            && self.max_price.map_or(true, |price| listing.price <= price)
+            && self.online.map_or(true, |online| listing.online == online)
+            && self.min_uptime_bps.map_or(true, |uptime| listing.uptime_bps >= uptime)
+    }
+
+    /// 1-based page number and page size, clamped to `MAX_PAGE_SIZE`.
+    pub fn page(&self) -> (usize, usize) {
+        let page = self.page.unwrap_or(1).max(1);
+        let page_size = self
+            .page_size
+            .unwrap_or(Self::DEFAULT_PAGE_SIZE)
+            .clamp(1, Self::MAX_PAGE_SIZE);
+        (page, page_size)
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct MachinePage {
+    pub total: usize,
+    pub page: usize,
+    pub page_size: usize,
+    pub machines: Vec<MachineListing>,
+}
+
+/// In-memory index of the machine listings and their hardware specs.
+///
+/// The server rebuilds it from the program accounts with `load` every minute; `apply_logs` can
+/// apply events from transaction logs between two loads.
+#[derive(Default)]
+pub struct MachineIndex {
+    machines: RwLock<HashMap<String, MachineListing>>,
+    /// Protocol parameters liveness is judged by, unknown until the first load.
+    params: RwLock<Option<ProtocolParams>>,
+}
+
+impl MachineIndex {
+    /// Replaces the index with every `Machine` and `MachineSpec` account of the program.
+    pub fn load(&self, client: &RpcClient) -> Result<usize, String> {
+        let config = fetch_protocol_config(client)?;
+        let mut machines = HashMap::new();
+        for (address, data) in fetch(client, &Machine::DISCRIMINATOR)? {
+            match Machine::try_deserialize(&mut data.as_slice()) {
+                Ok(machine) => {
+                    machines.insert(address.to_string(), MachineListing::from_machine(&address, &machine));
+                }
+                Err(e) => warn!("Skipping invalid machine account {}: {}", address, e),
+            }
+        }
+        for (address, data) in fetch(client, &MachineSpec::DISCRIMINATOR)? {
+            match MachineSpec::try_deserialize(&mut data.as_slice()) {
+                Ok(spec) => {
+                    if let Some(listing) = machines.get_mut(&spec.machine.to_string()) {
+                        listing.spec = Some(SpecRecord::from(&spec.spec));
+                    }
+                }
+                Err(e) => warn!("Skipping invalid machine spec account {}: {}", address, e),
+            }
+        }
+        for (address, data) in fetch(client, &MachineHeartbeat::DISCRIMINATOR)? {
+            match MachineHeartbeat::try_deserialize(&mut data.as_slice()) {
+                Ok(heartbeat) => {
+                    if let Some(listing) = machines.get_mut(&heartbeat.machine.to_string()) {
+                        listing.heartbeat = Some(HeartbeatRecord {
+                            first_seen: heartbeat.first_seen,
+                            last_seen: heartbeat.last_seen,
+                            beats: heartbeat.beats,
+                        });
+                    }
+                }
+                Err(e) => warn!("Skipping invalid machine heartbeat account {}: {}", address, e),
+            }
+        }
+        let count = machines.len();
+        *self.machines.write().unwrap() = machines;
+        self.set_params(config.params);
+        info!("Indexed {} machines", count);
+        Ok(count)
+    }
+
+    /// Applies the machine events of a transaction to the index.
+    pub fn apply_logs(&self, program_id: &Pubkey, logs: &[String]) {
+        for data in program_data(program_id, logs) {
+            if data.len() < 8 {
+                continue;
+            }
+            let (discriminator, mut payload) = data.split_at(8);
+            if discriminator == MachineEvent::DISCRIMINATOR {
+                match MachineEvent::deserialize(&mut payload) {
+                    Ok(event) => self.upsert(&event),
+                    Err(e) => warn!("Malformed MachineEvent: {}", e),
+                }
+            } else if discriminator == MachineRemovedEvent::DISCRIMINATOR {
+                match MachineRemovedEvent::deserialize(&mut payload) {
+                    Ok(event) => {
+                        let address = machine_pda(&event.owner, &event.uuid).to_string();
+                        self.machines.write().unwrap().remove(&address);
+                    }
+                    Err(e) => warn!("Malformed MachineRemovedEvent: {}", e),
+                }
+            } else if discriminator == MachineSpecEvent::DISCRIMINATOR {
+                match MachineSpecEvent::deserialize(&mut payload) {
+                    Ok(event) => self.set_spec(&event.machine, Some(SpecRecord::from(&event.spec))),
+                    Err(e) => warn!("Malformed MachineSpecEvent: {}", e),
+                }
+            } else if discriminator == MachineSpecRemovedEvent::DISCRIMINATOR {
+                match MachineSpecRemovedEvent::deserialize(&mut payload) {
+                    Ok(event) => self.set_spec(&event.machine, None),
+                    Err(e) => warn!("Malformed MachineSpecRemovedEvent: {}", e),
+                }
+            } else if discriminator == HeartbeatEvent::DISCRIMINATOR {
+                match HeartbeatEvent::deserialize(&mut payload) {
+                    Ok(event) => self.record_heartbeat(&event),
+                    Err(e) => warn!("Malformed HeartbeatEvent: {}", e),
+                }
+            }
+        }
+    }
+
+    fn upsert(&self, event: &MachineEvent) {
+        let address = machine_pda(&event.owner, &event.uuid);
+        let mut machines = self.machines.write().unwrap();
+        let listing = machines.entry(address.to_string()).or_insert_with(|| MachineListing {
+            address: address.to_string(),
+            owner: event.owner.to_string(),
+            uuid: Uuid::from_bytes(event.uuid).simple().to_string(),
+            status: String::new(),
+            price: 0,
+            max_duration: 0,
+            disk: 0,
+            score: 0,
+            completed_count: 0,
+            failed_count: 0,
+            spec: None,
+            heartbeat: None,
+            online: false,
+            uptime_bps: 0,
+        });
+        listing.status = status_name(&event.status).to_string();
+        listing.price = event.price;
+        listing.max_duration = event.max_duration;
+        listing.disk = event.disk;
+    }
+
+    fn set_spec(&self, machine: &Pubkey, spec: Option<SpecRecord>) {
+        if let Some(listing) = self.machines.write().unwrap().get_mut(&machine.to_string()) {
+            listing.spec = spec;
+        }
+    }
+
+    fn record_heartbeat(&self, event: &HeartbeatEvent) {
+        if let Some(listing) = self.machines.write().unwrap().get_mut(&event.machine.to_string()) {
+            let record = listing.heartbeat.get_or_insert(HeartbeatRecord {
+                first_seen: event.last_seen,
+                last_seen: event.last_seen,
+                beats: 0,
+            });
+            record.last_seen = record.last_seen.max(event.last_seen);
+            record.beats = record.beats.max(event.beats);
+        }
+    }
+
+    /// Returns one page of the machines matching the query, with their liveness as of `now`.
+    ///
+    /// Without a sort the machines are ordered by address, so pages are stable between requests.
+    /// Until the protocol parameters are known every machine is reported offline.
+    pub fn search(&self, query: &MachineQuery, now: i64) -> MachinePage {
+        let params = *self.params.read().unwrap();
+        let machines = self.machines.read().unwrap();
+        let mut listings: Vec<MachineListing> = machines
+            .values()
+            .cloned()
+            .map(|mut listing| {
+                if let Some(params) = &params {
+                    listing.refresh_liveness(now, params);
+                }
+                listing
+            })
+            .filter(|listing| query.matches(listing))
+            .collect();
+        listings.sort_by(|a, b| {
+            let order = match query.sort {
+                Some(MachineSort::PriceAsc) => a.price.cmp(&b.price),
+                Some(MachineSort::PriceDesc) => b.price.cmp(&a.price),
+                Some(MachineSort::ScoreAsc) => a.score.cmp(&b.score),
+                Some(MachineSort::ScoreDesc) => b.score.cmp(&a.score),
+                None => Ordering::Equal,
+            };
+            order.then_with(|| a.address.cmp(&b.address))
+        });
+
+        let (page, page_size) = query.page();
+        let total = listings.len();
+        let machines = listings
+            .into_iter()
+            .skip((page - 1).saturating_mul(page_size))
+            .take(page_size)
+            .collect();
+        MachinePage {
+            total,
+            page,
+            page_size,
+            machines,
+        }
+    }
+
+    /// Sets the protocol parameters liveness is judged by.
+    pub fn set_params(&self, params: ProtocolParams) {
+        *self.params.write().unwrap() = Some(params);
+    }
+
+    /// Adds or replaces a listing, for ingesting accounts fetched elsewhere.
+    pub fn insert(&self, listing: MachineListing) {
+        self.machines
+            .write()
+            .unwrap()
+            .insert(listing.address.clone(), listing);
+    }
+}
+
+fn fetch(client: &RpcClient, discriminator: &[u8]) -> Result<Vec<(Pubkey, Vec<u8>)>, String> {
+    let config = RpcProgramAccountsConfig {
+        filters: Some(vec![RpcFilterType::Memcmp(Memcmp::new_base58_encoded(
+            0,
+            discriminator,
+        ))]),
+        account_config: RpcAccountInfoConfig {
+            commitment: Some(client.commitment()),
+            ..RpcAccountInfoConfig::default()
+        },
+        ..RpcProgramAccountsConfig::default()
+    };
+    client
+        .get_program_accounts_with_config(&crate::ID, config)
+        .map(|accounts| {
+            accounts
+                .into_iter()
+                .map(|(address, account)| (address, account.data))
+                .collect()
+        })
+        .map_err(|e| format!("Error fetching machines: {}", e))
+}
@@ -1,3 +1,7 @@
 pub mod chain;
 pub mod analytics;
 pub mod market;
+pub mod events;
+pub mod model_registry;
+pub mod licensing;
+pub mod machine_index;
@@ -0,0 +1,187 @@
+use anchor_lang::{AccountDeserialize, AnchorDeserialize, Discriminator};
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use solana_client::rpc_client::RpcClient;
+use solana_client::rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig};
+use solana_client::rpc_filter::{Memcmp, RpcFilterType};
+use solana_sdk::hash::hash;
+use solana_sdk::pubkey::Pubkey;
+use std::collections::HashMap;
+use std::sync::RwLock;
+use crate::ai::ai_model::AiModel;
+use crate::services::events::program_data;
+use crate::utils::ai_model::{AiModelEvent, AiModelRemovedEvent};
+
+pub fn ai_model_pda(owner: &Pubkey, name: &str) -> Pubkey {
+    Pubkey::find_program_address(
+        &[b"ai-model", owner.as_ref(), hash(name.as_bytes()).as_ref()],
+        &crate::ID,
+    )
+    .0
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ModelRecord {
+    pub address: String,
+    pub owner: String,
+    pub name: String,
+    pub framework: u8,
+    pub license: u8,
+    pub type1: u8,
+    pub type2: u8,
+    pub tags: Vec<String>,
+    pub create_time: i64,
+    pub update_time: i64,
+}
+
+impl ModelRecord {
+    fn from_model(address: &Pubkey, model: &AiModel) -> Self {
+        ModelRecord {
+            address: address.to_string(),
+            owner: model.owner.to_string(),
+            name: model.name.clone(),
+            framework: model.framework,
+            license: model.license as u8,
+            type1: model.type1,
+            type2: model.type2,
+            tags: split_tags(&model.tags),
+            create_time: model.create_time,
+            update_time: model.update_time,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct ModelQuery {
+    pub framework: Option<u8>,
+    pub license: Option<u8>,
+    pub type1: Option<u8>,
+    pub type2: Option<u8>,
+    pub tag: Option<String>,
+    pub owner: Option<String>,
+}
+
+impl ModelQuery {
+    pub fn matches(&self, record: &ModelRecord) -> bool {
+        self.framework.map_or(true, |framework| record.framework == framework)
+            && self.license.map_or(true, |license| record.license == license)
+            && self.type1.map_or(true, |type1| record.type1 == type1)
+            && self.type2.map_or(true, |type2| record.type2 == type2)
+            && self.owner.as_ref().map_or(true, |owner| &record.owner == owner)
+            && self.tag.as_ref().map_or(true, |tag| {
+                record.tags.iter().any(|t| t.eq_ignore_ascii_case(tag.trim()))
+            })
+    }
+}
+
+/// Tags are stored on chain as a single comma-separated string.
+pub fn split_tags(tags: &str) -> Vec<String> {
+    tags.split(',')
+        .map(|tag| tag.trim().to_string())
+        .filter(|tag| !tag.is_empty())
+        .collect()
+}
+
+/// In-memory index of the registered AI models.
+///
+/// `load` rebuilds it from the program accounts and is called periodically by the API server;
+/// `apply_logs` can apply events from transaction logs between two loads.
+#[derive(Default)]
+pub struct ModelRegistry {
+    models: RwLock<HashMap<String, ModelRecord>>,
+}
+
+impl ModelRegistry {
+    /// Replaces the index with every `AiModel` account of the program.
+    pub fn load(&self, client: &RpcClient) -> Result<usize, String> {
+        let config = RpcProgramAccountsConfig {
+            filters: Some(vec![RpcFilterType::Memcmp(Memcmp::new_base58_encoded(
+                0,
+                &AiModel::DISCRIMINATOR,
+            ))]),
+            account_config: RpcAccountInfoConfig {
+                commitment: Some(client.commitment()),
+                ..RpcAccountInfoConfig::default()
+            },
+            ..RpcProgramAccountsConfig::default()
+        };
+        let accounts = client
+            .get_program_accounts_with_config(&crate::ID, config)
+            .map_err(|e| format!("Error fetching AI models: {}", e))?;
+
+        let mut models = HashMap::with_capacity(accounts.len());
+        for (address, account) in accounts {
+            match AiModel::try_deserialize(&mut account.data.as_slice()) {
+                Ok(model) => {
+                    models.insert(address.to_string(), ModelRecord::from_model(&address, &model));
+                }
+                Err(e) => warn!("Skipping invalid AI model account {}: {}", address, e),
+            }
+        }
+        let count = models.len();
+        *self.models.write().unwrap() = models;
+        info!("Indexed {} AI models", count);
+        Ok(count)
+    }
+
+    /// Applies the AI model events of a transaction to the index.
+    pub fn apply_logs(&self, program_id: &Pubkey, logs: &[String]) {
+        for data in program_data(program_id, logs) {
+            if data.len() < 8 {
+                continue;
+            }
+            let (discriminator, mut payload) = data.split_at(8);
+            if discriminator == AiModelEvent::DISCRIMINATOR {
+                match AiModelEvent::deserialize(&mut payload) {
+                    Ok(event) => self.upsert(&event),
+                    Err(e) => warn!("Malformed AiModelEvent: {}", e),
+                }
+            } else if discriminator == AiModelRemovedEvent::DISCRIMINATOR {
+                match AiModelRemovedEvent::deserialize(&mut payload) {
+                    Ok(event) => self.remove(&event),
+                    Err(e) => warn!("Malformed AiModelRemovedEvent: {}", e),
+                }
+            }
+        }
+    }
+
+    fn upsert(&self, event: &AiModelEvent) {
+        let address = ai_model_pda(&event.owner, &event.name).to_string();
+        let mut models = self.models.write().unwrap();
+        let record = models.entry(address.clone()).or_insert_with(|| ModelRecord {
+            address,
+            owner: event.owner.to_string(),
+            name: event.name.clone(),
+            framework: 0,
+            license: 0,
+            type1: 0,
+            type2: 0,
+            tags: Vec::new(),
+            create_time: event.update_time,
+            update_time: event.update_time,
+        });
+        record.framework = event.framework;
+        record.license = event.license as u8;
+        record.type1 = event.type1;
+        record.type2 = event.type2;
+        record.tags = split_tags(&event.tags);
+        record.update_time = event.update_time;
+    }
+
+    fn remove(&self, event: &AiModelRemovedEvent) {
+        let address = ai_model_pda(&event.owner, &event.name).to_string();
+        self.models.write().unwrap().remove(&address);
+    }
+
+    /// Returns the models matching the query, ordered by name.
+    pub fn search(&self, query: &ModelQuery) -> Vec<ModelRecord> {
+        let models = self.models.read().unwrap();
+        let mut records: Vec<ModelRecord> = models
+            .values()
+            .filter(|record| query.matches(record))
+            .cloned()
+            .collect();
+        records.sort_by(|a, b| a.name.cmp(&b.name).then_with(|| a.address.cmp(&b.address)));
+        records
+    }
+}
@@ -0,0 +1,59 @@
+use anchor_lang::prelude::*;
+use crate::state::protocol_config::ProtocolParams;
+
+/// Liveness of a machine, recorded by the heartbeats of its provider.
+///
+/// Time is split into slots of `ProtocolParams::heartbeat_interval` seconds and at most one
+/// heartbeat counts per slot, so sending more often does not raise the uptime.
+#[account]
+#[derive(InitSpace)]
+pub struct MachineHeartbeat {
+    pub machine: Pubkey,
+    pub first_seen: i64,
+    pub last_seen: i64,
+    pub beats: u64,
+}
+
+impl MachineHeartbeat {
+    /// Records a heartbeat at `now`, returning whether it counted towards the uptime.
+    pub fn record(&mut self, machine: Pubkey, now: i64, params: &ProtocolParams) -> bool {
+        let interval = params.heartbeat_interval;
+        if self.beats == 0 {
+            self.machine = machine;
+            self.first_seen = now;
+        }
+        let counted = self.beats == 0 || now / interval > self.last_seen / interval;
+        if counted {
+            self.beats = self.beats.saturating_add(1);
+        }
+        self.last_seen = self.last_seen.max(now);
+        counted
+    }
+
+    pub fn is_online(&self, now: i64, params: &ProtocolParams) -> bool {
+        self.beats > 0
+            && now.saturating_sub(self.last_seen)
+                <= params
+                    .heartbeat_interval
+                    .saturating_mul(params.heartbeat_max_missed)
+    }
+
+    /// Slots since the first heartbeat, including the current one.
+    pub fn expected_beats(&self, now: i64, params: &ProtocolParams) -> u64 {
+        if self.beats == 0 {
+            return 0;
+        }
+        let interval = params.heartbeat_interval;
+        let slots = now.max(self.first_seen) / interval - self.first_seen / interval;
+        slots as u64 + 1
+    }
+
+    /// Share of the expected heartbeats received, in basis points.
+    pub fn uptime_bps(&self, now: i64, params: &ProtocolParams) -> u16 {
+        let expected = self.expected_beats(now, params);
+        if expected == 0 {
+            return 0;
+        }
+        (self.beats.min(expected) * 10_000 / expected) as u16
+    }
+}
@@ -0,0 +1,60 @@
+use anchor_lang::prelude::*;
+use crate::ai::ai_model::ModelVersion;
+use crate::errors::DistriAIError;
+
+/// Binds an order or task to the model version it runs.
+///
+/// `ai_model` is the first field so that the jobs of a model can be listed with a single
+/// `memcmp` filter.
+#[account]
+#[derive(InitSpace)]
+pub struct JobBinding {
+    pub ai_model: Pubkey,
+    pub model_version: Pubkey,
+    pub job: Pubkey,
+    pub kind: JobKind,
+    pub requester: Pubkey,
+    pub machine_id: [u8; 16],
+    /// Model version produced by the job, or the default key while there is none.
+    pub output_version: Pubkey,
+    pub bind_time: i64,
+    pub complete_time: i64,
+}
+
+impl JobBinding {
+    /// Offset of `ai_model` in the account data.
+    pub const AI_MODEL_OFFSET: usize = 8;
+
+    /// Checks that a version belongs to `ai_model` and was published no earlier than `not_before`,
+    /// which rules out versions left behind by a removed model of the same address.
+    pub fn check_version(
+        ai_model: &Pubkey,
+        not_before: i64,
+        version: &ModelVersion,
+    ) -> Result<()> {
+        require_keys_eq!(version.ai_model, *ai_model, DistriAIError::InvalidVersion);
+        require_gte!(version.create_time, not_before, DistriAIError::InvalidVersion);
+        Ok(())
+    }
+
+    /// Records the completion of a bound order and the version it produced, which must have been
+    /// published for the bound model after the order was bound.
+    pub fn complete(
+        &mut self,
+        output_version: Option<(Pubkey, &ModelVersion)>,
+        now: i64,
+    ) -> Result<()> {
+        if let Some((address, version)) = output_version {
+            Self::check_version(&self.ai_model, self.bind_time, version)?;
+            self.output_version = address;
+        }
+        self.complete_time = now;
+        Ok(())
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq, InitSpace)]
+pub enum JobKind {
+    Order,
+    Task,
+}
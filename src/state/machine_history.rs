@@ -0,0 +1,25 @@
+use anchor_lang::prelude::*;
+
+/// Lifetime settlement record of a machine, kept across reward periods.
+///
+/// Only orders that settled hours to the provider from another buyer are counted, see
+/// `distribution::settled_order_hours`, so renting a machine to oneself builds no history.
+#[account]
+#[derive(InitSpace)]
+pub struct MachineHistory {
+    pub machine: Pubkey,
+    pub settled_orders: u32,
+    pub settled_hours: u64,
+}
+
+impl MachineHistory {
+    /// Records a completed order that settled `hours` to the provider.
+    pub fn record(&mut self, machine: Pubkey, hours: u32) {
+        self.machine = machine;
+        if hours == 0 {
+            return;
+        }
+        self.settled_orders = self.settled_orders.saturating_add(1);
+        self.settled_hours = self.settled_hours.saturating_add(hours as u64);
+    }
+}
@@ -0,0 +1,29 @@
+use anchor_lang::prelude::*;
+
+/// Hardware of a machine, in the units buyers filter on.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, Default, PartialEq, InitSpace)]
+pub struct HardwareSpec {
+    #[max_len(64)]
+    pub gpu_model: String,
+    pub gpu_count: u8,
+    pub vram_gb: u32,
+    pub cpu_cores: u16,
+    pub ram_gb: u32,
+    pub disk_gb: u32,
+    #[max_len(32)]
+    pub region: String,
+}
+
+impl HardwareSpec {
+    pub const GPU_MODEL_MAX_LENGTH: usize = 64;
+    pub const REGION_MAX_LENGTH: usize = 32;
+}
+
+/// Typed hardware spec of a machine, kept next to the machine so `Machine.metadata` stays free-form.
+#[account]
+#[derive(InitSpace)]
+pub struct MachineSpec {
+    pub machine: Pubkey,
+    pub spec: HardwareSpec,
+    pub update_time: i64,
+}
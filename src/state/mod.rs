@@ -0,0 +1,12 @@
+pub mod heartbeat;
+pub mod job;
+pub mod machine_history;
+pub mod machine_spec;
+pub mod order_rating;
+pub mod payment;
+pub mod protocol_config;
+pub mod queue;
+pub mod reward_period;
+pub mod stake;
+pub mod verifier;
+pub mod vesting;
@@ -0,0 +1,12 @@
+use anchor_lang::prelude::*;
+
+/// Score given by the buyer of a completed order. One per order.
+#[account]
+#[derive(InitSpace)]
+pub struct OrderRating {
+    pub order: Pubkey,
+    pub buyer: Pubkey,
+    pub machine_id: [u8; 16],
+    pub score: u8,
+    pub rate_time: i64,
+}
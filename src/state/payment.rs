@@ -0,0 +1,32 @@
+use anchor_lang::prelude::*;
+
+#[account]
+#[derive(InitSpace)]
+pub struct MachinePrice {
+    pub owner: Pubkey,
+    pub machine_id: [u8; 16],
+    pub mint: Pubkey,
+    pub price: u64,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct OrderPayment {
+    pub order: Pubkey,
+    pub mint: Pubkey,
+}
+
+impl OrderPayment {
+    /// Returns the payment mint recorded for an order.
+    ///
+    /// Orders placed before multi-mint payments have no payment account and were paid in the
+    /// protocol mint, passed as `default_mint`.
+    pub fn mint_of(info: &AccountInfo, default_mint: Pubkey) -> Result<Pubkey> {
+        if info.data_is_empty() {
+            return Ok(default_mint);
+        }
+        require_keys_eq!(*info.owner, crate::ID);
+        let data = info.try_borrow_data()?;
+        Ok(OrderPayment::try_deserialize(&mut &data[..])?.mint)
+    }
+}
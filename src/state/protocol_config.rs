@@ -0,0 +1,142 @@
+use anchor_lang::prelude::*;
+use crate::errors::DistriAIError;
+
+/// Tunable parameters of the protocol, changed together through `update_config`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, InitSpace)]
+pub struct ProtocolParams {
+    /// Seconds after an order is placed before the buyer can refund it while still `Preparing`.
+    pub order_cancel_window: i64,
+    /// Seconds in one unit of order duration.
+    pub duration_unit: i64,
+    /// Start time of reward period 0.
+    pub reward_genesis_time: i64,
+    /// Seconds in one reward period.
+    pub reward_period_duration: i64,
+    /// Reward pool of period 0.
+    pub reward_initial_pool: u64,
+    /// Number of periods after which the pool halves, or 0 to keep it constant.
+    pub reward_halving_periods: u32,
+    /// Seconds between two expected machine heartbeats.
+    pub heartbeat_interval: i64,
+    /// Heartbeats a machine can miss before it is treated as offline.
+    pub heartbeat_max_missed: i64,
+}
+
+impl ProtocolParams {
+    pub fn validate(&self) -> Result<()> {
+        require_gte!(self.order_cancel_window, 0, DistriAIError::InvalidConfig);
+        require_gt!(self.duration_unit, 0, DistriAIError::InvalidConfig);
+        require_gt!(self.reward_period_duration, 0, DistriAIError::InvalidConfig);
+        require_gt!(self.heartbeat_interval, 0, DistriAIError::InvalidConfig);
+        require_gt!(self.heartbeat_max_missed, 0, DistriAIError::InvalidConfig);
+        Ok(())
+    }
+
+    /// Checks that `new` can replace these parameters.
+    ///
+    /// The order duration unit and the reward period schedule are fixed at initialization: live
+    /// orders store their duration in units, and periods already closed or claimed are numbered
+    /// from the genesis time with the period duration.
+    pub fn validate_update(&self, new: &ProtocolParams) -> Result<()> {
+        new.validate()?;
+        require_eq!(new.duration_unit, self.duration_unit, DistriAIError::InvalidConfig);
+        require_eq!(
+            new.reward_genesis_time,
+            self.reward_genesis_time,
+            DistriAIError::InvalidConfig
+        );
+        require_eq!(
+            new.reward_period_duration,
+            self.reward_period_duration,
+            DistriAIError::InvalidConfig
+        );
+        Ok(())
+    }
+}
+
+/// Protocol configuration, a single PDA read by the instructions that depend on its values.
+///
+/// The mint is set once at initialization, since vaults and pools are derived from it.
+#[account]
+#[derive(InitSpace)]
+pub struct ProtocolConfig {
+    pub authority: Pubkey,
+    /// Authority proposed by `transfer_authority`, or the default key when none is pending.
+    pub pending_authority: Pubkey,
+    /// Key allowed to pause and resume instructions, set by the authority.
+    pub guardian: Pubkey,
+    /// Bitmask of the paused instructions, see the `PAUSE_*` flags.
+    pub paused: u64,
+    pub mint: Pubkey,
+    pub params: ProtocolParams,
+    /// Key allowed to record calls served under per-call model licenses, or the default key.
+    pub metering_authority: Pubkey,
+    /// Mints that model owners may price their licenses in.
+    #[max_len(8)]
+    pub payment_mints: Vec<Pubkey>,
+}
+
+impl ProtocolConfig {
+    // Instructions that take payments, settle them to sellers or model owners, or pay out
+    // rewards, including vested ones. Refunds, failed orders and stake withdrawals have no flag
+    // so users can always get their funds back.
+    pub const PAUSE_PLACE_ORDER: u64 = 1 << 0;
+    pub const PAUSE_RENEW_ORDER: u64 = 1 << 1;
+    pub const PAUSE_QUEUE_ORDER: u64 = 1 << 2;
+    pub const PAUSE_CLAIM: u64 = 1 << 3;
+    pub const PAUSE_REWARD_POOL_DEPOSIT: u64 = 1 << 4;
+    pub const PAUSE_STAKE_DEPOSIT: u64 = 1 << 5;
+    pub const PAUSE_COMPLETE_ORDER: u64 = 1 << 6;
+    pub const PAUSE_PURCHASE_LICENSE: u64 = 1 << 7;
+    pub const PAUSE_ALL: u64 = (1 << 8) - 1;
+
+    pub const MAX_PAYMENT_MINTS: usize = 8;
+
+    pub fn is_paused(&self, flag: u64) -> bool {
+        self.paused & flag != 0
+    }
+
+    pub fn require_active(&self, flag: u64) -> Result<()> {
+        require!(!self.is_paused(flag), DistriAIError::Paused);
+        Ok(())
+    }
+
+    pub fn accepts_payment_mint(&self, mint: &Pubkey) -> bool {
+        self.payment_mints.contains(mint)
+    }
+
+    pub fn current_period(&self, now: i64) -> Result<u32> {
+        require_gte!(
+            now,
+            self.params.reward_genesis_time,
+            DistriAIError::InvalidPeriod
+        );
+        let elapsed = now - self.params.reward_genesis_time;
+        u32::try_from(elapsed / self.params.reward_period_duration)
+            .map_err(|_| error!(DistriAIError::InvalidPeriod))
+    }
+
+    pub fn period_start_time(&self, period: u32) -> i64 {
+        self.params
+            .reward_genesis_time
+            .saturating_add(self.params.reward_period_duration.saturating_mul(period.into()))
+    }
+
+    pub fn period_pool(&self, period: u32) -> u64 {
+        if self.params.reward_halving_periods == 0 {
+            return self.params.reward_initial_pool;
+        }
+        self.params
+            .reward_initial_pool
+            .checked_shr(period / self.params.reward_halving_periods)
+            .unwrap_or(0)
+    }
+
+    /// Seconds covered by `duration` units of an order.
+    pub fn duration_seconds(&self, duration: u32) -> Result<i64> {
+        self.params
+            .duration_unit
+            .checked_mul(duration.into())
+            .ok_or_else(|| error!(DistriAIError::MathOverflow))
+    }
+}
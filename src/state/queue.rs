@@ -0,0 +1,65 @@
+use anchor_lang::prelude::*;
+
+#[account]
+#[derive(InitSpace)]
+pub struct MachineQueue {
+    pub owner: Pubkey,
+    pub machine_id: [u8; 16],
+    pub head: u64,
+    pub tail: u64,
+}
+
+impl MachineQueue {
+    pub fn is_empty(&self) -> bool {
+        self.head == self.tail
+    }
+
+    pub fn len(&self) -> u64 {
+        self.tail.saturating_sub(self.head)
+    }
+
+    /// Returns the number of orders in the queue, or 0 if nothing was ever queued on the machine.
+    pub fn len_of(info: &AccountInfo) -> Result<u64> {
+        if info.data_is_empty() {
+            return Ok(0);
+        }
+        require_keys_eq!(*info.owner, crate::ID);
+        let data = info.try_borrow_data()?;
+        Ok(MachineQueue::try_deserialize(&mut &data[..])?.len())
+    }
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct QueuedOrder {
+    pub order_id: [u8; 16],
+    pub buyer: Pubkey,
+    pub seller: Pubkey,
+    pub machine_id: [u8; 16],
+    pub position: u64,
+    pub mint: Pubkey,
+    pub price: u64,
+    pub duration: u32,
+    pub total: u64,
+    #[max_len(1024)]
+    pub metadata: String,
+    pub status: QueuedOrderStatus,
+    pub queue_time: i64,
+    pub expire_time: i64,
+}
+
+impl QueuedOrder {
+    pub const METADATA_MAX_LENGTH: usize = 1024;
+
+    pub fn is_expired(&self, now_ts: i64) -> bool {
+        now_ts >= self.expire_time
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq, InitSpace)]
+pub enum QueuedOrderStatus {
+    Queued,
+    Activated,
+    Cancelled,
+    Expired,
+}
@@ -0,0 +1,49 @@
+use anchor_lang::prelude::*;
+
+#[account]
+#[derive(InitSpace)]
+pub struct RewardPeriod {
+    pub period: u32,
+    pub machine_num: u32,
+    pub total_weight: u64,
+    pub reserved: u64,
+    pub claimed: u64,
+    pub closed: bool,
+    pub expired: bool,
+    pub close_time: i64,
+}
+
+impl RewardPeriod {
+    /// Seconds after a period is closed during which its rewards can be claimed.
+    pub const CLAIM_EXPIRY: i64 = 8 * 7 * 24 * 3600;
+
+    pub fn remaining(&self) -> u64 {
+        self.reserved.saturating_sub(self.claimed)
+    }
+
+    pub fn expire_time(&self) -> i64 {
+        self.close_time.saturating_add(Self::CLAIM_EXPIRY)
+    }
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct RewardLedger {
+    pub outstanding: u64,
+    pub total_reserved: u64,
+    pub total_claimed: u64,
+    pub total_expired: u64,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct RewardWork {
+    pub period: u32,
+    pub owner: Pubkey,
+    pub machine_id: [u8; 16],
+    pub task_num: u32,
+    pub order_hours: u32,
+    pub score: u8,
+    pub weight: u64,
+    pub claimed: bool,
+}
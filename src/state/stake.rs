@@ -0,0 +1,61 @@
+use anchor_lang::prelude::*;
+use crate::errors::DistriAIError;
+
+#[account]
+#[derive(InitSpace)]
+pub struct MachineStake {
+    pub owner: Pubkey,
+    pub machine_id: [u8; 16],
+    pub amount: u64,
+    pub active_orders: u32,
+    pub slashed: u64,
+    pub last_active_time: i64,
+}
+
+impl MachineStake {
+    /// Minimum stake (1000 DIST) required to rent out a machine.
+    pub const MIN_FOR_RENT: u64 = 1_000_000_000_000;
+    /// Seconds that must pass after the last active order before stake can be withdrawn.
+    pub const WITHDRAW_COOLDOWN: i64 = 7 * 24 * 3600;
+    /// Share of the stake, in basis points, slashed to the buyer of a failed order.
+    pub const SLASH_BPS: u64 = 1_000;
+    /// Cap of a single slash as a multiple of the total paid for the failed order.
+    pub const SLASH_MAX_TOTAL_MULTIPLE: u64 = 2;
+
+    /// Returns the amount slashed for a failed order of `order_total`.
+    pub fn slash_amount(&self, order_total: u64) -> u64 {
+        let share = self.amount.saturating_mul(Self::SLASH_BPS) / 10_000;
+        share.min(order_total.saturating_mul(Self::SLASH_MAX_TOTAL_MULTIPLE))
+    }
+
+    pub fn withdrawable_time(&self) -> i64 {
+        self.last_active_time.saturating_add(Self::WITHDRAW_COOLDOWN)
+    }
+
+    /// Returns the stake left after withdrawing `amount` at `now`.
+    ///
+    /// Stake is locked while orders are active and for the cooldown after the last one. A machine
+    /// offered for rent must keep the minimum stake.
+    pub fn remaining_after_withdraw(&self, amount: u64, now: i64, for_rent: bool) -> Result<u64> {
+        require_eq!(self.active_orders, 0, DistriAIError::StakeLocked);
+        require_gte!(now, self.withdrawable_time(), DistriAIError::StakeLocked);
+        let remaining = self
+            .amount
+            .checked_sub(amount)
+            .ok_or_else(|| error!(DistriAIError::InsufficientStake))?;
+        if for_rent {
+            require_gte!(remaining, Self::MIN_FOR_RENT, DistriAIError::InsufficientStake);
+        }
+        Ok(remaining)
+    }
+
+    /// Returns the staked amount, or 0 if the machine was never staked.
+    pub fn amount_of(info: &AccountInfo) -> Result<u64> {
+        if info.data_is_empty() {
+            return Ok(0);
+        }
+        require_keys_eq!(*info.owner, crate::ID);
+        let data = info.try_borrow_data()?;
+        Ok(MachineStake::try_deserialize(&mut &data[..])?.amount)
+    }
+}
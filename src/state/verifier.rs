@@ -0,0 +1,75 @@
+use anchor_lang::prelude::*;
+use crate::errors::DistriAIError;
+use crate::state::reward_period::RewardPeriod;
+
+#[account]
+#[derive(InitSpace)]
+pub struct Verifier {
+    pub key: Pubkey,
+    pub active: bool,
+    pub attested_count: u64,
+    pub challenged_count: u64,
+    pub register_time: i64,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct TaskProof {
+    pub task: Pubkey,
+    pub owner: Pubkey,
+    pub machine_id: [u8; 16],
+    pub period: u32,
+    pub result_hash: [u8; 32],
+    pub status: TaskProofStatus,
+    pub verifier: Pubkey,
+    pub submit_time: i64,
+    pub verify_time: i64,
+}
+
+impl TaskProof {
+    /// Marks a pending task verified by `verifier`.
+    ///
+    /// Tasks can be attested until their period is closed, so tasks submitted near the end of a
+    /// period are not lost.
+    pub fn attest(
+        &mut self,
+        verifier: Pubkey,
+        result_hash: [u8; 32],
+        reward_period: &RewardPeriod,
+        now: i64,
+    ) -> Result<()> {
+        require!(
+            self.status == TaskProofStatus::Pending,
+            DistriAIError::IncorrectStatus
+        );
+        require!(!reward_period.closed, DistriAIError::PeriodClosed);
+        require!(
+            self.result_hash == result_hash,
+            DistriAIError::ResultHashMismatch
+        );
+        self.status = TaskProofStatus::Verified;
+        self.verifier = verifier;
+        self.verify_time = now;
+        Ok(())
+    }
+
+    /// Invalidates a task until its period is closed, returning whether it had been verified.
+    pub fn challenge(&mut self, reward_period: &RewardPeriod, now: i64) -> Result<bool> {
+        require!(
+            self.status != TaskProofStatus::Invalidated,
+            DistriAIError::IncorrectStatus
+        );
+        require!(!reward_period.closed, DistriAIError::PeriodClosed);
+        let was_verified = self.status == TaskProofStatus::Verified;
+        self.status = TaskProofStatus::Invalidated;
+        self.verify_time = now;
+        Ok(was_verified)
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq, InitSpace)]
+pub enum TaskProofStatus {
+    Pending,
+    Verified,
+    Invalidated,
+}
@@ -0,0 +1,82 @@
+use anchor_lang::prelude::*;
+use crate::errors::DistriAIError;
+
+#[account]
+#[derive(InitSpace)]
+pub struct VestingConfig {
+    pub enabled: bool,
+    pub cliff: i64,
+    pub duration: i64,
+    pub accelerate_threshold: u32,
+}
+
+impl VestingConfig {
+    pub fn validate(&self) -> Result<()> {
+        require_gte!(self.cliff, 0, DistriAIError::InvalidConfig);
+        require_gte!(self.duration, self.cliff, DistriAIError::InvalidConfig);
+        require_gt!(self.accelerate_threshold, 0, DistriAIError::InvalidConfig);
+        Ok(())
+    }
+
+    /// Returns whether a machine with `settled_hours` of lifetime orders may accelerate vesting.
+    pub fn can_accelerate(&self, settled_hours: u64) -> bool {
+        self.accelerate_threshold > 0 && settled_hours >= self.accelerate_threshold as u64
+    }
+
+    /// Returns the vesting config if one has been set and vesting is enabled.
+    pub fn enabled_of(info: &AccountInfo) -> Result<Option<VestingConfig>> {
+        if info.data_is_empty() {
+            return Ok(None);
+        }
+        require_keys_eq!(*info.owner, crate::ID);
+        let data = info.try_borrow_data()?;
+        let config = VestingConfig::try_deserialize(&mut &data[..])?;
+        Ok(if config.enabled { Some(config) } else { None })
+    }
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct VestingSchedule {
+    pub owner: Pubkey,
+    pub machine_id: [u8; 16],
+    pub period: u32,
+    pub total: u64,
+    pub withdrawn: u64,
+    pub start_time: i64,
+    pub cliff: i64,
+    pub duration: i64,
+    pub accelerated: bool,
+}
+
+impl VestingSchedule {
+    /// Amount vested at `now`: nothing before the cliff, then linear until `start_time + duration`.
+    pub fn vested_amount(&self, now: i64) -> u64 {
+        if self.accelerated {
+            return self.total;
+        }
+        let elapsed = now.saturating_sub(self.start_time);
+        if elapsed < self.cliff {
+            return 0;
+        }
+        if self.duration <= 0 || elapsed >= self.duration {
+            return self.total;
+        }
+        (self.total as u128 * elapsed as u128 / self.duration as u128) as u64
+    }
+
+    pub fn withdrawable(&self, now: i64) -> u64 {
+        self.vested_amount(now).saturating_sub(self.withdrawn)
+    }
+
+    /// Marks everything vested at `now` as withdrawn and returns the amount to pay out.
+    pub fn withdraw(&mut self, now: i64) -> Result<u64> {
+        let amount = self.withdrawable(now);
+        require_gt!(amount, 0, DistriAIError::NothingVested);
+        self.withdrawn = self
+            .withdrawn
+            .checked_add(amount)
+            .ok_or_else(|| error!(DistriAIError::MathOverflow))?;
+        Ok(amount)
+    }
+}
@@ -0,0 +1,267 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::hash::hash;
+use crate::ai::ai_model::*;
+use crate::errors::DistriAIError;
+
+/// Registers an AI model under the owner.
+///
+/// Names can be longer than a seed, so the model PDA is keyed by the sha256 of the name.
+pub fn create_ai_model(
+    ctx: Context<CreateAiModel>,
+    name: String,
+    framework: u8,
+    license: LicenseType,
+    type1: u8,
+    type2: u8,
+    tags: String,
+) -> Result<()> {
+    require_gte!(
+        AiModel::NAME_MAX_LENGTH,
+        name.len(),
+        DistriAIError::StringTooLong
+    );
+    require_gte!(
+        AiModel::TAGS_MAX_LENGTH,
+        tags.len(),
+        DistriAIError::StringTooLong
+    );
+
+    let now_ts = Clock::get()?.unix_timestamp;
+    let ai_model = &mut ctx.accounts.ai_model;
+    ai_model.owner = ctx.accounts.owner.key();
+    ai_model.name = name;
+    ai_model.framework = framework;
+    ai_model.license = license;
+    ai_model.type1 = type1;
+    ai_model.type2 = type2;
+    ai_model.tags = tags;
+    ai_model.create_time = now_ts;
+    ai_model.update_time = now_ts;
+
+    emit!(AiModelEvent {
+        owner: ai_model.owner,
+        name: ai_model.name.clone(),
+        framework,
+        license,
+        type1,
+        type2,
+        tags: ai_model.tags.clone(),
+        update_time: now_ts,
+    });
+    Ok(())
+}
+
+/// Updates the classification and tags of a registered model. The name cannot change.
+pub fn update_ai_model(
+    ctx: Context<UpdateAiModel>,
+    _name: String,
+    framework: u8,
+    license: LicenseType,
+    type1: u8,
+    type2: u8,
+    tags: String,
+) -> Result<()> {
+    require_gte!(
+        AiModel::TAGS_MAX_LENGTH,
+        tags.len(),
+        DistriAIError::StringTooLong
+    );
+
+    let now_ts = Clock::get()?.unix_timestamp;
+    let ai_model = &mut ctx.accounts.ai_model;
+    ai_model.framework = framework;
+    ai_model.license = license;
+    ai_model.type1 = type1;
+    ai_model.type2 = type2;
+    ai_model.tags = tags;
+    ai_model.update_time = now_ts;
+
+    emit!(AiModelEvent {
+        owner: ai_model.owner,
+        name: ai_model.name.clone(),
+        framework,
+        license,
+        type1,
+        type2,
+        tags: ai_model.tags.clone(),
+        update_time: now_ts,
+    });
+    Ok(())
+}
+
+/// Removes a registered model and returns its rent to the owner.
+///
+/// Version PDAs are seeded with the model's `create_time`, so a model later created under the
+/// same name does not inherit the versions of the removed one.
+pub fn remove_ai_model(ctx: Context<RemoveAiModel>, _name: String) -> Result<()> {
+    let ai_model = &ctx.accounts.ai_model;
+
+    emit!(AiModelRemovedEvent {
+        owner: ai_model.owner,
+        name: ai_model.name.clone(),
+    });
+    Ok(())
+}
+
+/// Publishes an immutable version of a model's weights.
+///
+/// Each version lives at its own PDA so consumers can pin an exact release, and carries the
+/// sha256 of the artifact so downloads can be verified against the chain.
+pub fn publish_model_version(
+    ctx: Context<PublishModelVersion>,
+    major: u16,
+    minor: u16,
+    patch: u16,
+    content_hash: [u8; 32],
+    uri: String,
+    size: u64,
+    changelog: String,
+) -> Result<()> {
+    require!(
+        major != 0 || minor != 0 || patch != 0,
+        DistriAIError::InvalidVersion
+    );
+    require!(content_hash != [0; 32], DistriAIError::InvalidVersion);
+    require_gte!(
+        ModelVersion::URI_MAX_LENGTH,
+        uri.len(),
+        DistriAIError::StringTooLong
+    );
+    require_gte!(
+        ModelVersion::CHANGELOG_MAX_LENGTH,
+        changelog.len(),
+        DistriAIError::StringTooLong
+    );
+
+    let model_version = &mut ctx.accounts.model_version;
+    model_version.ai_model = ctx.accounts.ai_model.key();
+    model_version.major = major;
+    model_version.minor = minor;
+    model_version.patch = patch;
+    model_version.content_hash = content_hash;
+    model_version.uri = uri;
+    model_version.size = size;
+    model_version.changelog = changelog;
+    model_version.create_time = Clock::get()?.unix_timestamp;
+
+    emit!(ModelVersionEvent {
+        ai_model: model_version.ai_model,
+        major,
+        minor,
+        patch,
+        content_hash,
+        uri: model_version.uri.clone(),
+        size,
+    });
+    Ok(())
+}
+
+#[derive(Accounts)]
+#[instruction(name: String)]
+pub struct CreateAiModel<'info> {
+    #[account(
+        init,
+        seeds = [b"ai-model", owner.key().as_ref(), hash(name.as_bytes()).as_ref()],
+        bump,
+        payer = owner,
+        space = 8 + AiModel::INIT_SPACE
+    )]
+    pub ai_model: Account<'info, AiModel>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(name: String)]
+pub struct UpdateAiModel<'info> {
+    #[account(
+        mut,
+        has_one = owner,
+        seeds = [b"ai-model", owner.key().as_ref(), hash(name.as_bytes()).as_ref()],
+        bump
+    )]
+    pub ai_model: Account<'info, AiModel>,
+
+    pub owner: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(name: String)]
+pub struct RemoveAiModel<'info> {
+    #[account(
+        mut,
+        has_one = owner,
+        seeds = [b"ai-model", owner.key().as_ref(), hash(name.as_bytes()).as_ref()],
+        bump,
+        close = owner
+    )]
+    pub ai_model: Account<'info, AiModel>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(major: u16, minor: u16, patch: u16)]
+pub struct PublishModelVersion<'info> {
+    #[account(
+        has_one = owner
+    )]
+    pub ai_model: Account<'info, AiModel>,
+
+    #[account(
+        init,
+        seeds = [
+            b"model-version",
+            ai_model.key().as_ref(),
+            ai_model.create_time.to_le_bytes().as_ref(),
+            major.to_le_bytes().as_ref(),
+            minor.to_le_bytes().as_ref(),
+            patch.to_le_bytes().as_ref(),
+        ],
+        bump,
+        payer = owner,
+        space = 8 + ModelVersion::INIT_SPACE
+    )]
+    pub model_version: Account<'info, ModelVersion>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[event]
+#[derive(Debug, Clone)]
+pub struct AiModelEvent {
+    pub owner: Pubkey,
+    pub name: String,
+    pub framework: u8,
+    pub license: LicenseType,
+    pub type1: u8,
+    pub type2: u8,
+    pub tags: String,
+    pub update_time: i64,
+}
+
+#[event]
+#[derive(Debug, Clone)]
+pub struct AiModelRemovedEvent {
+    pub owner: Pubkey,
+    pub name: String,
+}
+
+#[event]
+#[derive(Debug, Clone)]
+pub struct ModelVersionEvent {
+    pub ai_model: Pubkey,
+    pub major: u16,
+    pub minor: u16,
+    pub patch: u16,
+    pub content_hash: [u8; 32],
+    pub uri: String,
+    pub size: u64,
+}
@@ -0,0 +1,50 @@
+use anchor_lang::prelude::Pubkey;
+
+/// Work units earned by each verified task.
+pub const TASK_UNITS: u64 = 10;
+/// Work units earned by each hour of completed orders.
+pub const ORDER_HOUR_UNITS: u64 = 4;
+/// Maximum work units a single machine can earn in a period.
+pub const MAX_WORK_UNITS: u64 = 10_000;
+/// Maximum share of a period pool, in basis points, a single machine can claim.
+pub const MAX_SHARE_BPS: u64 = 1_000;
+/// Scores are percentages, higher values are clamped.
+pub const MAX_SCORE: u8 = 100;
+
+/// Reward weight of a machine in a period.
+///
+/// Work units from tasks and completed order hours are capped at `MAX_WORK_UNITS`, then scaled by
+/// the machine score so that a machine with score 0 keeps a third of the weight of a perfect one.
+pub fn work_weight(task_num: u32, order_hours: u32, score: u8) -> u64 {
+    let units = u64::from(task_num)
+        .saturating_mul(TASK_UNITS)
+        .saturating_add(u64::from(order_hours).saturating_mul(ORDER_HOUR_UNITS))
+        .min(MAX_WORK_UNITS);
+    units * (50 + u64::from(score.min(MAX_SCORE)))
+}
+
+/// Share of `pool` for a machine with `weight` out of the finalized `total_weight` of a period.
+///
+/// Shares are rounded down and capped at `MAX_SHARE_BPS` of the pool, so the sum over all machines
+/// never exceeds the pool.
+pub fn machine_share(pool: u64, weight: u64, total_weight: u64) -> u64 {
+    if total_weight == 0 || weight == 0 {
+        return 0;
+    }
+    let weight = weight.min(total_weight);
+    let share = u128::from(pool) * u128::from(weight) / u128::from(total_weight);
+    let cap = u128::from(pool) * u128::from(MAX_SHARE_BPS) / 10_000;
+    share.min(cap) as u64
+}
+
+/// Order hours credited to a machine for an order that settled `seller_amount` to its provider at
+/// `price` per hour.
+///
+/// Only hours actually paid to the provider count, and orders placed by the machine owner earn
+/// nothing, so renting a machine to oneself cannot farm the periodic pool.
+pub fn settled_order_hours(buyer: &Pubkey, owner: &Pubkey, seller_amount: u64, price: u64) -> u32 {
+    if buyer == owner || price == 0 {
+        return 0;
+    }
+    u32::try_from(seller_amount / price).unwrap_or(u32::MAX)
+}
@@ -0,0 +1,148 @@
+use anchor_lang::prelude::*;
+use crate::ai::ai_model::*;
+use crate::errors::DistriAIError;
+use crate::state::job::*;
+use crate::state::order::*;
+use crate::state::task::*;
+
+/// Binds an order that has not started yet to the model version the buyer wants run.
+pub fn bind_order_model(ctx: Context<BindOrderModel>) -> Result<()> {
+    let order = &ctx.accounts.order;
+    require!(
+        order.status == OrderStatus::Preparing,
+        DistriAIError::IncorrectStatus
+    );
+    let ai_model = &ctx.accounts.ai_model;
+    let model_version = &ctx.accounts.model_version;
+    JobBinding::check_version(&ai_model.key(), ai_model.create_time, model_version)?;
+
+    let job_binding = &mut ctx.accounts.job_binding;
+    job_binding.ai_model = model_version.ai_model;
+    job_binding.model_version = model_version.key();
+    job_binding.job = order.key();
+    job_binding.kind = JobKind::Order;
+    job_binding.requester = order.buyer;
+    job_binding.machine_id = order.machine_id;
+    job_binding.bind_time = Clock::get()?.unix_timestamp;
+
+    emit!(JobBindingEvent {
+        ai_model: job_binding.ai_model,
+        model_version: job_binding.model_version,
+        job: job_binding.job,
+        kind: job_binding.kind.clone(),
+        output_version: job_binding.output_version,
+    });
+    Ok(())
+}
+
+/// Binds a submitted task to the model version it ran, and optionally the version it produced.
+pub fn bind_task_model(ctx: Context<BindTaskModel>) -> Result<()> {
+    let task = &ctx.accounts.task;
+    let ai_model = &ctx.accounts.ai_model;
+    let model_version = &ctx.accounts.model_version;
+    JobBinding::check_version(&ai_model.key(), ai_model.create_time, model_version)?;
+
+    let now_ts = Clock::get()?.unix_timestamp;
+    let job_binding = &mut ctx.accounts.job_binding;
+    job_binding.ai_model = model_version.ai_model;
+    job_binding.model_version = model_version.key();
+    job_binding.job = task.key();
+    job_binding.kind = JobKind::Task;
+    job_binding.requester = task.owner;
+    job_binding.machine_id = task.machine_id;
+    job_binding.bind_time = now_ts;
+    if let Some(output_version) = &ctx.accounts.output_version {
+        JobBinding::check_version(&ai_model.key(), ai_model.create_time, output_version)?;
+        job_binding.output_version = output_version.key();
+        job_binding.complete_time = now_ts;
+    }
+
+    emit!(JobBindingEvent {
+        ai_model: job_binding.ai_model,
+        model_version: job_binding.model_version,
+        job: job_binding.job,
+        kind: job_binding.kind.clone(),
+        output_version: job_binding.output_version,
+    });
+    Ok(())
+}
+
+/// Records the completion, and the produced model version if any, of a bound order.
+pub fn complete_job_binding(
+    job_binding: &mut JobBinding,
+    output_version: Option<(Pubkey, &ModelVersion)>,
+    now_ts: i64,
+) -> Result<JobBindingEvent> {
+    job_binding.complete(output_version, now_ts)?;
+    Ok(JobBindingEvent {
+        ai_model: job_binding.ai_model,
+        model_version: job_binding.model_version,
+        job: job_binding.job,
+        kind: job_binding.kind.clone(),
+        output_version: job_binding.output_version,
+    })
+}
+
+#[derive(Accounts)]
+pub struct BindOrderModel<'info> {
+    #[account(
+        has_one = buyer
+    )]
+    pub order: Box<Account<'info, Order>>,
+
+    pub ai_model: Box<Account<'info, AiModel>>,
+
+    pub model_version: Box<Account<'info, ModelVersion>>,
+
+    #[account(
+        init,
+        seeds = [b"job-binding", order.key().as_ref()],
+        bump,
+        payer = buyer,
+        space = 8 + JobBinding::INIT_SPACE
+    )]
+    pub job_binding: Box<Account<'info, JobBinding>>,
+
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct BindTaskModel<'info> {
+    #[account(
+        has_one = owner
+    )]
+    pub task: Box<Account<'info, Task>>,
+
+    pub ai_model: Box<Account<'info, AiModel>>,
+
+    pub model_version: Box<Account<'info, ModelVersion>>,
+
+    pub output_version: Option<Box<Account<'info, ModelVersion>>>,
+
+    #[account(
+        init,
+        seeds = [b"job-binding", task.key().as_ref()],
+        bump,
+        payer = owner,
+        space = 8 + JobBinding::INIT_SPACE
+    )]
+    pub job_binding: Box<Account<'info, JobBinding>>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[event]
+#[derive(Debug, Clone)]
+pub struct JobBindingEvent {
+    pub ai_model: Pubkey,
+    pub model_version: Pubkey,
+    pub job: Pubkey,
+    pub kind: JobKind,
+    pub output_version: Pubkey,
+}
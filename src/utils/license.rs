@@ -0,0 +1,294 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token::{transfer_checked, Mint, Token, TokenAccount, TransferChecked},
+};
+use crate::ai::ai_model::*;
+use crate::errors::DistriAIError;
+use crate::state::protocol_config::ProtocolConfig;
+
+/// Sets the price of a paid model in one of the protocol's payment mints.
+pub fn set_model_price(
+    ctx: Context<SetModelPrice>,
+    price: u64,
+    subscription_period: i64,
+) -> Result<()> {
+    let ai_model = &ctx.accounts.ai_model;
+    require!(ai_model.license.is_paid(), DistriAIError::LicenseNotPaid);
+    require!(
+        ctx.accounts.config.accepts_payment_mint(&ctx.accounts.mint.key()),
+        DistriAIError::MintNotAccepted
+    );
+    require_gt!(price, 0, DistriAIError::InvalidQuantity);
+    if ai_model.license == LicenseType::PaidSubscription {
+        require_gt!(subscription_period, 0, DistriAIError::InvalidConfig);
+    }
+
+    let model_price = &mut ctx.accounts.model_price;
+    model_price.ai_model = ai_model.key();
+    model_price.mint = ctx.accounts.mint.key();
+    model_price.price = price;
+    model_price.subscription_period = subscription_period;
+
+    emit!(ModelPriceEvent {
+        ai_model: model_price.ai_model,
+        mint: model_price.mint,
+        price,
+        subscription_period,
+    });
+    Ok(())
+}
+
+/// Stops accepting a mint as payment for a model.
+pub fn remove_model_price(ctx: Context<RemoveModelPrice>) -> Result<()> {
+    let model_price = &ctx.accounts.model_price;
+
+    emit!(ModelPriceEvent {
+        ai_model: model_price.ai_model,
+        mint: model_price.mint,
+        price: 0,
+        subscription_period: 0,
+    });
+    Ok(())
+}
+
+/// Buys `quantity` calls or subscription periods of a paid model.
+///
+/// The payment goes to the model owner and the buyer's license receipt is created or extended.
+/// Subscriptions bought before the previous one expires are appended to it.
+pub fn purchase_model_license(ctx: Context<PurchaseModelLicense>, quantity: u64) -> Result<()> {
+    ctx.accounts.config.require_active(ProtocolConfig::PAUSE_PURCHASE_LICENSE)?;
+    let ai_model = &ctx.accounts.ai_model;
+    require!(ai_model.license.is_paid(), DistriAIError::LicenseNotPaid);
+    require_gt!(quantity, 0, DistriAIError::InvalidQuantity);
+
+    let model_price = &ctx.accounts.model_price;
+    let amount = model_price
+        .price
+        .checked_mul(quantity)
+        .ok_or(DistriAIError::MathOverflow)?;
+
+    let now_ts = Clock::get()?.unix_timestamp;
+    let receipt = &mut ctx.accounts.license_receipt;
+    if receipt.license != ai_model.license {
+        receipt.calls_remaining = 0;
+        receipt.expire_time = 0;
+    }
+    receipt.ai_model = ai_model.key();
+    receipt.buyer = ctx.accounts.buyer.key();
+    receipt.license = ai_model.license;
+    receipt.mint = model_price.mint;
+    receipt.total_paid = receipt.total_paid.saturating_add(amount);
+    receipt.purchase_time = now_ts;
+    match ai_model.license {
+        LicenseType::PaidPerCall => {
+            receipt.calls_remaining = receipt.calls_remaining.saturating_add(quantity);
+        }
+        _ => {
+            let extension = i64::try_from(quantity)
+                .ok()
+                .and_then(|quantity| quantity.checked_mul(model_price.subscription_period))
+                .ok_or(DistriAIError::MathOverflow)?;
+            receipt.expire_time = receipt.expire_time.max(now_ts).saturating_add(extension);
+        }
+    }
+
+    // Transfer token from buyer to model owner
+    let cpi_context = CpiContext::new(
+        ctx.accounts.token_program.to_account_info(),
+        TransferChecked {
+            from: ctx.accounts.buyer_ata.to_account_info(),
+            mint: ctx.accounts.mint.to_account_info(),
+            to: ctx.accounts.owner_ata.to_account_info(),
+            authority: ctx.accounts.buyer.to_account_info(),
+        },
+    );
+    transfer_checked(cpi_context, amount, ctx.accounts.mint.decimals)?;
+
+    emit!(LicenseEvent {
+        ai_model: receipt.ai_model,
+        buyer: receipt.buyer,
+        license: receipt.license,
+        mint: receipt.mint,
+        amount,
+        calls_remaining: receipt.calls_remaining,
+        expire_time: receipt.expire_time,
+    });
+    Ok(())
+}
+
+/// Records calls served under a per-call license.
+///
+/// Signed by the model owner or by the protocol's metering authority, which the API server uses to
+/// charge every invocation it serves.
+pub fn consume_license_calls(ctx: Context<ConsumeLicenseCalls>, calls: u64) -> Result<()> {
+    let receipt = &mut ctx.accounts.license_receipt;
+    require!(
+        receipt.license == LicenseType::PaidPerCall,
+        DistriAIError::IncorrectStatus
+    );
+    require_gte!(
+        receipt.calls_remaining,
+        calls,
+        DistriAIError::InvalidQuantity
+    );
+    receipt.calls_remaining -= calls;
+
+    emit!(LicenseEvent {
+        ai_model: receipt.ai_model,
+        buyer: receipt.buyer,
+        license: receipt.license,
+        mint: receipt.mint,
+        amount: 0,
+        calls_remaining: receipt.calls_remaining,
+        expire_time: receipt.expire_time,
+    });
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct SetModelPrice<'info> {
+    #[account(
+        seeds = [b"protocol-config"],
+        bump
+    )]
+    pub config: Box<Account<'info, ProtocolConfig>>,
+
+    #[account(
+        has_one = owner
+    )]
+    pub ai_model: Box<Account<'info, AiModel>>,
+
+    #[account(
+        init_if_needed,
+        seeds = [b"model-price", ai_model.key().as_ref(), mint.key().as_ref()],
+        bump,
+        payer = owner,
+        space = 8 + ModelPrice::INIT_SPACE
+    )]
+    pub model_price: Box<Account<'info, ModelPrice>>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    pub mint: Account<'info, Mint>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RemoveModelPrice<'info> {
+    #[account(
+        has_one = owner
+    )]
+    pub ai_model: Box<Account<'info, AiModel>>,
+
+    #[account(
+        mut,
+        seeds = [b"model-price", ai_model.key().as_ref(), model_price.mint.as_ref()],
+        bump,
+        close = owner
+    )]
+    pub model_price: Box<Account<'info, ModelPrice>>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct PurchaseModelLicense<'info> {
+    #[account(
+        seeds = [b"protocol-config"],
+        bump
+    )]
+    pub config: Box<Account<'info, ProtocolConfig>>,
+
+    #[account(
+        has_one = owner
+    )]
+    pub ai_model: Box<Account<'info, AiModel>>,
+
+    #[account(
+        seeds = [b"model-price", ai_model.key().as_ref(), mint.key().as_ref()],
+        bump
+    )]
+    pub model_price: Box<Account<'info, ModelPrice>>,
+
+    #[account(
+        init_if_needed,
+        seeds = [b"license-receipt", ai_model.key().as_ref(), buyer.key().as_ref()],
+        bump,
+        payer = buyer,
+        space = 8 + LicenseReceipt::INIT_SPACE
+    )]
+    pub license_receipt: Box<Account<'info, LicenseReceipt>>,
+
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = buyer
+    )]
+    pub buyer_ata: Account<'info, TokenAccount>,
+
+    /// CHECK: Model owner, validated against the model.
+    pub owner: UncheckedAccount<'info>,
+
+    #[account(
+        init_if_needed,
+        payer = buyer,
+        associated_token::mint = mint,
+        associated_token::authority = owner
+    )]
+    pub owner_ata: Account<'info, TokenAccount>,
+
+    pub mint: Account<'info, Mint>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ConsumeLicenseCalls<'info> {
+    #[account(
+        seeds = [b"protocol-config"],
+        bump
+    )]
+    pub config: Box<Account<'info, ProtocolConfig>>,
+
+    #[account(
+        constraint = ai_model.owner == meter.key()
+            || config.metering_authority == meter.key() @ DistriAIError::Unauthorized
+    )]
+    pub ai_model: Box<Account<'info, AiModel>>,
+
+    #[account(
+        mut,
+        seeds = [b"license-receipt", ai_model.key().as_ref(), license_receipt.buyer.as_ref()],
+        bump
+    )]
+    pub license_receipt: Box<Account<'info, LicenseReceipt>>,
+
+    /// Model owner or metering authority.
+    pub meter: Signer<'info>,
+}
+
+#[event]
+pub struct ModelPriceEvent {
+    pub ai_model: Pubkey,
+    pub mint: Pubkey,
+    pub price: u64,
+    pub subscription_period: i64,
+}
+
+#[event]
+pub struct LicenseEvent {
+    pub ai_model: Pubkey,
+    pub buyer: Pubkey,
+    pub license: LicenseType,
+    pub mint: Pubkey,
+    pub amount: u64,
+    pub calls_remaining: u64,
+    pub expire_time: i64,
+}
@@ -0,0 +1,413 @@
+use anchor_lang::prelude::*;
+use crate::errors::DistriAIError;
+use crate::state::heartbeat::*;
+use crate::state::machine::*;
+use crate::state::machine_spec::*;
+use crate::state::payment::*;
+use crate::state::protocol_config::*;
+use crate::state::queue::*;
+use crate::state::stake::*;
+
+/// Registers a machine under the owner. New machines are `Idle` until offered for rent.
+pub fn add_machine(ctx: Context<AddMachine>, uuid: [u8; 16], metadata: String) -> Result<()> {
+    require_gte!(
+        Machine::METADATA_MAX_LENGTH,
+        metadata.len(),
+        DistriAIError::StringTooLong
+    );
+
+    let machine = &mut ctx.accounts.machine;
+    machine.owner = ctx.accounts.owner.key();
+    machine.uuid = uuid;
+    machine.metadata = metadata;
+    machine.status = MachineStatus::Idle;
+
+    emit!(machine_event(machine));
+    Ok(())
+}
+
+/// Updates the metadata of a machine that is not being rented.
+pub fn update_machine(ctx: Context<UpdateMachine>, metadata: String) -> Result<()> {
+    require_gte!(
+        Machine::METADATA_MAX_LENGTH,
+        metadata.len(),
+        DistriAIError::StringTooLong
+    );
+
+    let machine = &mut ctx.accounts.machine;
+    require!(
+        machine.status != MachineStatus::Renting,
+        DistriAIError::IncorrectStatus
+    );
+    machine.metadata = metadata;
+
+    emit!(machine_event(machine));
+    Ok(())
+}
+
+/// Offers a machine for rent, or changes the terms of a machine already offered.
+///
+/// The machine must hold the minimum stake, as checked again by `place_order`.
+pub fn make_offer(ctx: Context<MakeOffer>, price: u64, max_duration: u32, disk: u32) -> Result<()> {
+    require_gt!(price, 0, DistriAIError::InvalidQuantity);
+    require_gt!(max_duration, 0, DistriAIError::InvalidQuantity);
+    require_gte!(
+        ctx.accounts.stake.amount,
+        MachineStake::MIN_FOR_RENT,
+        DistriAIError::InsufficientStake
+    );
+
+    let machine = &mut ctx.accounts.machine;
+    require!(
+        machine.status == MachineStatus::Idle || machine.status == MachineStatus::ForRent,
+        DistriAIError::IncorrectStatus
+    );
+    machine.status = MachineStatus::ForRent;
+    machine.price = price;
+    machine.max_duration = max_duration;
+    machine.disk = disk;
+
+    emit!(machine_event(machine));
+    Ok(())
+}
+
+/// Takes a machine off the market. Orders already placed are not affected.
+pub fn cancel_offer(ctx: Context<CancelOffer>) -> Result<()> {
+    let machine = &mut ctx.accounts.machine;
+    require!(
+        machine.status == MachineStatus::ForRent,
+        DistriAIError::IncorrectStatus
+    );
+    machine.status = MachineStatus::Idle;
+
+    emit!(machine_event(machine));
+    Ok(())
+}
+
+/// Removes an idle machine and returns its rent to the owner.
+///
+/// Stake can only be withdrawn through the machine account, so it must be withdrawn first. The
+/// same holds for queued orders, which must be cancelled or skipped before the machine goes.
+/// The spec and heartbeat of the machine are closed with it, as are the `MachinePrice` accounts
+/// passed in the remaining accounts, so a machine registered again under the same uuid starts
+/// without them.
+pub fn remove_machine<'info>(ctx: Context<'_, '_, 'info, 'info, RemoveMachine<'info>>) -> Result<()> {
+    let machine = &ctx.accounts.machine;
+    require!(
+        machine.status == MachineStatus::Idle,
+        DistriAIError::IncorrectStatus
+    );
+    require_eq!(
+        MachineStake::amount_of(&ctx.accounts.stake)?,
+        0,
+        DistriAIError::StakeLocked
+    );
+    // Queued orders can only be cancelled or skipped while the machine exists
+    require_eq!(
+        MachineQueue::len_of(&ctx.accounts.queue)?,
+        0,
+        DistriAIError::QueueNotEmpty
+    );
+
+    let owner = ctx.accounts.owner.to_account_info();
+    let machine_spec = &ctx.accounts.machine_spec;
+    if !machine_spec.data_is_empty() {
+        Account::<MachineSpec>::try_from(machine_spec)?.close(owner.clone())?;
+    }
+    let heartbeat = &ctx.accounts.heartbeat;
+    if !heartbeat.data_is_empty() {
+        Account::<MachineHeartbeat>::try_from(heartbeat)?.close(owner.clone())?;
+    }
+    for price_info in ctx.remaining_accounts {
+        require!(price_info.is_writable, DistriAIError::InvalidBatch);
+        let machine_price = Account::<MachinePrice>::try_from(price_info)?;
+        let (price_pda, _) = Pubkey::find_program_address(
+            &[b"machine-price", machine.key().as_ref(), machine_price.mint.as_ref()],
+            &crate::ID,
+        );
+        require_keys_eq!(price_info.key(), price_pda, DistriAIError::InvalidBatch);
+        machine_price.close(owner.clone())?;
+    }
+
+    emit!(MachineRemovedEvent {
+        owner: machine.owner,
+        uuid: machine.uuid,
+    });
+    Ok(())
+}
+
+/// Publishes the typed hardware spec of a machine, replacing the previous one.
+pub fn set_machine_spec(ctx: Context<SetMachineSpec>, spec: HardwareSpec) -> Result<()> {
+    require_gte!(
+        HardwareSpec::GPU_MODEL_MAX_LENGTH,
+        spec.gpu_model.len(),
+        DistriAIError::StringTooLong
+    );
+    require_gte!(
+        HardwareSpec::REGION_MAX_LENGTH,
+        spec.region.len(),
+        DistriAIError::StringTooLong
+    );
+
+    let machine_spec = &mut ctx.accounts.machine_spec;
+    machine_spec.machine = ctx.accounts.machine.key();
+    machine_spec.spec = spec;
+    machine_spec.update_time = Clock::get()?.unix_timestamp;
+
+    emit!(MachineSpecEvent {
+        machine: machine_spec.machine,
+        spec: machine_spec.spec.clone(),
+        update_time: machine_spec.update_time,
+    });
+    Ok(())
+}
+
+/// Removes the hardware spec of a machine and returns its rent to the owner.
+pub fn remove_machine_spec(ctx: Context<RemoveMachineSpec>) -> Result<()> {
+    emit!(MachineSpecRemovedEvent {
+        machine: ctx.accounts.machine_spec.machine,
+    });
+    Ok(())
+}
+
+/// Records that the machine is alive. Providers are expected to send one every
+/// `heartbeat_interval` seconds of the protocol config; `place_order` and
+/// `activate_queued_order` reject machines that stop.
+pub fn heartbeat(ctx: Context<Heartbeat>) -> Result<()> {
+    let now_ts = Clock::get()?.unix_timestamp;
+    let machine = ctx.accounts.machine.key();
+    let params = &ctx.accounts.config.params;
+    let heartbeat = &mut ctx.accounts.heartbeat;
+    if heartbeat.record(machine, now_ts, params) {
+        emit!(HeartbeatEvent {
+            machine,
+            last_seen: now_ts,
+            beats: heartbeat.beats,
+            uptime_bps: heartbeat.uptime_bps(now_ts, params),
+        });
+    }
+    Ok(())
+}
+
+fn machine_event(machine: &Machine) -> MachineEvent {
+    MachineEvent {
+        owner: machine.owner,
+        uuid: machine.uuid,
+        metadata: machine.metadata.clone(),
+        status: machine.status.clone(),
+        price: machine.price,
+        max_duration: machine.max_duration,
+        disk: machine.disk,
+    }
+}
+
+#[derive(Accounts)]
+#[instruction(uuid: [u8; 16])]
+pub struct AddMachine<'info> {
+    #[account(
+        init,
+        seeds = [b"machine", owner.key().as_ref(), uuid.as_ref()],
+        bump,
+        payer = owner,
+        space = 8 + Machine::INIT_SPACE
+    )]
+    pub machine: Account<'info, Machine>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateMachine<'info> {
+    #[account(
+        mut,
+        has_one = owner
+    )]
+    pub machine: Account<'info, Machine>,
+
+    pub owner: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct MakeOffer<'info> {
+    #[account(
+        mut,
+        has_one = owner
+    )]
+    pub machine: Account<'info, Machine>,
+
+    #[account(
+        seeds = [b"stake", owner.key().as_ref(), machine.uuid.as_ref()],
+        bump
+    )]
+    pub stake: Account<'info, MachineStake>,
+
+    pub owner: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CancelOffer<'info> {
+    #[account(
+        mut,
+        has_one = owner
+    )]
+    pub machine: Account<'info, Machine>,
+
+    pub owner: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct RemoveMachine<'info> {
+    #[account(
+        mut,
+        has_one = owner,
+        close = owner
+    )]
+    pub machine: Account<'info, Machine>,
+
+    /// CHECK: Stake of the machine, which may not exist if the machine was never staked.
+    #[account(
+        seeds = [b"stake", owner.key().as_ref(), machine.uuid.as_ref()],
+        bump
+    )]
+    pub stake: UncheckedAccount<'info>,
+
+    /// CHECK: Hardware spec of the machine, closed with it if it was published.
+    #[account(
+        mut,
+        seeds = [b"machine-spec", machine.key().as_ref()],
+        bump
+    )]
+    pub machine_spec: UncheckedAccount<'info>,
+
+    /// CHECK: Heartbeat of the machine, closed with it if the machine ever sent one.
+    #[account(
+        mut,
+        seeds = [b"heartbeat", machine.key().as_ref()],
+        bump
+    )]
+    pub heartbeat: UncheckedAccount<'info>,
+
+    /// CHECK: Order queue of the machine, which may not exist if nothing was ever queued.
+    #[account(
+        seeds = [b"queue", machine.key().as_ref()],
+        bump
+    )]
+    pub queue: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetMachineSpec<'info> {
+    #[account(
+        has_one = owner
+    )]
+    pub machine: Account<'info, Machine>,
+
+    #[account(
+        init_if_needed,
+        seeds = [b"machine-spec", machine.key().as_ref()],
+        bump,
+        payer = owner,
+        space = 8 + MachineSpec::INIT_SPACE
+    )]
+    pub machine_spec: Account<'info, MachineSpec>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RemoveMachineSpec<'info> {
+    #[account(
+        has_one = owner
+    )]
+    pub machine: Account<'info, Machine>,
+
+    #[account(
+        mut,
+        seeds = [b"machine-spec", machine.key().as_ref()],
+        bump,
+        close = owner
+    )]
+    pub machine_spec: Account<'info, MachineSpec>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct Heartbeat<'info> {
+    #[account(
+        seeds = [b"protocol-config"],
+        bump
+    )]
+    pub config: Box<Account<'info, ProtocolConfig>>,
+
+    #[account(
+        has_one = owner
+    )]
+    pub machine: Account<'info, Machine>,
+
+    #[account(
+        init_if_needed,
+        seeds = [b"heartbeat", machine.key().as_ref()],
+        bump,
+        payer = owner,
+        space = 8 + MachineHeartbeat::INIT_SPACE
+    )]
+    pub heartbeat: Account<'info, MachineHeartbeat>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[event]
+#[derive(Debug, Clone)]
+pub struct MachineEvent {
+    pub owner: Pubkey,
+    pub uuid: [u8; 16],
+    pub metadata: String,
+    pub status: MachineStatus,
+    pub price: u64,
+    pub max_duration: u32,
+    pub disk: u32,
+}
+
+#[event]
+#[derive(Debug, Clone)]
+pub struct MachineRemovedEvent {
+    pub owner: Pubkey,
+    pub uuid: [u8; 16],
+}
+
+#[event]
+#[derive(Debug, Clone)]
+pub struct MachineSpecEvent {
+    pub machine: Pubkey,
+    pub spec: HardwareSpec,
+    pub update_time: i64,
+}
+
+#[event]
+#[derive(Debug, Clone)]
+pub struct MachineSpecRemovedEvent {
+    pub machine: Pubkey,
+}
+
+#[event]
+#[derive(Debug, Clone)]
+pub struct HeartbeatEvent {
+    pub machine: Pubkey,
+    pub last_seen: i64,
+    pub beats: u64,
+    pub uptime_bps: u16,
+}
@@ -0,0 +1,75 @@
+/// Builds the next version of an account from the previous one.
+///
+/// Every field of the source struct must appear in exactly one of `copy`, `map` or `drop`, and
+/// every field of the target struct must appear in exactly one of `copy`, `map` or `init`. The
+/// source is destructured without `..` and the target is built as a struct literal, so adding a
+/// field to either struct without deciding what happens to it is a compile error rather than a
+/// silently lost value.
+///
+/// ```ignore
+/// let machine_new = migrate_account!(&machine_before, Machine => MachineNew {
+///     copy { owner, uuid, metadata },
+///     map { status: status.clone() },
+///     drop { legacy },
+///     init { data: 0 },
+/// });
+/// ```
+#[macro_export]
+macro_rules! migrate_account {
+    (
+        $source:expr, $from:ident => $to:ident {
+            copy { $($copy:ident),* $(,)? }
+            $(, map { $($map:ident : $map_value:expr),* $(,)? })?
+            $(, drop { $($drop:ident),* $(,)? })?
+            $(, init { $($init:ident : $init_value:expr),* $(,)? })?
+            $(,)?
+        }
+    ) => {{
+        let source: &$from = $source;
+        #[allow(unused_variables)]
+        let $from {
+            $($copy,)*
+            $($($map,)*)?
+            $($($drop: _,)*)?
+        } = ::core::clone::Clone::clone(source);
+        $to {
+            $($($map: $map_value,)*)?
+            $($copy,)*
+            $($($init: $init_value,)*)?
+        }
+    }};
+}
+
+/// Declares how a status enum is rewritten by a migration.
+///
+/// The table expands to a `match` without a wildcard arm, so leaving out a variant fails to
+/// compile. Listing one twice is denied as an unreachable pattern, so it fails to compile too.
+///
+/// ```ignore
+/// status_table! {
+///     pub fn order_new_status(OrderStatus) {
+///         Preparing => Preparing,
+///         Training => Training,
+///         Completed => Completed,
+///         Failed => Failed,
+///         Refunded => Refunded,
+///     }
+/// }
+/// ```
+#[macro_export]
+macro_rules! status_table {
+    (
+        $(#[$meta:meta])*
+        $vis:vis fn $name:ident($status:ident) {
+            $($from:ident => $to:ident),* $(,)?
+        }
+    ) => {
+        $(#[$meta])*
+        #[deny(unreachable_patterns)]
+        $vis fn $name(status: &$status) -> $status {
+            match status {
+                $($status::$from => $status::$to,)*
+            }
+        }
+    };
+}
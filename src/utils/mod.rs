@@ -1,2 +1,18 @@
+pub mod ai_model;
 pub mod config;
+pub mod distribution;
+pub mod job;
+pub mod license;
 pub mod logger;
+pub mod machine;
+pub mod migrate;
+pub mod order;
+pub mod payment;
+pub mod pricing;
+pub mod protocol_config;
+pub mod queue;
+pub mod reward;
+pub mod stake;
+pub mod task;
+pub mod verifier;
+pub mod vesting;
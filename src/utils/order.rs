@@ -3,10 +3,24 @@ use anchor_spl::{
     associated_token::AssociatedToken,
     token::{transfer_checked, Mint, Token, TokenAccount, TransferChecked},
 };
-use crate::dist_token;
+use crate::ai::ai_model::*;
 use crate::errors::DistriAIError;
+use crate::state::heartbeat::*;
+use crate::state::job::*;
+use crate::state::machine_history::*;
 use crate::state::machine::*;
 use crate::state::order::*;
+use crate::state::order_rating::*;
+use crate::state::payment::*;
+use crate::state::protocol_config::*;
+use crate::state::queue::*;
+use crate::state::reward_period::*;
+use crate::state::stake::*;
+use crate::utils::distribution;
+use crate::utils::job::complete_job_binding;
+use crate::utils::pricing;
+use crate::utils::reward::refresh_work_weight;
+use crate::utils::stake::*;
 
 /// Places an order to rent a machine, handling payment and updating machine status.
 pub fn place_order(
@@ -15,6 +29,8 @@ pub fn place_order(
     duration: u32,
     metadata: String,
 ) -> Result<()> {
+    ctx.accounts.config.require_active(ProtocolConfig::PAUSE_PLACE_ORDER)?;
+
     require_gte!(
         Order::METADATA_MAX_LENGTH,
         metadata.len(),
@@ -26,14 +42,41 @@ pub fn place_order(
         machine.status == MachineStatus::ForRent,
         DistriAIError::IncorrectStatus
     );
+    require_gt!(duration, 0, DistriAIError::InvalidQuantity);
     require_gte!(
         machine.max_duration,
         duration,
         DistriAIError::DurationTooMuch
     );
+    require!(
+        ctx.accounts
+            .heartbeat
+            .is_online(Clock::get()?.unix_timestamp, &ctx.accounts.config.params),
+        DistriAIError::MachineOffline
+    );
+
+    // Reserved orders are served first, see `activate_queued_order`
+    let queue = &mut ctx.accounts.queue;
+    require!(queue.is_empty(), DistriAIError::QueueNotEmpty);
+    queue.owner = machine.owner;
+    queue.machine_id = machine.uuid;
+
+    let stake = &mut ctx.accounts.stake;
+    require_gte!(
+        stake.amount,
+        MachineStake::MIN_FOR_RENT,
+        DistriAIError::InsufficientStake
+    );
+    stake.active_orders = stake.active_orders.saturating_add(1);
 
     // Transfer token from buyer to vault
-    let total = machine.price.saturating_mul(duration.into());
+    let price = payment_price(
+        machine,
+        &ctx.accounts.machine_price,
+        &ctx.accounts.mint,
+        &ctx.accounts.config,
+    )?;
+    let total = pricing::order_total(price, duration)?;
     let cpi_context = CpiContext::new(
         ctx.accounts.token_program.to_account_info(),
         TransferChecked {
@@ -50,7 +93,7 @@ pub fn place_order(
     order.buyer = ctx.accounts.buyer.key();
     order.seller = machine.owner;
     order.machine_id = machine.uuid;
-    order.price = machine.price;
+    order.price = price;
     order.duration = duration;
     order.total = total;
     order.metadata = metadata;
@@ -61,24 +104,47 @@ pub fn place_order(
     machine.status = MachineStatus::Renting;
     machine.order_pda = order.key();
 
-    emit!(OrderEvent {
+    let order_payment = &mut ctx.accounts.order_payment;
+    order_payment.order = order.key();
+    order_payment.mint = ctx.accounts.mint.key();
+
+    emit!(OrderPlacedEvent {
         order_id: order.order_id,
         buyer: order.buyer,
         seller: order.seller,
         machine_id: order.machine_id,
+        status: order.status.clone(),
+        mint: order_payment.mint,
+        price: order.price,
+        duration: order.duration,
+        total: order.total,
+        order_time: order.order_time,
     });
     Ok(())
 }
 
 // RenewOrder renews an existing order by extending its duration and updating the total price.
+//
+// Renewals are charged at the price locked in when the order was placed, in the order's payment
+// mint, so `order.total == order.price * order.duration` holds for the whole life of the order and
+// later price changes by the seller only apply to new orders.
 pub fn renew_order(ctx: Context<RenewOrder>, duration: u32) -> Result<()> {
+    ctx.accounts.config.require_active(ProtocolConfig::PAUSE_RENEW_ORDER)?;
+
+    require_keys_eq!(
+        ctx.accounts.mint.key(),
+        OrderPayment::mint_of(&ctx.accounts.order_payment, ctx.accounts.config.mint)?,
+        DistriAIError::MintMismatch
+    );
+
     let order = &mut ctx.accounts.order;
     require!(
         order.status == OrderStatus::Training,
         DistriAIError::IncorrectStatus
     );
 
-    let new_duration = order.duration.saturating_add(duration);
+    let (new_duration, new_total, amount) =
+        pricing::renewal(order.duration, order.total, order.price, duration)?;
     let machine = &ctx.accounts.machine;
     require_gte!(
         machine.max_duration,
@@ -86,20 +152,10 @@ pub fn renew_order(ctx: Context<RenewOrder>, duration: u32) -> Result<()> {
         DistriAIError::DurationTooMuch
     );
 
-    let total = order.price.saturating_mul(duration.into());
-
     order.duration = new_duration;
-    order.total = order.total.saturating_add(total);
-
-    emit!(OrderEvent {
-        order_id: order.order_id,
-        buyer: order.buyer,
-        seller: order.seller,
-        machine_id: order.machine_id,
-    });
+    order.total = new_total;
 
     // Transfer token from buyer to vault
-    let total = machine.price.saturating_mul(duration.into());
     let cpi_context = CpiContext::new(
         ctx.accounts.token_program.to_account_info(),
         TransferChecked {
@@ -109,7 +165,21 @@ pub fn renew_order(ctx: Context<RenewOrder>, duration: u32) -> Result<()> {
             authority: ctx.accounts.buyer.to_account_info(),
         },
     );
-    transfer_checked(cpi_context, total, ctx.accounts.mint.decimals)?;
+    transfer_checked(cpi_context, amount, ctx.accounts.mint.decimals)?;
+
+    emit!(RenewEvent {
+        order_id: order.order_id,
+        buyer: order.buyer,
+        seller: order.seller,
+        machine_id: order.machine_id,
+        status: order.status.clone(),
+        mint: ctx.accounts.mint.key(),
+        duration,
+        amount,
+        total_duration: order.duration,
+        total: order.total,
+        renew_time: Clock::get()?.unix_timestamp,
+    });
     Ok(())
 }
 
@@ -124,17 +194,26 @@ pub fn start_order(ctx: Context<StartOrder>) -> Result<()> {
     order.status = OrderStatus::Training;
     order.start_time = Clock::get()?.unix_timestamp;
 
-    emit!(OrderEvent {
+    emit!(OrderStartedEvent {
         order_id: order.order_id,
         buyer: order.buyer,
         seller: order.seller,
         machine_id: order.machine_id,
+        status: order.status.clone(),
+        order_time: order.order_time,
+        start_time: order.start_time,
     });
     Ok(())
 }
 
 // refund_order is a Solana program function to process a refund for an order.
 pub fn refund_order(ctx: Context<RefundOrder>) -> Result<()> {
+    require_keys_eq!(
+        ctx.accounts.mint.key(),
+        OrderPayment::mint_of(&ctx.accounts.order_payment, ctx.accounts.config.mint)?,
+        DistriAIError::MintMismatch
+    );
+
     let order = &mut ctx.accounts.order;
     require!(
         order.status == OrderStatus::Preparing || order.status == OrderStatus::Training,
@@ -142,11 +221,12 @@ pub fn refund_order(ctx: Context<RefundOrder>) -> Result<()> {
     );
 
     let now_ts = Clock::get()?.unix_timestamp;
-    if order.status == OrderStatus::Preparing {
+    let (seller_amount, buyer_amount, slashed) = if order.status == OrderStatus::Preparing {
         let order_cancelable_time = order
             .order_time
-            .saturating_add(300);
-        require_gte!(now_ts, order_cancelable_time, DistriAIError::IncorrectStatus);
+            .checked_add(ctx.accounts.config.params.order_cancel_window)
+            .ok_or(DistriAIError::MathOverflow)?;
+        require_gte!(now_ts, order_cancelable_time, DistriAIError::OrderNotCancelable);
 
         order.status = OrderStatus::Refunded;
 
@@ -154,6 +234,37 @@ pub fn refund_order(ctx: Context<RefundOrder>) -> Result<()> {
         machine.status = MachineStatus::ForRent;
         machine.failed_count = machine.failed_count.saturating_add(1);
 
+        // The machine was never started, so the provider is slashed
+        let stake_info = ctx.accounts.stake.to_account_info();
+        let slashed = if stake_info.data_is_empty() {
+            // Orders placed before provider staking have no stake to release or slash
+            0
+        } else {
+            let mut stake = Account::<MachineStake>::try_from(&stake_info)?;
+            stake.active_orders = stake.active_orders.saturating_sub(1);
+            stake.last_active_time = now_ts;
+            let slashed = slash_stake(
+                &mut stake,
+                order.total,
+                &ctx.accounts.stake_vault,
+                ctx.bumps.stake_vault,
+                &ctx.accounts.buyer_stake_ata,
+                &ctx.accounts.stake_mint,
+                &ctx.accounts.token_program,
+            )?;
+            stake.exit(&crate::ID)?;
+            slashed
+        };
+        if slashed > 0 {
+            emit!(SlashEvent {
+                order_id: order.order_id,
+                buyer: order.buyer,
+                owner: order.seller,
+                machine_id: order.machine_id,
+                amount: slashed,
+            });
+        }
+
         // Transfer token from vault to buyer
         let mint_key = ctx.accounts.mint.key();
         let signer: &[&[&[u8]]] = &[&[b"vault", mint_key.as_ref(), &[ctx.bumps.vault]]];
@@ -168,18 +279,18 @@ pub fn refund_order(ctx: Context<RefundOrder>) -> Result<()> {
             signer,
         );
         transfer_checked(cpi_context, order.total, ctx.accounts.mint.decimals)?;
+        (0, order.total, slashed)
     } else {
-        let used_duration: u32 = now_ts
-            .saturating_sub(order.start_time)
-            .saturating_div(3600)
-            .saturating_add(1)
-            .try_into()
-            .unwrap();
+        let used_duration = pricing::used_duration(
+            order.start_time,
+            now_ts,
+            ctx.accounts.config.params.duration_unit,
+        )?;
 
         require_gt!(
             order.duration,
             used_duration,
-            DistriAIError::IncorrectStatus
+            DistriAIError::DurationUsedUp
         );
 
         order.status = OrderStatus::Refunded;
@@ -189,8 +300,11 @@ pub fn refund_order(ctx: Context<RefundOrder>) -> Result<()> {
         machine.status = MachineStatus::ForRent;
         machine.completed_count = machine.completed_count.saturating_add(1);
 
+        release_stake(&ctx.accounts.stake, now_ts)?;
+
         // Transfer token from vault to seller
-        let used_total = order.price.saturating_mul(used_duration.into());
+        let (used_total, buyer_amount) =
+            pricing::refund_split(order.total, order.price, used_duration)?;
         let mint_key = ctx.accounts.mint.key();
         let signer: &[&[&[u8]]] = &[&[b"vault", mint_key.as_ref(), &[ctx.bumps.vault]]];
         let cpi_context_seller = CpiContext::new_with_signer(
@@ -216,29 +330,41 @@ pub fn refund_order(ctx: Context<RefundOrder>) -> Result<()> {
             },
             signer,
         );
-        transfer_checked(
-            cpi_context_buyer,
-            order.total.saturating_sub(used_total),
-            ctx.accounts.mint.decimals,
-        )?;
-    }
+        transfer_checked(cpi_context_buyer, buyer_amount, ctx.accounts.mint.decimals)?;
+        (used_total, buyer_amount, 0)
+    };
 
-    emit!(OrderEvent {
+    emit!(OrderRefundedEvent {
         order_id: order.order_id,
         buyer: order.buyer,
         seller: order.seller,
         machine_id: order.machine_id,
+        status: order.status.clone(),
+        mint: ctx.accounts.mint.key(),
+        seller_amount,
+        buyer_amount,
+        slashed,
+        start_time: order.start_time,
+        refund_time: now_ts,
     });
     Ok(())
 }
 
 // Define a public function `order_completed` to handle the completion of an order.
-pub fn order_completed(ctx: Context<OrderCompleted>, metadata: String, score: u8) -> Result<()> {
+// The hours settled to the seller are credited to the machine's work in the current reward `period`.
+// The machine score is left to the buyer, see `rate_order`.
+pub fn order_completed(ctx: Context<OrderCompleted>, metadata: String, period: u32) -> Result<()> {
+    ctx.accounts.config.require_active(ProtocolConfig::PAUSE_COMPLETE_ORDER)?;
     require_gte!(
         Order::METADATA_MAX_LENGTH,
         metadata.len(),
         DistriAIError::StringTooLong
     );
+    require_keys_eq!(
+        ctx.accounts.mint.key(),
+        OrderPayment::mint_of(&ctx.accounts.order_payment, ctx.accounts.config.mint)?,
+        DistriAIError::MintMismatch
+    );
 
     let order = &mut ctx.accounts.order;
     require!(
@@ -246,10 +372,12 @@ pub fn order_completed(ctx: Context<OrderCompleted>, metadata: String, score: u8
         DistriAIError::IncorrectStatus
     );
     let now_ts = Clock::get()?.unix_timestamp;
+    let config = &ctx.accounts.config;
     let order_endtime = order
         .start_time
-        .saturating_add(order.duration.saturating_mul(3600).into());
-    require_gte!(now_ts, order_endtime, DistriAIError::IncorrectStatus);
+        .checked_add(config.duration_seconds(order.duration)?)
+        .ok_or(DistriAIError::MathOverflow)?;
+    require_gte!(now_ts, order_endtime, DistriAIError::OrderNotEnded);
     order.metadata = metadata;
     order.status = OrderStatus::Completed;
 
@@ -260,7 +388,44 @@ pub fn order_completed(ctx: Context<OrderCompleted>, metadata: String, score: u8
     );
     machine.status = MachineStatus::ForRent;
     machine.completed_count = machine.completed_count.saturating_add(1);
-    machine.score = score;
+
+    release_stake(&ctx.accounts.stake, now_ts)?;
+
+    require_eq!(
+        period,
+        config.current_period(now_ts)?,
+        DistriAIError::InvalidPeriod
+    );
+    let reward_work = &mut ctx.accounts.reward_work;
+    reward_work.period = period;
+    reward_work.owner = machine.owner;
+    reward_work.machine_id = machine.uuid;
+    reward_work.score = machine.score;
+    let settled_hours =
+        distribution::settled_order_hours(&order.buyer, &machine.owner, order.total, order.price);
+    reward_work.order_hours = reward_work.order_hours.saturating_add(settled_hours);
+    ctx.accounts.machine_history.record(machine.key(), settled_hours);
+    let reward_period = &mut ctx.accounts.reward_period;
+    reward_period.period = period;
+    refresh_work_weight(reward_period, reward_work);
+
+    // An order bound to a model cannot be completed without its binding.
+    let job_binding_info = ctx.accounts.job_binding.to_account_info();
+    if job_binding_info.data_is_empty() {
+        require!(
+            ctx.accounts.output_version.is_none(),
+            DistriAIError::InvalidVersion
+        );
+    } else {
+        let mut job_binding = Account::<JobBinding>::try_from(&job_binding_info)?;
+        let output_version = ctx
+            .accounts
+            .output_version
+            .as_ref()
+            .map(|version| (version.key(), &***version));
+        emit!(complete_job_binding(&mut job_binding, output_version, now_ts)?);
+        job_binding.exit(&crate::ID)?;
+    }
 
     // Transfer token from vault to seller
     let mint_key = ctx.accounts.mint.key();
@@ -277,11 +442,63 @@ pub fn order_completed(ctx: Context<OrderCompleted>, metadata: String, score: u8
     );
     transfer_checked(cpi_context, order.total, ctx.accounts.mint.decimals)?;
 
-    emit!(OrderEvent {
+    emit!(OrderCompletedEvent {
+        order_id: order.order_id,
+        buyer: order.buyer,
+        seller: order.seller,
+        machine_id: order.machine_id,
+        status: order.status.clone(),
+        mint: ctx.accounts.mint.key(),
+        seller_amount: order.total,
+        start_time: order.start_time,
+        complete_time: now_ts,
+    });
+    Ok(())
+}
+
+// rate_order lets the buyer of a completed order score the machine it ran on.
+// The score updates the machine and its work in the current reward `period`. Machine owners
+// cannot rate orders on their own machines.
+pub fn rate_order(ctx: Context<RateOrder>, score: u8, period: u32) -> Result<()> {
+    require_gte!(distribution::MAX_SCORE, score, DistriAIError::InvalidScore);
+    let order = &ctx.accounts.order;
+    require!(
+        order.status == OrderStatus::Completed,
+        DistriAIError::IncorrectStatus
+    );
+    let machine = &mut ctx.accounts.machine;
+    require_keys_neq!(order.buyer, machine.owner, DistriAIError::Unauthorized);
+    let now_ts = Clock::get()?.unix_timestamp;
+    require_eq!(
+        period,
+        ctx.accounts.config.current_period(now_ts)?,
+        DistriAIError::InvalidPeriod
+    );
+    machine.score = score;
+
+    let order_rating = &mut ctx.accounts.order_rating;
+    order_rating.order = order.key();
+    order_rating.buyer = order.buyer;
+    order_rating.machine_id = order.machine_id;
+    order_rating.score = score;
+    order_rating.rate_time = now_ts;
+
+    let reward_work = &mut ctx.accounts.reward_work;
+    reward_work.period = period;
+    reward_work.owner = machine.owner;
+    reward_work.machine_id = machine.uuid;
+    reward_work.score = score;
+    let reward_period = &mut ctx.accounts.reward_period;
+    reward_period.period = period;
+    refresh_work_weight(reward_period, reward_work);
+
+    emit!(OrderRatedEvent {
         order_id: order.order_id,
         buyer: order.buyer,
         seller: order.seller,
         machine_id: order.machine_id,
+        score,
+        rate_time: now_ts,
     });
     Ok(())
 }
@@ -293,6 +510,11 @@ pub fn order_failed(ctx: Context<OrderFailed>, metadata: String) -> Result<()> {
         metadata.len(),
         DistriAIError::StringTooLong
     );
+    require_keys_eq!(
+        ctx.accounts.mint.key(),
+        OrderPayment::mint_of(&ctx.accounts.order_payment, ctx.accounts.config.mint)?,
+        DistriAIError::MintMismatch
+    );
 
     let order = &mut ctx.accounts.order;
     require!(
@@ -310,7 +532,38 @@ pub fn order_failed(ctx: Context<OrderFailed>, metadata: String) -> Result<()> {
     machine.status = MachineStatus::ForRent;
     machine.failed_count = machine.failed_count.saturating_add(1);
 
-    // Transfer token from vault to seller
+    let now_ts = Clock::get()?.unix_timestamp;
+    let stake_info = ctx.accounts.stake.to_account_info();
+    let slashed = if stake_info.data_is_empty() {
+        // Orders placed before provider staking have no stake to release or slash
+        0
+    } else {
+        let mut stake = Account::<MachineStake>::try_from(&stake_info)?;
+        stake.active_orders = stake.active_orders.saturating_sub(1);
+        stake.last_active_time = now_ts;
+        let slashed = slash_stake(
+            &mut stake,
+            order.total,
+            &ctx.accounts.stake_vault,
+            ctx.bumps.stake_vault,
+            &ctx.accounts.buyer_stake_ata,
+            &ctx.accounts.stake_mint,
+            &ctx.accounts.token_program,
+        )?;
+        stake.exit(&crate::ID)?;
+        slashed
+    };
+    if slashed > 0 {
+        emit!(SlashEvent {
+            order_id: order.order_id,
+            buyer: order.buyer,
+            owner: order.seller,
+            machine_id: order.machine_id,
+            amount: slashed,
+        });
+    }
+
+    // Transfer token from vault to buyer
     let mint_key = ctx.accounts.mint.key();
     let signer: &[&[&[u8]]] = &[&[b"vault", mint_key.as_ref(), &[ctx.bumps.vault]]];
     let cpi_context = CpiContext::new_with_signer(
@@ -325,11 +578,16 @@ pub fn order_failed(ctx: Context<OrderFailed>, metadata: String) -> Result<()> {
     );
     transfer_checked(cpi_context, order.total, ctx.accounts.mint.decimals)?;
 
-    emit!(OrderEvent {
+    emit!(OrderFailedEvent {
         order_id: order.order_id,
         buyer: order.buyer,
         seller: order.seller,
         machine_id: order.machine_id,
+        status: order.status.clone(),
+        mint: ctx.accounts.mint.key(),
+        buyer_amount: order.total,
+        slashed,
+        fail_time: now_ts,
     });
     Ok(())
 }
@@ -342,20 +600,45 @@ pub fn remove_order(ctx: Context<RemoveOrder>) -> Result<()> {
         DistriAIError::IncorrectStatus
     );
 
-    emit!(OrderEvent {
+    emit!(OrderRemovedEvent {
         order_id: order.order_id,
         buyer: order.buyer,
         seller: order.seller,
         machine_id: order.machine_id,
+        status: order.status.clone(),
+        remove_time: Clock::get()?.unix_timestamp,
     });
     Ok(())
 }
 
+// payment_price returns the hourly price of a machine in the given payment mint.
+// Without a machine price only the protocol mint is accepted, at the machine's own price.
+pub fn payment_price(
+    machine: &Machine,
+    machine_price: &Option<Box<Account<MachinePrice>>>,
+    mint: &Account<Mint>,
+    config: &ProtocolConfig,
+) -> Result<u64> {
+    match machine_price {
+        Some(machine_price) => Ok(machine_price.price),
+        None => {
+            require_keys_eq!(mint.key(), config.mint, DistriAIError::MintNotAccepted);
+            Ok(machine.price)
+        }
+    }
+}
+
 // The `derive(Accounts)` macro generates a struct that represents a group of Solana accounts.
 // The `instruction` attribute specifies a parameter that will be passed to the program.
 #[derive(Accounts)]
 #[instruction(order_id: [u8; 16])]
 pub struct PlaceOrder<'info> {
+    #[account(
+        seeds = [b"protocol-config"],
+        bump
+    )]
+    pub config: Box<Account<'info, ProtocolConfig>>,
+
     // Mutable reference to the Machine account
     #[account(mut)]
     pub machine: Box<Account<'info, Machine>>,
@@ -369,6 +652,43 @@ pub struct PlaceOrder<'info> {
     )]
     pub order: Box<Account<'info, Order>>,
 
+    #[account(
+        init,
+        seeds = [b"order-payment", order.key().as_ref()],
+        bump,
+        payer = buyer,
+        space = 8 + OrderPayment::INIT_SPACE
+    )]
+    pub order_payment: Box<Account<'info, OrderPayment>>,
+
+    #[account(
+        seeds = [b"machine-price", machine.key().as_ref(), mint.key().as_ref()],
+        bump
+    )]
+    pub machine_price: Option<Box<Account<'info, MachinePrice>>>,
+
+    #[account(
+        mut,
+        seeds = [b"stake", machine.owner.as_ref(), machine.uuid.as_ref()],
+        bump
+    )]
+    pub stake: Box<Account<'info, MachineStake>>,
+
+    #[account(
+        seeds = [b"heartbeat", machine.key().as_ref()],
+        bump
+    )]
+    pub heartbeat: Box<Account<'info, MachineHeartbeat>>,
+
+    #[account(
+        init_if_needed,
+        seeds = [b"queue", machine.key().as_ref()],
+        bump,
+        payer = buyer,
+        space = 8 + MachineQueue::INIT_SPACE
+    )]
+    pub queue: Box<Account<'info, MachineQueue>>,
+
     #[account(mut)]
     pub buyer: Signer<'info>,
 
@@ -389,9 +709,6 @@ pub struct PlaceOrder<'info> {
     )]
     pub vault: Account<'info, TokenAccount>,
 
-    #[account(
-        address = dist_token::ID
-    )]
     pub mint: Account<'info, Mint>,
     pub token_program: Program<'info, Token>,
     pub associated_token_program: Program<'info, AssociatedToken>,
@@ -400,6 +717,12 @@ pub struct PlaceOrder<'info> {
 
 #[derive(Accounts)]
 pub struct RenewOrder<'info> {
+    #[account(
+        seeds = [b"protocol-config"],
+        bump
+    )]
+    pub config: Box<Account<'info, ProtocolConfig>>,
+
     #[account(
         mut,
         constraint = machine.uuid == order.machine_id && machine.owner == order.seller
@@ -412,6 +735,13 @@ pub struct RenewOrder<'info> {
     )]
     pub order: Box<Account<'info, Order>>,
 
+    /// CHECK: Holds the payment mint of the order, empty for orders paid in DIST before multi-mint payments.
+    #[account(
+        seeds = [b"order-payment", order.key().as_ref()],
+        bump
+    )]
+    pub order_payment: UncheckedAccount<'info>,
+
     #[account(mut)]
     pub buyer: Signer<'info>,
 
@@ -429,9 +759,6 @@ pub struct RenewOrder<'info> {
     )]
     pub vault: Account<'info, TokenAccount>,
 
-    #[account(
-        address = dist_token::ID
-    )]
     pub mint: Account<'info, Mint>,
     pub token_program: Program<'info, Token>,
     pub associated_token_program: Program<'info, AssociatedToken>,
@@ -451,6 +778,12 @@ pub struct StartOrder<'info> {
 
 #[derive(Accounts)]
 pub struct RefundOrder<'info> {
+    #[account(
+        seeds = [b"protocol-config"],
+        bump
+    )]
+    pub config: Box<Account<'info, ProtocolConfig>>,
+
     #[account(
         mut,
         constraint = machine.uuid == order.machine_id && machine.owner == order.seller
@@ -463,6 +796,21 @@ pub struct RefundOrder<'info> {
     )]
     pub order: Box<Account<'info, Order>>,
 
+    /// CHECK: Holds the payment mint of the order, empty for orders paid in DIST before multi-mint payments.
+    #[account(
+        seeds = [b"order-payment", order.key().as_ref()],
+        bump
+    )]
+    pub order_payment: UncheckedAccount<'info>,
+
+    /// CHECK: Stake of the machine, empty for orders placed before provider staking.
+    #[account(
+        mut,
+        seeds = [b"stake", order.seller.as_ref(), order.machine_id.as_ref()],
+        bump
+    )]
+    pub stake: UncheckedAccount<'info>,
+
     #[account(mut)]
     pub buyer: Signer<'info>,
 
@@ -488,8 +836,24 @@ pub struct RefundOrder<'info> {
     pub vault: Account<'info, TokenAccount>,
 
     #[account(
-        address = dist_token::ID
+        mut,
+        associated_token::mint = stake_mint,
+        associated_token::authority = order.buyer
+    )]
+    pub buyer_stake_ata: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [b"stake-vault", stake_mint.key().as_ref()],
+        bump
+    )]
+    pub stake_vault: Account<'info, TokenAccount>,
+
+    #[account(
+        address = config.mint
     )]
+    pub stake_mint: Account<'info, Mint>,
+
     pub mint: Account<'info, Mint>,
     pub token_program: Program<'info, Token>,
     pub associated_token_program: Program<'info, AssociatedToken>,
@@ -497,7 +861,14 @@ pub struct RefundOrder<'info> {
 }
 
 #[derive(Accounts)]
+#[instruction(metadata: String, period: u32)]
 pub struct OrderCompleted<'info> {
+    #[account(
+        seeds = [b"protocol-config"],
+        bump
+    )]
+    pub config: Box<Account<'info, ProtocolConfig>>,
+
     #[account(
         mut,
         constraint = machine.uuid == order.machine_id && machine.owner == order.seller
@@ -510,6 +881,59 @@ pub struct OrderCompleted<'info> {
     )]
     pub order: Box<Account<'info, Order>>,
 
+    /// CHECK: Holds the payment mint of the order, empty for orders paid in DIST before multi-mint payments.
+    #[account(
+        seeds = [b"order-payment", order.key().as_ref()],
+        bump
+    )]
+    pub order_payment: UncheckedAccount<'info>,
+
+    /// CHECK: Stake of the machine, empty for orders placed before provider staking.
+    #[account(
+        mut,
+        seeds = [b"stake", order.seller.as_ref(), order.machine_id.as_ref()],
+        bump
+    )]
+    pub stake: UncheckedAccount<'info>,
+
+    #[account(
+        init_if_needed,
+        seeds = [b"reward-period", period.to_le_bytes().as_ref()],
+        bump,
+        payer = seller,
+        space = 8 + RewardPeriod::INIT_SPACE
+    )]
+    pub reward_period: Box<Account<'info, RewardPeriod>>,
+
+    #[account(
+        init_if_needed,
+        seeds = [b"reward-work", period.to_le_bytes().as_ref(), seller.key().as_ref(), order.machine_id.as_ref()],
+        bump,
+        payer = seller,
+        space = 8 + RewardWork::INIT_SPACE
+    )]
+    pub reward_work: Box<Account<'info, RewardWork>>,
+
+    #[account(
+        init_if_needed,
+        seeds = [b"machine-history", machine.key().as_ref()],
+        bump,
+        payer = seller,
+        space = 8 + MachineHistory::INIT_SPACE
+    )]
+    pub machine_history: Box<Account<'info, MachineHistory>>,
+
+    /// CHECK: Model binding of the order, empty if the buyer did not bind one.
+    #[account(
+        mut,
+        seeds = [b"job-binding", order.key().as_ref()],
+        bump
+    )]
+    pub job_binding: UncheckedAccount<'info>,
+
+    /// Model version produced by the order, if any.
+    pub output_version: Option<Box<Account<'info, ModelVersion>>>,
+
     #[account(mut)]
     pub seller: Signer<'info>,
 
@@ -528,17 +952,73 @@ pub struct OrderCompleted<'info> {
     )]
     pub vault: Account<'info, TokenAccount>,
 
-    #[account(
-        address = dist_token::ID
-    )]
     pub mint: Account<'info, Mint>,
     pub token_program: Program<'info, Token>,
     pub associated_token_program: Program<'info, AssociatedToken>,
     pub system_program: Program<'info, System>,
 }
 
+#[derive(Accounts)]
+#[instruction(score: u8, period: u32)]
+pub struct RateOrder<'info> {
+    #[account(
+        seeds = [b"protocol-config"],
+        bump
+    )]
+    pub config: Box<Account<'info, ProtocolConfig>>,
+
+    #[account(
+        mut,
+        constraint = machine.uuid == order.machine_id && machine.owner == order.seller
+    )]
+    pub machine: Box<Account<'info, Machine>>,
+
+    #[account(
+        has_one = buyer
+    )]
+    pub order: Box<Account<'info, Order>>,
+
+    #[account(
+        init,
+        seeds = [b"order-rating", order.key().as_ref()],
+        bump,
+        payer = buyer,
+        space = 8 + OrderRating::INIT_SPACE
+    )]
+    pub order_rating: Box<Account<'info, OrderRating>>,
+
+    #[account(
+        init_if_needed,
+        seeds = [b"reward-period", period.to_le_bytes().as_ref()],
+        bump,
+        payer = buyer,
+        space = 8 + RewardPeriod::INIT_SPACE
+    )]
+    pub reward_period: Box<Account<'info, RewardPeriod>>,
+
+    #[account(
+        init_if_needed,
+        seeds = [b"reward-work", period.to_le_bytes().as_ref(), order.seller.as_ref(), order.machine_id.as_ref()],
+        bump,
+        payer = buyer,
+        space = 8 + RewardWork::INIT_SPACE
+    )]
+    pub reward_work: Box<Account<'info, RewardWork>>,
+
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
 #[derive(Accounts)]
 pub struct OrderFailed<'info> {
+    #[account(
+        seeds = [b"protocol-config"],
+        bump
+    )]
+    pub config: Box<Account<'info, ProtocolConfig>>,
+
     #[account(
         mut,
         constraint = machine.uuid == order.machine_id && machine.owner == order.seller
@@ -551,6 +1031,21 @@ pub struct OrderFailed<'info> {
     )]
     pub order: Box<Account<'info, Order>>,
 
+    /// CHECK: Holds the payment mint of the order, empty for orders paid in DIST before multi-mint payments.
+    #[account(
+        seeds = [b"order-payment", order.key().as_ref()],
+        bump
+    )]
+    pub order_payment: UncheckedAccount<'info>,
+
+    /// CHECK: Stake of the machine, empty for orders placed before provider staking.
+    #[account(
+        mut,
+        seeds = [b"stake", order.seller.as_ref(), order.machine_id.as_ref()],
+        bump
+    )]
+    pub stake: UncheckedAccount<'info>,
+
     #[account(mut)]
     pub seller: Signer<'info>,
 
@@ -569,8 +1064,24 @@ pub struct OrderFailed<'info> {
     pub vault: Account<'info, TokenAccount>,
 
     #[account(
-        address = dist_token::ID
+        mut,
+        associated_token::mint = stake_mint,
+        associated_token::authority = order.buyer
+    )]
+    pub buyer_stake_ata: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [b"stake-vault", stake_mint.key().as_ref()],
+        bump
     )]
+    pub stake_vault: Account<'info, TokenAccount>,
+
+    #[account(
+        address = config.mint
+    )]
+    pub stake_mint: Account<'info, Mint>,
+
     pub mint: Account<'info, Mint>,
     pub token_program: Program<'info, Token>,
     pub associated_token_program: Program<'info, AssociatedToken>,
@@ -585,14 +1096,124 @@ pub struct RemoveOrder<'info> {
     )]
     pub order: Box<Account<'info, Order>>,
 
+    #[account(
+        mut,
+        seeds = [b"order-payment", order.key().as_ref()],
+        bump,
+        close = buyer
+    )]
+    pub order_payment: Option<Box<Account<'info, OrderPayment>>>,
+
     #[account(mut)]
     pub buyer: Signer<'info>,
 }
 
 #[event]
-pub struct OrderEvent {
+#[derive(Debug, Clone)]
+pub struct OrderPlacedEvent {
+    pub order_id: [u8; 16],
+    pub buyer: Pubkey,
+    pub seller: Pubkey,
+    pub machine_id: [u8; 16],
+    pub status: OrderStatus,
+    pub mint: Pubkey,
+    pub price: u64,
+    pub duration: u32,
+    pub total: u64,
+    pub order_time: i64,
+}
+
+#[event]
+#[derive(Debug, Clone)]
+pub struct OrderStartedEvent {
+    pub order_id: [u8; 16],
+    pub buyer: Pubkey,
+    pub seller: Pubkey,
+    pub machine_id: [u8; 16],
+    pub status: OrderStatus,
+    pub order_time: i64,
+    pub start_time: i64,
+}
+
+#[event]
+#[derive(Debug, Clone)]
+pub struct RenewEvent {
+    pub order_id: [u8; 16],
+    pub buyer: Pubkey,
+    pub seller: Pubkey,
+    pub machine_id: [u8; 16],
+    pub status: OrderStatus,
+    pub mint: Pubkey,
+    pub duration: u32,
+    pub amount: u64,
+    pub total_duration: u32,
+    pub total: u64,
+    pub renew_time: i64,
+}
+
+#[event]
+#[derive(Debug, Clone)]
+pub struct OrderRefundedEvent {
+    pub order_id: [u8; 16],
+    pub buyer: Pubkey,
+    pub seller: Pubkey,
+    pub machine_id: [u8; 16],
+    pub status: OrderStatus,
+    pub mint: Pubkey,
+    pub seller_amount: u64,
+    pub buyer_amount: u64,
+    /// Provider stake slashed to the buyer, only for orders that never started.
+    pub slashed: u64,
+    pub start_time: i64,
+    pub refund_time: i64,
+}
+
+#[event]
+#[derive(Debug, Clone)]
+pub struct OrderCompletedEvent {
+    pub order_id: [u8; 16],
+    pub buyer: Pubkey,
+    pub seller: Pubkey,
+    pub machine_id: [u8; 16],
+    pub status: OrderStatus,
+    pub mint: Pubkey,
+    pub seller_amount: u64,
+    pub start_time: i64,
+    pub complete_time: i64,
+}
+
+#[event]
+#[derive(Debug, Clone)]
+pub struct OrderRatedEvent {
+    pub order_id: [u8; 16],
+    pub buyer: Pubkey,
+    pub seller: Pubkey,
+    pub machine_id: [u8; 16],
+    pub score: u8,
+    pub rate_time: i64,
+}
+
+#[event]
+#[derive(Debug, Clone)]
+pub struct OrderFailedEvent {
+    pub order_id: [u8; 16],
+    pub buyer: Pubkey,
+    pub seller: Pubkey,
+    pub machine_id: [u8; 16],
+    pub status: OrderStatus,
+    pub mint: Pubkey,
+    pub buyer_amount: u64,
+    pub slashed: u64,
+    pub fail_time: i64,
+}
+
+#[event]
+#[derive(Debug, Clone)]
+pub struct OrderRemovedEvent {
     pub order_id: [u8; 16],
     pub buyer: Pubkey,
     pub seller: Pubkey,
     pub machine_id: [u8; 16],
+    pub status: OrderStatus,
+    pub remove_time: i64,
 }
@@ -0,0 +1,85 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::Mint;
+use crate::state::machine::*;
+use crate::state::payment::*;
+
+/// Accepts a payment mint for a machine at the given price per hour.
+pub fn set_machine_price(ctx: Context<SetMachinePrice>, price: u64) -> Result<()> {
+    let machine = &ctx.accounts.machine;
+    let machine_price = &mut ctx.accounts.machine_price;
+    machine_price.owner = machine.owner;
+    machine_price.machine_id = machine.uuid;
+    machine_price.mint = ctx.accounts.mint.key();
+    machine_price.price = price;
+
+    emit!(MachinePriceEvent {
+        owner: machine_price.owner,
+        machine_id: machine_price.machine_id,
+        mint: machine_price.mint,
+        price,
+    });
+    Ok(())
+}
+
+/// Stops accepting a payment mint for a machine.
+pub fn remove_machine_price(ctx: Context<RemoveMachinePrice>) -> Result<()> {
+    let machine_price = &ctx.accounts.machine_price;
+
+    emit!(MachinePriceEvent {
+        owner: machine_price.owner,
+        machine_id: machine_price.machine_id,
+        mint: machine_price.mint,
+        price: 0,
+    });
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct SetMachinePrice<'info> {
+    #[account(
+        has_one = owner
+    )]
+    pub machine: Box<Account<'info, Machine>>,
+
+    #[account(
+        init_if_needed,
+        seeds = [b"machine-price", machine.key().as_ref(), mint.key().as_ref()],
+        bump,
+        payer = owner,
+        space = 8 + MachinePrice::INIT_SPACE
+    )]
+    pub machine_price: Box<Account<'info, MachinePrice>>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    pub mint: Account<'info, Mint>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RemoveMachinePrice<'info> {
+    #[account(
+        has_one = owner
+    )]
+    pub machine: Box<Account<'info, Machine>>,
+
+    #[account(
+        mut,
+        seeds = [b"machine-price", machine.key().as_ref(), machine_price.mint.as_ref()],
+        bump,
+        close = owner
+    )]
+    pub machine_price: Box<Account<'info, MachinePrice>>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+}
+
+#[event]
+pub struct MachinePriceEvent {
+    pub owner: Pubkey,
+    pub machine_id: [u8; 16],
+    pub mint: Pubkey,
+    pub price: u64,
+}
@@ -0,0 +1,50 @@
+use anchor_lang::prelude::*;
+use crate::errors::DistriAIError;
+
+/// Amount escrowed for renting a machine at `price` per hour for `duration` hours.
+pub fn order_total(price: u64, duration: u32) -> Result<u64> {
+    price
+        .checked_mul(duration.into())
+        .ok_or_else(|| error!(DistriAIError::MathOverflow))
+}
+
+/// Duration and total of an order renewed for `extra_duration` more hours at its own `price`.
+///
+/// Returns the new duration, the new total and the amount the buyer pays for the renewal.
+pub fn renewal(
+    duration: u32,
+    total: u64,
+    price: u64,
+    extra_duration: u32,
+) -> Result<(u32, u64, u64)> {
+    let amount = order_total(price, extra_duration)?;
+    let new_duration = duration
+        .checked_add(extra_duration)
+        .ok_or_else(|| error!(DistriAIError::MathOverflow))?;
+    let new_total = total
+        .checked_add(amount)
+        .ok_or_else(|| error!(DistriAIError::MathOverflow))?;
+    Ok((new_duration, new_total, amount))
+}
+
+/// Hours of an order started at `start_time` that are used at `now`. The current hour counts as used.
+pub fn used_duration(start_time: i64, now: i64, duration_unit: i64) -> Result<u32> {
+    require_gt!(duration_unit, 0, DistriAIError::InvalidConfig);
+    let elapsed = now
+        .checked_sub(start_time)
+        .filter(|elapsed| *elapsed >= 0)
+        .ok_or_else(|| error!(DistriAIError::MathOverflow))?;
+    u32::try_from(elapsed / duration_unit + 1).map_err(|_| error!(DistriAIError::MathOverflow))
+}
+
+/// Splits the escrow of an order refunded after `used_duration` hours.
+///
+/// Returns the amount paid to the seller for the used hours and the amount returned to the buyer.
+/// The two always add up to `total`.
+pub fn refund_split(total: u64, price: u64, used_duration: u32) -> Result<(u64, u64)> {
+    let seller_amount = order_total(price, used_duration)?;
+    let buyer_amount = total
+        .checked_sub(seller_amount)
+        .ok_or_else(|| error!(DistriAIError::MathOverflow))?;
+    Ok((seller_amount, buyer_amount))
+}
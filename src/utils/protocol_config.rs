@@ -0,0 +1,264 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::Mint;
+use crate::errors::DistriAIError;
+use crate::state::protocol_config::*;
+
+/// Creates the protocol config. Only the upgrade authority of the program can do this, once;
+/// afterwards the config authority governs every parameter.
+pub fn initialize_config(ctx: Context<InitializeConfig>, params: ProtocolParams) -> Result<()> {
+    params.validate()?;
+
+    let config = &mut ctx.accounts.config;
+    config.authority = ctx.accounts.admin.key();
+    config.pending_authority = Pubkey::default();
+    config.guardian = config.authority;
+    config.paused = 0;
+    config.mint = ctx.accounts.mint.key();
+    config.params = params;
+    config.metering_authority = Pubkey::default();
+    config.payment_mints = vec![config.mint];
+
+    emit!(ConfigUpdatedEvent {
+        authority: config.authority,
+        old: params,
+        new: params,
+    });
+    Ok(())
+}
+
+/// Replaces the protocol parameters, emitting the old and new values.
+///
+/// `duration_unit`, `reward_genesis_time` and `reward_period_duration` cannot be changed.
+pub fn update_config(ctx: Context<UpdateConfig>, params: ProtocolParams) -> Result<()> {
+    let config = &mut ctx.accounts.config;
+    config.params.validate_update(&params)?;
+    let old = config.params;
+    config.params = params;
+
+    emit!(ConfigUpdatedEvent {
+        authority: config.authority,
+        old,
+        new: params,
+    });
+    Ok(())
+}
+
+/// Proposes a new authority, which takes over once it calls `accept_authority`.
+///
+/// Proposing the default key cancels a pending handover.
+pub fn transfer_authority(ctx: Context<UpdateConfig>, new_authority: Pubkey) -> Result<()> {
+    let config = &mut ctx.accounts.config;
+    config.pending_authority = new_authority;
+
+    emit!(AuthorityEvent {
+        authority: config.authority,
+        pending_authority: new_authority,
+    });
+    Ok(())
+}
+
+/// Completes an authority handover started by `transfer_authority`.
+pub fn accept_authority(ctx: Context<AcceptAuthority>) -> Result<()> {
+    let config = &mut ctx.accounts.config;
+    config.authority = config.pending_authority;
+    config.pending_authority = Pubkey::default();
+
+    emit!(AuthorityEvent {
+        authority: config.authority,
+        pending_authority: config.pending_authority,
+    });
+    Ok(())
+}
+
+/// Sets the key allowed to pause and resume instructions.
+pub fn set_guardian(ctx: Context<UpdateConfig>, guardian: Pubkey) -> Result<()> {
+    let config = &mut ctx.accounts.config;
+    config.guardian = guardian;
+
+    emit!(GuardianEvent {
+        authority: config.authority,
+        guardian,
+    });
+    Ok(())
+}
+
+/// Sets the key allowed to record calls served under per-call model licenses.
+pub fn set_metering_authority(ctx: Context<UpdateConfig>, metering_authority: Pubkey) -> Result<()> {
+    let config = &mut ctx.accounts.config;
+    config.metering_authority = metering_authority;
+
+    emit!(MeteringAuthorityEvent {
+        authority: config.authority,
+        metering_authority,
+    });
+    Ok(())
+}
+
+/// Replaces the mints that model licenses can be priced in.
+pub fn set_payment_mints(ctx: Context<UpdateConfig>, payment_mints: Vec<Pubkey>) -> Result<()> {
+    require_gte!(
+        ProtocolConfig::MAX_PAYMENT_MINTS,
+        payment_mints.len(),
+        DistriAIError::InvalidConfig
+    );
+
+    let config = &mut ctx.accounts.config;
+    config.payment_mints = payment_mints.clone();
+
+    emit!(PaymentMintsEvent {
+        authority: config.authority,
+        payment_mints,
+    });
+    Ok(())
+}
+
+/// Replaces the set of paused instructions with `paused`, a mask of `ProtocolConfig::PAUSE_*`.
+///
+/// Emits `Paused` for the newly paused flags and `Resumed` for the newly resumed ones.
+pub fn set_paused(ctx: Context<SetPaused>, paused: u64) -> Result<()> {
+    require_eq!(
+        paused & !ProtocolConfig::PAUSE_ALL,
+        0,
+        DistriAIError::InvalidConfig
+    );
+
+    let config = &mut ctx.accounts.config;
+    let previous = config.paused;
+    config.paused = paused;
+
+    let guardian = ctx.accounts.guardian.key();
+    let now_ts = Clock::get()?.unix_timestamp;
+    if paused & !previous != 0 {
+        emit!(Paused {
+            guardian,
+            flags: paused & !previous,
+            paused,
+            time: now_ts,
+        });
+    }
+    if previous & !paused != 0 {
+        emit!(Resumed {
+            guardian,
+            flags: previous & !paused,
+            paused,
+            time: now_ts,
+        });
+    }
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct InitializeConfig<'info> {
+    #[account(
+        init,
+        seeds = [b"protocol-config"],
+        bump,
+        payer = admin,
+        space = 8 + ProtocolConfig::INIT_SPACE
+    )]
+    pub config: Box<Account<'info, ProtocolConfig>>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(
+        seeds = [crate::ID.as_ref()],
+        bump,
+        seeds::program = anchor_lang::solana_program::bpf_loader_upgradeable::ID,
+        constraint = program_data.upgrade_authority_address == Some(admin.key()) @ DistriAIError::Unauthorized
+    )]
+    pub program_data: Account<'info, ProgramData>,
+
+    pub mint: Account<'info, Mint>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateConfig<'info> {
+    #[account(
+        mut,
+        seeds = [b"protocol-config"],
+        bump,
+        has_one = authority @ DistriAIError::Unauthorized
+    )]
+    pub config: Box<Account<'info, ProtocolConfig>>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct AcceptAuthority<'info> {
+    #[account(
+        mut,
+        seeds = [b"protocol-config"],
+        bump,
+        constraint = config.pending_authority == pending_authority.key() @ DistriAIError::Unauthorized
+    )]
+    pub config: Box<Account<'info, ProtocolConfig>>,
+
+    pub pending_authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetPaused<'info> {
+    #[account(
+        mut,
+        seeds = [b"protocol-config"],
+        bump,
+        has_one = guardian @ DistriAIError::Unauthorized
+    )]
+    pub config: Box<Account<'info, ProtocolConfig>>,
+
+    pub guardian: Signer<'info>,
+}
+
+#[event]
+pub struct ConfigUpdatedEvent {
+    pub authority: Pubkey,
+    pub old: ProtocolParams,
+    pub new: ProtocolParams,
+}
+
+#[event]
+pub struct AuthorityEvent {
+    pub authority: Pubkey,
+    pub pending_authority: Pubkey,
+}
+
+#[event]
+pub struct GuardianEvent {
+    pub authority: Pubkey,
+    pub guardian: Pubkey,
+}
+
+#[event]
+pub struct MeteringAuthorityEvent {
+    pub authority: Pubkey,
+    pub metering_authority: Pubkey,
+}
+
+#[event]
+pub struct PaymentMintsEvent {
+    pub authority: Pubkey,
+    pub payment_mints: Vec<Pubkey>,
+}
+
+#[event]
+pub struct Paused {
+    pub guardian: Pubkey,
+    /// Flags paused by this call.
+    pub flags: u64,
+    /// All paused flags after this call.
+    pub paused: u64,
+    pub time: i64,
+}
+
+#[event]
+pub struct Resumed {
+    pub guardian: Pubkey,
+    /// Flags resumed by this call.
+    pub flags: u64,
+    /// All paused flags after this call.
+    pub paused: u64,
+    pub time: i64,
+}
@@ -0,0 +1,503 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token::{transfer_checked, Mint, Token, TokenAccount, TransferChecked},
+};
+use crate::errors::DistriAIError;
+use crate::state::heartbeat::*;
+use crate::state::machine::*;
+use crate::state::order::*;
+use crate::state::payment::*;
+use crate::state::protocol_config::*;
+use crate::state::queue::*;
+use crate::state::stake::*;
+use crate::utils::order::{payment_price, OrderPlacedEvent};
+use crate::utils::pricing;
+
+/// Reserves a busy machine by queueing an escrowed order behind the current one.
+///
+/// A machine with a non-empty queue can always be reserved, even while it is `ForRent` and the head
+/// reservation has not been activated or skipped yet, since `place_order` refuses it in that state.
+pub fn queue_order(
+    ctx: Context<QueueOrder>,
+    order_id: [u8; 16],
+    duration: u32,
+    expire_time: i64,
+    metadata: String,
+) -> Result<()> {
+    ctx.accounts.config.require_active(ProtocolConfig::PAUSE_QUEUE_ORDER)?;
+
+    require_gte!(
+        Order::METADATA_MAX_LENGTH.min(QueuedOrder::METADATA_MAX_LENGTH),
+        metadata.len(),
+        DistriAIError::StringTooLong
+    );
+
+    let machine = &ctx.accounts.machine;
+    require!(
+        machine.status == MachineStatus::Renting || !ctx.accounts.queue.is_empty(),
+        DistriAIError::IncorrectStatus
+    );
+    require_gt!(duration, 0, DistriAIError::InvalidQuantity);
+    require_gte!(
+        machine.max_duration,
+        duration,
+        DistriAIError::DurationTooMuch
+    );
+    let now_ts = Clock::get()?.unix_timestamp;
+    require_gt!(expire_time, now_ts, DistriAIError::QueueExpired);
+
+    // Transfer token from buyer to vault
+    let price = payment_price(
+        machine,
+        &ctx.accounts.machine_price,
+        &ctx.accounts.mint,
+        &ctx.accounts.config,
+    )?;
+    let total = pricing::order_total(price, duration)?;
+    let cpi_context = CpiContext::new(
+        ctx.accounts.token_program.to_account_info(),
+        TransferChecked {
+            from: ctx.accounts.buyer_ata.to_account_info(),
+            mint: ctx.accounts.mint.to_account_info(),
+            to: ctx.accounts.vault.to_account_info(),
+            authority: ctx.accounts.buyer.to_account_info(),
+        },
+    );
+    transfer_checked(cpi_context, total, ctx.accounts.mint.decimals)?;
+
+    let queue = &mut ctx.accounts.queue;
+    queue.owner = machine.owner;
+    queue.machine_id = machine.uuid;
+
+    let queued_order = &mut ctx.accounts.queued_order;
+    queued_order.order_id = order_id;
+    queued_order.buyer = ctx.accounts.buyer.key();
+    queued_order.seller = machine.owner;
+    queued_order.machine_id = machine.uuid;
+    queued_order.position = queue.tail;
+    queued_order.mint = ctx.accounts.mint.key();
+    queued_order.price = price;
+    queued_order.duration = duration;
+    queued_order.total = total;
+    queued_order.metadata = metadata;
+    queued_order.status = QueuedOrderStatus::Queued;
+    queued_order.queue_time = now_ts;
+    queued_order.expire_time = expire_time;
+
+    queue.tail = queue.tail.saturating_add(1);
+
+    emit!(QueueEvent {
+        order_id: queued_order.order_id,
+        buyer: queued_order.buyer,
+        seller: queued_order.seller,
+        machine_id: queued_order.machine_id,
+        position: queued_order.position,
+        head: queue.head,
+        status: queued_order.status.clone(),
+    });
+    Ok(())
+}
+
+/// Cancels a queued order and returns its escrow to the buyer. Either the buyer or the seller may cancel.
+pub fn cancel_queued_order(ctx: Context<CancelQueuedOrder>) -> Result<()> {
+    let queued_order = &mut ctx.accounts.queued_order;
+    require!(
+        queued_order.status == QueuedOrderStatus::Queued,
+        DistriAIError::IncorrectStatus
+    );
+    let signer = ctx.accounts.signer.key();
+    require!(
+        signer == queued_order.buyer || signer == queued_order.seller,
+        DistriAIError::Unauthorized
+    );
+    queued_order.status = QueuedOrderStatus::Cancelled;
+
+    // Transfer token from vault to buyer
+    let mint_key = ctx.accounts.mint.key();
+    let signer: &[&[&[u8]]] = &[&[b"vault", mint_key.as_ref(), &[ctx.bumps.vault]]];
+    let cpi_context = CpiContext::new_with_signer(
+        ctx.accounts.token_program.to_account_info(),
+        TransferChecked {
+            from: ctx.accounts.vault.to_account_info(),
+            mint: ctx.accounts.mint.to_account_info(),
+            to: ctx.accounts.buyer_ata.to_account_info(),
+            authority: ctx.accounts.vault.to_account_info(),
+        },
+        signer,
+    );
+    transfer_checked(cpi_context, queued_order.total, ctx.accounts.mint.decimals)?;
+
+    emit!(QueueEvent {
+        order_id: queued_order.order_id,
+        buyer: queued_order.buyer,
+        seller: queued_order.seller,
+        machine_id: queued_order.machine_id,
+        position: queued_order.position,
+        head: ctx.accounts.queue.head,
+        status: queued_order.status.clone(),
+    });
+    Ok(())
+}
+
+/// Turns the queued order at the head of the queue into a live order once the machine is free.
+///
+/// Permissionless, so the provider, the buyer or a keeper can activate the next reservation in the
+/// same transaction that completes or refunds the current order. The signer pays the order rent;
+/// the rent of the closed queued order goes back to the buyer who paid it. As with `place_order`,
+/// the machine must still be sending heartbeats.
+pub fn activate_queued_order(ctx: Context<ActivateQueuedOrder>) -> Result<()> {
+    let queue = &mut ctx.accounts.queue;
+    let queued_order = &mut ctx.accounts.queued_order;
+    require_eq!(queued_order.position, queue.head, DistriAIError::NotQueueHead);
+    require!(
+        queued_order.status == QueuedOrderStatus::Queued,
+        DistriAIError::IncorrectStatus
+    );
+    let now_ts = Clock::get()?.unix_timestamp;
+    require!(!queued_order.is_expired(now_ts), DistriAIError::QueueExpired);
+
+    let machine = &mut ctx.accounts.machine;
+    require!(
+        machine.status == MachineStatus::ForRent,
+        DistriAIError::IncorrectStatus
+    );
+    require!(
+        ctx.accounts.heartbeat.is_online(now_ts, &ctx.accounts.config.params),
+        DistriAIError::MachineOffline
+    );
+
+    let stake = &mut ctx.accounts.stake;
+    require_gte!(
+        stake.amount,
+        MachineStake::MIN_FOR_RENT,
+        DistriAIError::InsufficientStake
+    );
+    stake.active_orders = stake.active_orders.saturating_add(1);
+
+    let order = &mut ctx.accounts.order;
+    order.order_id = queued_order.order_id;
+    order.buyer = queued_order.buyer;
+    order.seller = queued_order.seller;
+    order.machine_id = queued_order.machine_id;
+    order.price = queued_order.price;
+    order.duration = queued_order.duration;
+    order.total = queued_order.total;
+    order.metadata = queued_order.metadata.clone();
+    order.status = OrderStatus::Preparing;
+    order.order_time = now_ts;
+    order.refund_time = 0;
+
+    machine.status = MachineStatus::Renting;
+    machine.order_pda = order.key();
+
+    let order_payment = &mut ctx.accounts.order_payment;
+    order_payment.order = order.key();
+    order_payment.mint = queued_order.mint;
+
+    queued_order.status = QueuedOrderStatus::Activated;
+    queue.head = queue.head.saturating_add(1);
+
+    emit!(OrderPlacedEvent {
+        order_id: order.order_id,
+        buyer: order.buyer,
+        seller: order.seller,
+        machine_id: order.machine_id,
+        status: order.status.clone(),
+        mint: order_payment.mint,
+        price: order.price,
+        duration: order.duration,
+        total: order.total,
+        order_time: order.order_time,
+    });
+
+    emit!(QueueEvent {
+        order_id: queued_order.order_id,
+        buyer: queued_order.buyer,
+        seller: queued_order.seller,
+        machine_id: queued_order.machine_id,
+        position: queued_order.position,
+        head: queue.head,
+        status: queued_order.status.clone(),
+    });
+    Ok(())
+}
+
+/// Pops a cancelled or expired order off the head of the queue, refunding the escrow of expired ones.
+pub fn skip_queued_order(ctx: Context<SkipQueuedOrder>) -> Result<()> {
+    let queue = &mut ctx.accounts.queue;
+    let queued_order = &mut ctx.accounts.queued_order;
+    require_eq!(queued_order.position, queue.head, DistriAIError::NotQueueHead);
+
+    if queued_order.status == QueuedOrderStatus::Queued {
+        let now_ts = Clock::get()?.unix_timestamp;
+        require!(queued_order.is_expired(now_ts), DistriAIError::QueueNotExpired);
+        queued_order.status = QueuedOrderStatus::Expired;
+
+        // Transfer token from vault to buyer
+        let mint_key = ctx.accounts.mint.key();
+        let signer: &[&[&[u8]]] = &[&[b"vault", mint_key.as_ref(), &[ctx.bumps.vault]]];
+        let cpi_context = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            TransferChecked {
+                from: ctx.accounts.vault.to_account_info(),
+                mint: ctx.accounts.mint.to_account_info(),
+                to: ctx.accounts.buyer_ata.to_account_info(),
+                authority: ctx.accounts.vault.to_account_info(),
+            },
+            signer,
+        );
+        transfer_checked(cpi_context, queued_order.total, ctx.accounts.mint.decimals)?;
+    }
+    require!(
+        queued_order.status == QueuedOrderStatus::Cancelled
+            || queued_order.status == QueuedOrderStatus::Expired,
+        DistriAIError::IncorrectStatus
+    );
+
+    queue.head = queue.head.saturating_add(1);
+
+    emit!(QueueEvent {
+        order_id: queued_order.order_id,
+        buyer: queued_order.buyer,
+        seller: queued_order.seller,
+        machine_id: queued_order.machine_id,
+        position: queued_order.position,
+        head: queue.head,
+        status: queued_order.status.clone(),
+    });
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct QueueOrder<'info> {
+    #[account(
+        seeds = [b"protocol-config"],
+        bump
+    )]
+    pub config: Box<Account<'info, ProtocolConfig>>,
+
+    pub machine: Box<Account<'info, Machine>>,
+
+    #[account(
+        init_if_needed,
+        seeds = [b"queue", machine.key().as_ref()],
+        bump,
+        payer = buyer,
+        space = 8 + MachineQueue::INIT_SPACE
+    )]
+    pub queue: Box<Account<'info, MachineQueue>>,
+
+    #[account(
+        init,
+        seeds = [b"queued-order", machine.key().as_ref(), queue.tail.to_le_bytes().as_ref()],
+        bump,
+        payer = buyer,
+        space = 8 + QueuedOrder::INIT_SPACE
+    )]
+    pub queued_order: Box<Account<'info, QueuedOrder>>,
+
+    #[account(
+        seeds = [b"machine-price", machine.key().as_ref(), mint.key().as_ref()],
+        bump
+    )]
+    pub machine_price: Option<Box<Account<'info, MachinePrice>>>,
+
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = buyer
+    )]
+    pub buyer_ata: Account<'info, TokenAccount>,
+
+    #[account(
+        init_if_needed,
+        seeds = [b"vault", mint.key().as_ref()],
+        bump,
+        payer = buyer,
+        token::mint = mint,
+        token::authority = vault
+    )]
+    pub vault: Account<'info, TokenAccount>,
+
+    pub mint: Account<'info, Mint>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CancelQueuedOrder<'info> {
+    pub machine: Box<Account<'info, Machine>>,
+
+    #[account(
+        seeds = [b"queue", machine.key().as_ref()],
+        bump
+    )]
+    pub queue: Box<Account<'info, MachineQueue>>,
+
+    #[account(
+        mut,
+        seeds = [b"queued-order", machine.key().as_ref(), queued_order.position.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub queued_order: Box<Account<'info, QueuedOrder>>,
+
+    pub signer: Signer<'info>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = queued_order.buyer
+    )]
+    pub buyer_ata: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", mint.key().as_ref()],
+        bump
+    )]
+    pub vault: Account<'info, TokenAccount>,
+
+    #[account(
+        address = queued_order.mint
+    )]
+    pub mint: Account<'info, Mint>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
+
+#[derive(Accounts)]
+pub struct ActivateQueuedOrder<'info> {
+    #[account(
+        seeds = [b"protocol-config"],
+        bump
+    )]
+    pub config: Box<Account<'info, ProtocolConfig>>,
+
+    #[account(
+        mut,
+        constraint = machine.uuid == queued_order.machine_id && machine.owner == queued_order.seller
+    )]
+    pub machine: Box<Account<'info, Machine>>,
+
+    #[account(
+        seeds = [b"heartbeat", machine.key().as_ref()],
+        bump
+    )]
+    pub heartbeat: Box<Account<'info, MachineHeartbeat>>,
+
+    #[account(
+        mut,
+        seeds = [b"queue", machine.key().as_ref()],
+        bump
+    )]
+    pub queue: Box<Account<'info, MachineQueue>>,
+
+    #[account(
+        mut,
+        seeds = [b"queued-order", machine.key().as_ref(), queue.head.to_le_bytes().as_ref()],
+        bump,
+        close = buyer
+    )]
+    pub queued_order: Box<Account<'info, QueuedOrder>>,
+
+    /// CHECK: Receives the rent of the closed queued order.
+    #[account(
+        mut,
+        address = queued_order.buyer
+    )]
+    pub buyer: UncheckedAccount<'info>,
+
+    #[account(
+        init,
+        seeds = [b"order", queued_order.buyer.as_ref(), queued_order.order_id.as_ref()],
+        bump,
+        payer = signer,
+        space = 8 + Order::INIT_SPACE
+    )]
+    pub order: Box<Account<'info, Order>>,
+
+    #[account(
+        init,
+        seeds = [b"order-payment", order.key().as_ref()],
+        bump,
+        payer = signer,
+        space = 8 + OrderPayment::INIT_SPACE
+    )]
+    pub order_payment: Box<Account<'info, OrderPayment>>,
+
+    #[account(
+        mut,
+        seeds = [b"stake", machine.owner.as_ref(), machine.uuid.as_ref()],
+        bump
+    )]
+    pub stake: Box<Account<'info, MachineStake>>,
+
+    #[account(mut)]
+    pub signer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SkipQueuedOrder<'info> {
+    pub machine: Box<Account<'info, Machine>>,
+
+    #[account(
+        mut,
+        seeds = [b"queue", machine.key().as_ref()],
+        bump
+    )]
+    pub queue: Box<Account<'info, MachineQueue>>,
+
+    #[account(
+        mut,
+        seeds = [b"queued-order", machine.key().as_ref(), queue.head.to_le_bytes().as_ref()],
+        bump,
+        close = buyer
+    )]
+    pub queued_order: Box<Account<'info, QueuedOrder>>,
+
+    /// CHECK: Receives the rent of the closed queued order.
+    #[account(
+        mut,
+        address = queued_order.buyer
+    )]
+    pub buyer: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = buyer
+    )]
+    pub buyer_ata: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", mint.key().as_ref()],
+        bump
+    )]
+    pub vault: Account<'info, TokenAccount>,
+
+    #[account(
+        address = queued_order.mint
+    )]
+    pub mint: Account<'info, Mint>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
+
+#[event]
+pub struct QueueEvent {
+    pub order_id: [u8; 16],
+    pub buyer: Pubkey,
+    pub seller: Pubkey,
+    pub machine_id: [u8; 16],
+    pub position: u64,
+    pub head: u64,
+    pub status: QueuedOrderStatus,
+}
@@ -3,13 +3,17 @@ use anchor_spl::{
     associated_token::AssociatedToken,
     token::{transfer_checked, Mint, Token, TokenAccount, TransferChecked},
 };
-use crate::dist_token;
 use crate::errors::DistriAIError;
 use crate::state::machine::*;
-use crate::state::reward::*;
+use crate::state::protocol_config::*;
+use crate::state::reward_period::*;
+use crate::state::vesting::*;
+use crate::utils::distribution;
 
 //  Deposits an amount into the reward pool.
 pub fn reward_pool_deposit(ctx: Context<RewardPoolDeposit>, amount: u64) -> Result<()> {
+    ctx.accounts.config.require_active(ProtocolConfig::PAUSE_REWARD_POOL_DEPOSIT)?;
+
     let cpi_context = CpiContext::new(
         ctx.accounts.token_program.to_account_info(),
         TransferChecked {
@@ -23,21 +27,96 @@ pub fn reward_pool_deposit(ctx: Context<RewardPoolDeposit>, amount: u64) -> Resu
     Ok(())
 }
 
-/// Processes a reward claim for a specific period.
-pub fn claim(ctx: Context<Claim>, period: u32) -> Result<()> {
+/// Closes an ended period and earmarks its pool from the unreserved balance of the reward pool.
+pub fn close_period(ctx: Context<ClosePeriod>, period: u32) -> Result<()> {
+    let config = &ctx.accounts.config;
     require_gt!(
-        Reward::current_period()?,
+        config.current_period(Clock::get()?.unix_timestamp)?,
         period,
         DistriAIError::InvalidPeriod
     );
 
-    let reward = &ctx.accounts.reward;
-    let reward_machine = &mut ctx.accounts.reward_machine;
-    require!(!reward_machine.claimed, DistriAIError::RepeatClaim);
-    reward_machine.claimed = true;
+    let reward_period = &mut ctx.accounts.reward_period;
+    require!(!reward_period.closed, DistriAIError::PeriodClosed);
+
+    let ledger = &mut ctx.accounts.reward_ledger;
+    let available = ctx
+        .accounts
+        .reward_pool
+        .amount
+        .checked_sub(ledger.outstanding)
+        .ok_or(DistriAIError::MathOverflow)?;
+    let reserved = if reward_period.total_weight == 0 {
+        0
+    } else {
+        config.period_pool(period).min(available)
+    };
+    reward_period.reserved = reserved;
+    reward_period.closed = true;
+    reward_period.close_time = Clock::get()?.unix_timestamp;
+    ledger.outstanding = ledger.outstanding.saturating_add(reserved);
+    ledger.total_reserved = ledger.total_reserved.saturating_add(reserved);
+
+    emit!(RewardPeriodEvent {
+        period,
+        reserved,
+        claimed: reward_period.claimed,
+        released: 0,
+    });
+    Ok(())
+}
+
+/// Releases the unclaimed rewards of an expired period so that later periods can reserve them.
+pub fn expire_period(ctx: Context<ExpirePeriod>, period: u32) -> Result<()> {
+    let reward_period = &mut ctx.accounts.reward_period;
+    require!(reward_period.closed, DistriAIError::PeriodNotClosed);
+    require!(!reward_period.expired, DistriAIError::ClaimExpired);
+    let now_ts = Clock::get()?.unix_timestamp;
+    require_gte!(now_ts, reward_period.expire_time(), DistriAIError::PeriodNotExpired);
+
+    let released = reward_period.remaining();
+    reward_period.expired = true;
+
+    let ledger = &mut ctx.accounts.reward_ledger;
+    ledger.outstanding = ledger
+        .outstanding
+        .checked_sub(released)
+        .ok_or(DistriAIError::MathOverflow)?;
+    ledger.total_expired = ledger.total_expired.saturating_add(released);
+
+    emit!(RewardPeriodEvent {
+        period,
+        reserved: reward_period.reserved,
+        claimed: reward_period.claimed,
+        released,
+    });
+    Ok(())
+}
+
+/// Processes a reward claim for a specific period.
+///
+/// The share of each machine is computed at claim time from its work weight and the period's
+/// total weight, which are final once the period has been closed, and is paid out of the amount
+/// reserved for that period. While vesting is enabled rewards must be claimed with `claim_vesting`.
+pub fn claim(ctx: Context<Claim>, period: u32) -> Result<()> {
+    ctx.accounts.config.require_active(ProtocolConfig::PAUSE_CLAIM)?;
+
+    require!(
+        VestingConfig::enabled_of(&ctx.accounts.vesting_config)?.is_none(),
+        DistriAIError::VestingRequired
+    );
+    let reward_work = &mut ctx.accounts.reward_work;
+    let amount = settle_share(&mut ctx.accounts.reward_period, reward_work, period)?;
+
+    let ledger = &mut ctx.accounts.reward_ledger;
+    ledger.outstanding = ledger
+        .outstanding
+        .checked_sub(amount)
+        .ok_or(DistriAIError::MathOverflow)?;
+    ledger.total_claimed = ledger.total_claimed.saturating_add(amount);
 
     let machine = &mut ctx.accounts.machine;
-    machine.claimed_periodic_rewards = machine.claimed_periodic_rewards.saturating_add(reward.unit_periodic_reward);
+    machine.claimed_periodic_rewards = machine.claimed_periodic_rewards.saturating_add(amount);
 
     // Transfer token from reward pool to owner
     let mint_key = ctx.accounts.mint.key();
@@ -52,18 +131,182 @@ pub fn claim(ctx: Context<Claim>, period: u32) -> Result<()> {
         },
         signer,
     );
-    transfer_checked(cpi_context, reward.unit_periodic_reward, ctx.accounts.mint.decimals)?;
+    transfer_checked(cpi_context, amount, ctx.accounts.mint.decimals)?;
 
     emit!(RewardEvent {
-        period: reward_machine.period,
-        owner: reward_machine.owner,
-        machine_id: reward_machine.machine_id,
+        period: reward_work.period,
+        owner: reward_work.owner,
+        machine_id: reward_work.machine_id,
+        amount,
     });
     Ok(())
 }
 
+/// Claims the rewards of many machines across many periods with a single token transfer.
+///
+/// The remaining accounts hold, for each entry in order, the writable `RewardPeriod` of the
+/// entry followed by `machines` writable `(Machine, RewardWork)` pairs. Entries must be in
+/// strictly increasing period order, and every account is written back before the next one is
+/// loaded so that a machine passed twice is rejected as a repeat claim.
+pub fn claim_batch<'info>(
+    ctx: Context<'_, '_, 'info, 'info, ClaimBatch<'info>>,
+    entries: Vec<ClaimEntry>,
+) -> Result<()> {
+    ctx.accounts.config.require_active(ProtocolConfig::PAUSE_CLAIM)?;
+
+    require!(
+        VestingConfig::enabled_of(&ctx.accounts.vesting_config)?.is_none(),
+        DistriAIError::VestingRequired
+    );
+    let owner = ctx.accounts.owner.key();
+    let expected = entries
+        .iter()
+        .map(|entry| 1 + 2 * entry.machines as usize)
+        .sum::<usize>();
+    require_eq!(
+        ctx.remaining_accounts.len(),
+        expected,
+        DistriAIError::InvalidBatch
+    );
+
+    let mut accounts = ctx.remaining_accounts.iter();
+    let mut last_period: Option<u32> = None;
+    let mut total: u64 = 0;
+    for entry in &entries {
+        require!(
+            last_period.map_or(true, |last| entry.period > last),
+            DistriAIError::InvalidBatch
+        );
+        last_period = Some(entry.period);
+
+        let period_info = next_writable(&mut accounts)?;
+        let (period_pda, _) = Pubkey::find_program_address(
+            &[b"reward-period", entry.period.to_le_bytes().as_ref()],
+            &crate::ID,
+        );
+        require_keys_eq!(period_info.key(), period_pda, DistriAIError::InvalidBatch);
+        let mut reward_period = Account::<RewardPeriod>::try_from(period_info)?;
+
+        for _ in 0..entry.machines {
+            let machine_info = next_writable(&mut accounts)?;
+            let work_info = next_writable(&mut accounts)?;
+            let mut machine = Account::<Machine>::try_from(machine_info)?;
+            require_keys_eq!(machine.owner, owner, DistriAIError::Unauthorized);
+            let (work_pda, _) = Pubkey::find_program_address(
+                &[
+                    b"reward-work",
+                    entry.period.to_le_bytes().as_ref(),
+                    owner.as_ref(),
+                    machine.uuid.as_ref(),
+                ],
+                &crate::ID,
+            );
+            require_keys_eq!(work_info.key(), work_pda, DistriAIError::InvalidBatch);
+            let mut reward_work = Account::<RewardWork>::try_from(work_info)?;
+
+            let amount = settle_share(&mut reward_period, &mut reward_work, entry.period)?;
+            machine.claimed_periodic_rewards = machine.claimed_periodic_rewards.saturating_add(amount);
+            total = total.saturating_add(amount);
+            reward_work.exit(&crate::ID)?;
+            machine.exit(&crate::ID)?;
+
+            emit!(RewardEvent {
+                period: entry.period,
+                owner,
+                machine_id: reward_work.machine_id,
+                amount,
+            });
+        }
+        reward_period.exit(&crate::ID)?;
+    }
+    require_gt!(total, 0, DistriAIError::NoRewardWork);
+
+    let ledger = &mut ctx.accounts.reward_ledger;
+    ledger.outstanding = ledger
+        .outstanding
+        .checked_sub(total)
+        .ok_or(DistriAIError::MathOverflow)?;
+    ledger.total_claimed = ledger.total_claimed.saturating_add(total);
+
+    // Transfer token from reward pool to owner
+    let mint_key = ctx.accounts.mint.key();
+    let signer: &[&[&[u8]]] = &[&[b"reward-pool", mint_key.as_ref(), &[ctx.bumps.reward_pool]]];
+    let cpi_context = CpiContext::new_with_signer(
+        ctx.accounts.token_program.to_account_info(),
+        TransferChecked {
+            from: ctx.accounts.reward_pool.to_account_info(),
+            mint: ctx.accounts.mint.to_account_info(),
+            to: ctx.accounts.owner_ata.to_account_info(),
+            authority: ctx.accounts.reward_pool.to_account_info(),
+        },
+        signer,
+    );
+    transfer_checked(cpi_context, total, ctx.accounts.mint.decimals)?;
+    Ok(())
+}
+
+fn next_writable<'a, 'info>(
+    accounts: &mut std::slice::Iter<'a, AccountInfo<'info>>,
+) -> Result<&'a AccountInfo<'info>> {
+    let info = accounts.next().ok_or(DistriAIError::InvalidBatch)?;
+    require!(info.is_writable, DistriAIError::InvalidBatch);
+    Ok(info)
+}
+
+/// Marks the work of a machine as claimed and returns its share of the period reserve.
+pub fn settle_share(
+    reward_period: &mut RewardPeriod,
+    reward_work: &mut RewardWork,
+    period: u32,
+) -> Result<u64> {
+    require_eq!(reward_period.period, period, DistriAIError::InvalidPeriod);
+    require!(reward_period.closed, DistriAIError::PeriodNotClosed);
+    require!(!reward_period.expired, DistriAIError::ClaimExpired);
+
+    require!(!reward_work.claimed, DistriAIError::RepeatClaim);
+    let amount = distribution::machine_share(
+        reward_period.reserved,
+        reward_work.weight,
+        reward_period.total_weight,
+    );
+    require_gt!(amount, 0, DistriAIError::NoRewardWork);
+    require_gte!(
+        reward_period.remaining(),
+        amount,
+        DistriAIError::PeriodPoolExhausted
+    );
+    reward_work.claimed = true;
+    reward_period.claimed = reward_period.claimed.saturating_add(amount);
+    Ok(amount)
+}
+
+/// Recomputes the weight of a machine after its work changed and updates the period total.
+pub fn refresh_work_weight(reward_period: &mut RewardPeriod, reward_work: &mut RewardWork) {
+    let weight = distribution::work_weight(
+        reward_work.task_num,
+        reward_work.order_hours,
+        reward_work.score,
+    );
+    if reward_work.weight == 0 && weight > 0 {
+        reward_period.machine_num = reward_period.machine_num.saturating_add(1);
+    } else if reward_work.weight > 0 && weight == 0 {
+        reward_period.machine_num = reward_period.machine_num.saturating_sub(1);
+    }
+    reward_period.total_weight = reward_period
+        .total_weight
+        .saturating_sub(reward_work.weight)
+        .saturating_add(weight);
+    reward_work.weight = weight;
+}
+
 #[derive(Accounts)]
 pub struct RewardPoolDeposit<'info> {
+    #[account(
+        seeds = [b"protocol-config"],
+        bump
+    )]
+    pub config: Box<Account<'info, ProtocolConfig>>,
+
     #[account(mut)]
     pub signer: Signer<'info>,
 
@@ -85,7 +328,7 @@ pub struct RewardPoolDeposit<'info> {
     pub reward_pool: Account<'info, TokenAccount>,
 
     #[account(
-        address = dist_token::ID
+        address = config.mint
     )]
     pub mint: Account<'info, Mint>,
     pub token_program: Program<'info, Token>,
@@ -96,6 +339,12 @@ pub struct RewardPoolDeposit<'info> {
 #[derive(Accounts)]
 #[instruction(period: u32)]
 pub struct Claim<'info> {
+    #[account(
+        seeds = [b"protocol-config"],
+        bump
+    )]
+    pub config: Box<Account<'info, ProtocolConfig>>,
+
     #[account(
         mut,
         has_one = owner
@@ -103,17 +352,81 @@ pub struct Claim<'info> {
     pub machine: Account<'info, Machine>,
 
     #[account(
-        seeds = [b"reward", period.to_le_bytes().as_ref()],
+        mut,
+        seeds = [b"reward-period", period.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub reward_period: Account<'info, RewardPeriod>,
+
+    #[account(
+        mut,
+        seeds = [b"reward-ledger"],
+        bump
+    )]
+    pub reward_ledger: Account<'info, RewardLedger>,
+
+    #[account(
+        mut,
+        seeds = [b"reward-work", period.to_le_bytes().as_ref(), owner.key().as_ref(), machine.uuid.as_ref()],
+        bump
+    )]
+    pub reward_work: Account<'info, RewardWork>,
+
+    /// CHECK: Vesting config, which may not have been created yet.
+    #[account(
+        seeds = [b"vesting-config"],
+        bump
+    )]
+    pub vesting_config: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(
+        init_if_needed,
+        payer = owner,
+        associated_token::mint = mint,
+        associated_token::authority = owner
+    )]
+    pub owner_ata: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [b"reward-pool", mint.key().as_ref()],
         bump
     )]
-    pub reward: Account<'info, Reward>,
+    pub reward_pool: Account<'info, TokenAccount>,
+
+    #[account(
+        address = config.mint
+    )]
+    pub mint: Account<'info, Mint>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimBatch<'info> {
+    #[account(
+        seeds = [b"protocol-config"],
+        bump
+    )]
+    pub config: Box<Account<'info, ProtocolConfig>>,
 
     #[account(
         mut,
-        seeds = [b"reward-machine", period.to_le_bytes().as_ref(), owner.key().as_ref(), machine.uuid.as_ref()],
+        seeds = [b"reward-ledger"],
+        bump
+    )]
+    pub reward_ledger: Account<'info, RewardLedger>,
+
+    /// CHECK: Vesting config, which may not have been created yet.
+    #[account(
+        seeds = [b"vesting-config"],
         bump
     )]
-    pub reward_machine: Account<'info, RewardMachine>,
+    pub vesting_config: UncheckedAccount<'info>,
 
     #[account(mut)]
     pub owner: Signer<'info>,
@@ -134,7 +447,7 @@ pub struct Claim<'info> {
     pub reward_pool: Account<'info, TokenAccount>,
 
     #[account(
-        address = dist_token::ID
+        address = config.mint
     )]
     pub mint: Account<'info, Mint>,
     pub token_program: Program<'info, Token>,
@@ -142,9 +455,83 @@ pub struct Claim<'info> {
     pub system_program: Program<'info, System>,
 }
 
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct ClaimEntry {
+    pub period: u32,
+    pub machines: u8,
+}
+
+#[derive(Accounts)]
+#[instruction(period: u32)]
+pub struct ClosePeriod<'info> {
+    #[account(
+        seeds = [b"protocol-config"],
+        bump
+    )]
+    pub config: Box<Account<'info, ProtocolConfig>>,
+
+    #[account(
+        mut,
+        seeds = [b"reward-period", period.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub reward_period: Account<'info, RewardPeriod>,
+
+    #[account(
+        init_if_needed,
+        seeds = [b"reward-ledger"],
+        bump,
+        payer = signer,
+        space = 8 + RewardLedger::INIT_SPACE
+    )]
+    pub reward_ledger: Account<'info, RewardLedger>,
+
+    #[account(
+        seeds = [b"reward-pool", mint.key().as_ref()],
+        bump
+    )]
+    pub reward_pool: Account<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub signer: Signer<'info>,
+
+    #[account(
+        address = config.mint
+    )]
+    pub mint: Account<'info, Mint>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(period: u32)]
+pub struct ExpirePeriod<'info> {
+    #[account(
+        mut,
+        seeds = [b"reward-period", period.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub reward_period: Account<'info, RewardPeriod>,
+
+    #[account(
+        mut,
+        seeds = [b"reward-ledger"],
+        bump
+    )]
+    pub reward_ledger: Account<'info, RewardLedger>,
+}
+
 #[event]
 pub struct RewardEvent {
     pub period: u32,
     pub owner: Pubkey,
     pub machine_id: [u8; 16],
+    pub amount: u64,
+}
+
+#[event]
+pub struct RewardPeriodEvent {
+    pub period: u32,
+    pub reserved: u64,
+    pub claimed: u64,
+    pub released: u64,
 }
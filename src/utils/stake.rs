@@ -0,0 +1,234 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token::{transfer_checked, Mint, Token, TokenAccount, TransferChecked},
+};
+use crate::state::machine::*;
+use crate::state::protocol_config::*;
+use crate::state::stake::*;
+
+/// Deposits DIST tokens into the stake of a machine.
+pub fn stake_deposit(ctx: Context<StakeDeposit>, amount: u64) -> Result<()> {
+    ctx.accounts.config.require_active(ProtocolConfig::PAUSE_STAKE_DEPOSIT)?;
+
+    let machine = &ctx.accounts.machine;
+    let stake = &mut ctx.accounts.stake;
+    stake.owner = machine.owner;
+    stake.machine_id = machine.uuid;
+    stake.amount = stake.amount.saturating_add(amount);
+
+    // Transfer token from owner to stake vault
+    let cpi_context = CpiContext::new(
+        ctx.accounts.token_program.to_account_info(),
+        TransferChecked {
+            from: ctx.accounts.owner_ata.to_account_info(),
+            mint: ctx.accounts.mint.to_account_info(),
+            to: ctx.accounts.stake_vault.to_account_info(),
+            authority: ctx.accounts.owner.to_account_info(),
+        },
+    );
+    transfer_checked(cpi_context, amount, ctx.accounts.mint.decimals)?;
+
+    emit!(StakeEvent {
+        owner: stake.owner,
+        machine_id: stake.machine_id,
+        amount,
+        total: stake.amount,
+    });
+    Ok(())
+}
+
+/// Withdraws DIST tokens from the stake of a machine once the cooldown has passed.
+pub fn stake_withdraw(ctx: Context<StakeWithdraw>, amount: u64) -> Result<()> {
+    let stake = &mut ctx.accounts.stake;
+    let now_ts = Clock::get()?.unix_timestamp;
+    let for_rent = ctx.accounts.machine.status == MachineStatus::ForRent;
+    stake.amount = stake.remaining_after_withdraw(amount, now_ts, for_rent)?;
+
+    // Transfer token from stake vault to owner
+    let mint_key = ctx.accounts.mint.key();
+    let signer: &[&[&[u8]]] = &[&[b"stake-vault", mint_key.as_ref(), &[ctx.bumps.stake_vault]]];
+    let cpi_context = CpiContext::new_with_signer(
+        ctx.accounts.token_program.to_account_info(),
+        TransferChecked {
+            from: ctx.accounts.stake_vault.to_account_info(),
+            mint: ctx.accounts.mint.to_account_info(),
+            to: ctx.accounts.owner_ata.to_account_info(),
+            authority: ctx.accounts.stake_vault.to_account_info(),
+        },
+        signer,
+    );
+    transfer_checked(cpi_context, amount, ctx.accounts.mint.decimals)?;
+
+    emit!(StakeEvent {
+        owner: stake.owner,
+        machine_id: stake.machine_id,
+        amount,
+        total: stake.amount,
+    });
+    Ok(())
+}
+
+/// Slashes part of a machine stake to the buyer of a failed order of `order_total`, returning the
+/// slashed amount.
+pub fn slash_stake<'info>(
+    stake: &mut MachineStake,
+    order_total: u64,
+    stake_vault: &Account<'info, TokenAccount>,
+    stake_vault_bump: u8,
+    buyer_ata: &Account<'info, TokenAccount>,
+    mint: &Account<'info, Mint>,
+    token_program: &Program<'info, Token>,
+) -> Result<u64> {
+    let amount = stake.slash_amount(order_total);
+    if amount == 0 {
+        return Ok(0);
+    }
+    stake.amount = stake.amount.saturating_sub(amount);
+    stake.slashed = stake.slashed.saturating_add(amount);
+
+    // Transfer token from stake vault to buyer
+    let mint_key = mint.key();
+    let signer: &[&[&[u8]]] = &[&[b"stake-vault", mint_key.as_ref(), &[stake_vault_bump]]];
+    let cpi_context = CpiContext::new_with_signer(
+        token_program.to_account_info(),
+        TransferChecked {
+            from: stake_vault.to_account_info(),
+            mint: mint.to_account_info(),
+            to: buyer_ata.to_account_info(),
+            authority: stake_vault.to_account_info(),
+        },
+        signer,
+    );
+    transfer_checked(cpi_context, amount, mint.decimals)?;
+    Ok(amount)
+}
+
+/// Releases an ended order from the stake of its machine.
+///
+/// Orders placed before provider staking have no stake account, which is left untouched.
+pub fn release_stake(stake: &UncheckedAccount, now_ts: i64) -> Result<()> {
+    let stake_info = stake.to_account_info();
+    if stake_info.data_is_empty() {
+        return Ok(());
+    }
+    let mut stake = Account::<MachineStake>::try_from(&stake_info)?;
+    stake.active_orders = stake.active_orders.saturating_sub(1);
+    stake.last_active_time = now_ts;
+    stake.exit(&crate::ID)
+}
+
+#[derive(Accounts)]
+pub struct StakeDeposit<'info> {
+    #[account(
+        seeds = [b"protocol-config"],
+        bump
+    )]
+    pub config: Box<Account<'info, ProtocolConfig>>,
+
+    #[account(
+        has_one = owner
+    )]
+    pub machine: Box<Account<'info, Machine>>,
+
+    #[account(
+        init_if_needed,
+        seeds = [b"stake", owner.key().as_ref(), machine.uuid.as_ref()],
+        bump,
+        payer = owner,
+        space = 8 + MachineStake::INIT_SPACE
+    )]
+    pub stake: Box<Account<'info, MachineStake>>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = owner
+    )]
+    pub owner_ata: Account<'info, TokenAccount>,
+
+    #[account(
+        init_if_needed,
+        seeds = [b"stake-vault", mint.key().as_ref()],
+        bump,
+        payer = owner,
+        token::mint = mint,
+        token::authority = stake_vault
+    )]
+    pub stake_vault: Account<'info, TokenAccount>,
+
+    #[account(
+        address = config.mint
+    )]
+    pub mint: Account<'info, Mint>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct StakeWithdraw<'info> {
+    #[account(
+        seeds = [b"protocol-config"],
+        bump
+    )]
+    pub config: Box<Account<'info, ProtocolConfig>>,
+
+    #[account(
+        has_one = owner
+    )]
+    pub machine: Box<Account<'info, Machine>>,
+
+    #[account(
+        mut,
+        seeds = [b"stake", owner.key().as_ref(), machine.uuid.as_ref()],
+        bump
+    )]
+    pub stake: Box<Account<'info, MachineStake>>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(
+        init_if_needed,
+        payer = owner,
+        associated_token::mint = mint,
+        associated_token::authority = owner
+    )]
+    pub owner_ata: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [b"stake-vault", mint.key().as_ref()],
+        bump
+    )]
+    pub stake_vault: Account<'info, TokenAccount>,
+
+    #[account(
+        address = config.mint
+    )]
+    pub mint: Account<'info, Mint>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event]
+pub struct StakeEvent {
+    pub owner: Pubkey,
+    pub machine_id: [u8; 16],
+    pub amount: u64,
+    pub total: u64,
+}
+
+#[event]
+pub struct SlashEvent {
+    pub order_id: [u8; 16],
+    pub buyer: Pubkey,
+    pub owner: Pubkey,
+    pub machine_id: [u8; 16],
+    pub amount: u64,
+}
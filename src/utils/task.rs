@@ -1,15 +1,23 @@
 use anchor_lang::prelude::*;
 use crate::errors::DistriAIError;
 use crate::state::machine::*;
+use crate::state::protocol_config::*;
 use crate::state::reward::*;
+use crate::state::reward_period::*;
 use crate::state::task::*;
+use crate::state::verifier::*;
+use crate::utils::reward::refresh_work_weight;
 
 // submit_task is a public function to submit a new task to the system.
+//
+// The task carries a commitment to its result artifact and only counts toward rewards once a
+// registered verifier attests it with `attest_task`.
 pub fn submit_task(
     ctx: Context<SubmitTask>,
     uuid: [u8; 16],
     period: u32,
     metadata: String,
+    result_hash: [u8; 32],
 ) -> Result<()> {
     require_gte!(
         Task::METADATA_MAX_LENGTH,
@@ -23,7 +31,7 @@ pub fn submit_task(
     );
     require_eq!(
         period,
-        Reward::current_period()?,
+        ctx.accounts.config.current_period(Clock::get()?.unix_timestamp)?,
         DistriAIError::InvalidPeriod
     );
 
@@ -34,23 +42,31 @@ pub fn submit_task(
     task.machine_id = machine.uuid;
     task.metadata = metadata;
 
-    let reward_machine = &mut ctx.accounts.reward_machine;
-    reward_machine.period = period;
-    reward_machine.owner = machine.owner;
-    reward_machine.machine_id = machine.uuid;
-    reward_machine.task_num = reward_machine.task_num.saturating_add(1);
+    let task_proof = &mut ctx.accounts.task_proof;
+    task_proof.task = task.key();
+    task_proof.owner = task.owner;
+    task_proof.machine_id = task.machine_id;
+    task_proof.period = period;
+    task_proof.result_hash = result_hash;
+    task_proof.status = TaskProofStatus::Pending;
+    task_proof.submit_time = Clock::get()?.unix_timestamp;
+
+    let reward_work = &mut ctx.accounts.reward_work;
+    reward_work.period = period;
+    reward_work.owner = machine.owner;
+    reward_work.machine_id = machine.uuid;
+    reward_work.score = machine.score;
+
+    let reward_period = &mut ctx.accounts.reward_period;
+    reward_period.period = period;
+    refresh_work_weight(reward_period, reward_work);
 
     let reward = &mut ctx.accounts.reward;
     reward.period = period;
     if reward.start_time == 0 {
-        reward.start_time = Reward::start_time(period);
-        reward.pool = Reward::pool(period);
-    }
-    if reward_machine.task_num == 1 {
-        reward.machine_num = reward.machine_num.saturating_add(1);
+        reward.start_time = ctx.accounts.config.period_start_time(period);
+        reward.pool = ctx.accounts.config.period_pool(period);
     }
-    reward.unit_periodic_reward = reward.pool.saturating_div(reward.machine_num.into());
-    reward.task_num = reward.task_num.saturating_add(1);
 
     emit!(TaskEvent {
         uuid: task.uuid,
@@ -61,9 +77,84 @@ pub fn submit_task(
     Ok(())
 }
 
+// attest_task lets a registered verifier confirm the result of a task, counting it toward rewards.
+// Tasks of past periods can still be attested until `close_period` runs for their period.
+pub fn attest_task(ctx: Context<AttestTask>, result_hash: [u8; 32]) -> Result<()> {
+    let verifier = &mut ctx.accounts.verifier;
+    require!(verifier.active, DistriAIError::Unauthorized);
+
+    let task_proof = &mut ctx.accounts.task_proof;
+    task_proof.attest(
+        verifier.key,
+        result_hash,
+        &ctx.accounts.reward_period,
+        Clock::get()?.unix_timestamp,
+    )?;
+    verifier.attested_count = verifier.attested_count.saturating_add(1);
+
+    let reward_work = &mut ctx.accounts.reward_work;
+    reward_work.task_num = reward_work.task_num.saturating_add(1);
+
+    let reward = &mut ctx.accounts.reward;
+    if reward_work.task_num == 1 {
+        reward.machine_num = reward.machine_num.saturating_add(1);
+    }
+    reward.task_num = reward.task_num.saturating_add(1);
+    refresh_work_weight(&mut ctx.accounts.reward_period, reward_work);
+
+    emit!(TaskAttestEvent {
+        uuid: ctx.accounts.task.uuid,
+        period: task_proof.period,
+        owner: task_proof.owner,
+        machine_id: task_proof.machine_id,
+        verifier: verifier.key,
+        status: task_proof.status.clone(),
+    });
+    Ok(())
+}
+
+// challenge_task lets a registered verifier invalidate a task before its period is closed.
+pub fn challenge_task(ctx: Context<ChallengeTask>) -> Result<()> {
+    let verifier = &mut ctx.accounts.verifier;
+    require!(verifier.active, DistriAIError::Unauthorized);
+
+    let task_proof = &mut ctx.accounts.task_proof;
+    let was_verified =
+        task_proof.challenge(&ctx.accounts.reward_period, Clock::get()?.unix_timestamp)?;
+    verifier.challenged_count = verifier.challenged_count.saturating_add(1);
+
+    if was_verified {
+        let reward_work = &mut ctx.accounts.reward_work;
+        reward_work.task_num = reward_work.task_num.saturating_sub(1);
+
+        let reward = &mut ctx.accounts.reward;
+        if reward_work.task_num == 0 {
+            reward.machine_num = reward.machine_num.saturating_sub(1);
+        }
+        reward.task_num = reward.task_num.saturating_sub(1);
+        refresh_work_weight(&mut ctx.accounts.reward_period, reward_work);
+    }
+
+    emit!(TaskAttestEvent {
+        uuid: ctx.accounts.task.uuid,
+        period: task_proof.period,
+        owner: task_proof.owner,
+        machine_id: task_proof.machine_id,
+        verifier: verifier.key,
+        status: task_proof.status.clone(),
+    });
+    Ok(())
+}
+
 #[derive(Accounts)]
 #[instruction(uuid: [u8; 16], period: u32)]
 pub struct SubmitTask<'info> {
+    #[account(
+        seeds = [b"protocol-config"],
+        bump
+    )]
+    pub config: Box<Account<'info, ProtocolConfig>>,
+
     #[account(
         mut,
         has_one = owner
@@ -79,6 +170,15 @@ pub struct SubmitTask<'info> {
     )]
     pub task: Account<'info, Task>,
 
+    #[account(
+        init,
+        seeds = [b"task-proof", task.key().as_ref()],
+        bump,
+        payer = owner,
+        space = 8 + TaskProof::INIT_SPACE
+    )]
+    pub task_proof: Account<'info, TaskProof>,
+
     #[account(
         init_if_needed,
         seeds = [b"reward", period.to_le_bytes().as_ref()],
@@ -90,12 +190,21 @@ pub struct SubmitTask<'info> {
 
     #[account(
         init_if_needed,
-        seeds = [b"reward-machine", period.to_le_bytes().as_ref(), owner.key().as_ref(), machine.uuid.as_ref()],
+        seeds = [b"reward-period", period.to_le_bytes().as_ref()],
+        bump,
+        payer = owner,
+        space = 8 + RewardPeriod::INIT_SPACE
+    )]
+    pub reward_period: Account<'info, RewardPeriod>,
+
+    #[account(
+        init_if_needed,
+        seeds = [b"reward-work", period.to_le_bytes().as_ref(), owner.key().as_ref(), machine.uuid.as_ref()],
         bump,
         payer = owner,
-        space = 8 + RewardMachine::INIT_SPACE
+        space = 8 + RewardWork::INIT_SPACE
     )]
-    pub reward_machine: Account<'info, RewardMachine>,
+    pub reward_work: Account<'info, RewardWork>,
 
     #[account(mut)]
     pub owner: Signer<'info>,
@@ -103,6 +212,98 @@ pub struct SubmitTask<'info> {
     pub system_program: Program<'info, System>,
 }
 
+#[derive(Accounts)]
+pub struct AttestTask<'info> {
+    #[account(
+        seeds = [b"task", task.owner.as_ref(), task.uuid.as_ref()],
+        bump
+    )]
+    pub task: Account<'info, Task>,
+
+    #[account(
+        mut,
+        seeds = [b"task-proof", task.key().as_ref()],
+        bump
+    )]
+    pub task_proof: Account<'info, TaskProof>,
+
+    #[account(
+        mut,
+        seeds = [b"reward", task.period.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub reward: Account<'info, Reward>,
+
+    #[account(
+        mut,
+        seeds = [b"reward-period", task.period.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub reward_period: Account<'info, RewardPeriod>,
+
+    #[account(
+        mut,
+        seeds = [b"reward-work", task.period.to_le_bytes().as_ref(), task.owner.as_ref(), task.machine_id.as_ref()],
+        bump
+    )]
+    pub reward_work: Account<'info, RewardWork>,
+
+    #[account(
+        mut,
+        seeds = [b"verifier", signer.key().as_ref()],
+        bump
+    )]
+    pub verifier: Account<'info, Verifier>,
+
+    pub signer: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ChallengeTask<'info> {
+    #[account(
+        seeds = [b"task", task.owner.as_ref(), task.uuid.as_ref()],
+        bump
+    )]
+    pub task: Account<'info, Task>,
+
+    #[account(
+        mut,
+        seeds = [b"task-proof", task.key().as_ref()],
+        bump
+    )]
+    pub task_proof: Account<'info, TaskProof>,
+
+    #[account(
+        mut,
+        seeds = [b"reward", task.period.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub reward: Account<'info, Reward>,
+
+    #[account(
+        mut,
+        seeds = [b"reward-period", task.period.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub reward_period: Account<'info, RewardPeriod>,
+
+    #[account(
+        mut,
+        seeds = [b"reward-work", task.period.to_le_bytes().as_ref(), task.owner.as_ref(), task.machine_id.as_ref()],
+        bump
+    )]
+    pub reward_work: Account<'info, RewardWork>,
+
+    #[account(
+        mut,
+        seeds = [b"verifier", signer.key().as_ref()],
+        bump
+    )]
+    pub verifier: Account<'info, Verifier>,
+
+    pub signer: Signer<'info>,
+}
+
 #[event]
 pub struct TaskEvent {
     pub uuid: [u8; 16],
@@ -110,3 +311,13 @@ pub struct TaskEvent {
     pub owner: Pubkey,
     pub machine_id: [u8; 16],
 }
+
+#[event]
+pub struct TaskAttestEvent {
+    pub uuid: [u8; 16],
+    pub period: u32,
+    pub owner: Pubkey,
+    pub machine_id: [u8; 16],
+    pub verifier: Pubkey,
+    pub status: TaskProofStatus,
+}
@@ -0,0 +1,84 @@
+use anchor_lang::prelude::*;
+use crate::errors::DistriAIError;
+use crate::state::protocol_config::*;
+use crate::state::verifier::*;
+
+/// Registers a key that may attest and challenge task results.
+pub fn register_verifier(ctx: Context<RegisterVerifier>, key: Pubkey) -> Result<()> {
+    let verifier = &mut ctx.accounts.verifier;
+    verifier.key = key;
+    verifier.active = true;
+    verifier.register_time = Clock::get()?.unix_timestamp;
+
+    emit!(VerifierEvent {
+        key,
+        active: verifier.active,
+    });
+    Ok(())
+}
+
+/// Enables or disables a registered verifier.
+pub fn set_verifier_active(ctx: Context<SetVerifierActive>, active: bool) -> Result<()> {
+    let verifier = &mut ctx.accounts.verifier;
+    verifier.active = active;
+
+    emit!(VerifierEvent {
+        key: verifier.key,
+        active,
+    });
+    Ok(())
+}
+
+#[derive(Accounts)]
+#[instruction(key: Pubkey)]
+pub struct RegisterVerifier<'info> {
+    #[account(
+        seeds = [b"protocol-config"],
+        bump
+    )]
+    pub config: Box<Account<'info, ProtocolConfig>>,
+
+    #[account(
+        init,
+        seeds = [b"verifier", key.as_ref()],
+        bump,
+        payer = admin,
+        space = 8 + Verifier::INIT_SPACE
+    )]
+    pub verifier: Account<'info, Verifier>,
+
+    #[account(
+        mut,
+        address = config.authority @ DistriAIError::Unauthorized
+    )]
+    pub admin: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetVerifierActive<'info> {
+    #[account(
+        seeds = [b"protocol-config"],
+        bump
+    )]
+    pub config: Box<Account<'info, ProtocolConfig>>,
+
+    #[account(
+        mut,
+        seeds = [b"verifier", verifier.key.as_ref()],
+        bump
+    )]
+    pub verifier: Account<'info, Verifier>,
+
+    #[account(
+        address = config.authority @ DistriAIError::Unauthorized
+    )]
+    pub admin: Signer<'info>,
+}
+
+#[event]
+pub struct VerifierEvent {
+    pub key: Pubkey,
+    pub active: bool,
+}
@@ -0,0 +1,312 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token::{transfer_checked, Mint, Token, TokenAccount, TransferChecked},
+};
+use crate::errors::DistriAIError;
+use crate::state::machine::*;
+use crate::state::machine_history::*;
+use crate::state::protocol_config::*;
+use crate::state::reward_period::*;
+use crate::state::vesting::*;
+use crate::utils::reward::settle_share;
+
+/// Sets the vesting terms applied to periodic rewards claimed from now on.
+pub fn set_vesting_config(
+    ctx: Context<SetVestingConfig>,
+    enabled: bool,
+    cliff: i64,
+    duration: i64,
+    accelerate_threshold: u32,
+) -> Result<()> {
+    let config = &mut ctx.accounts.vesting_config;
+    config.enabled = enabled;
+    config.cliff = cliff;
+    config.duration = duration;
+    config.accelerate_threshold = accelerate_threshold;
+    config.validate()?;
+
+    emit!(VestingConfigEvent {
+        enabled,
+        cliff,
+        duration,
+        accelerate_threshold,
+    });
+    Ok(())
+}
+
+/// Claims the reward of a period into a vesting schedule instead of paying it out.
+///
+/// The tokens stay in the reward pool and remain counted as outstanding in the reward ledger
+/// until they are withdrawn.
+pub fn claim_vesting(ctx: Context<ClaimVesting>, period: u32) -> Result<()> {
+    ctx.accounts.config.require_active(ProtocolConfig::PAUSE_CLAIM)?;
+
+    let config = VestingConfig::enabled_of(&ctx.accounts.vesting_config)?
+        .ok_or(DistriAIError::VestingDisabled)?;
+    let reward_work = &mut ctx.accounts.reward_work;
+    let amount = settle_share(&mut ctx.accounts.reward_period, reward_work, period)?;
+
+    let machine = &mut ctx.accounts.machine;
+    machine.claimed_periodic_rewards = machine.claimed_periodic_rewards.saturating_add(amount);
+
+    let schedule = &mut ctx.accounts.vesting_schedule;
+    schedule.owner = reward_work.owner;
+    schedule.machine_id = reward_work.machine_id;
+    schedule.period = period;
+    schedule.total = amount;
+    schedule.start_time = Clock::get()?.unix_timestamp;
+    schedule.cliff = config.cliff;
+    schedule.duration = config.duration;
+
+    emit!(VestingEvent {
+        period,
+        owner: schedule.owner,
+        machine_id: schedule.machine_id,
+        total: schedule.total,
+        withdrawn: schedule.withdrawn,
+        accelerated: schedule.accelerated,
+    });
+    Ok(())
+}
+
+/// Withdraws the vested part of a schedule that has not been withdrawn yet.
+pub fn withdraw_vested(ctx: Context<WithdrawVested>) -> Result<()> {
+    ctx.accounts.config.require_active(ProtocolConfig::PAUSE_CLAIM)?;
+
+    let schedule = &mut ctx.accounts.vesting_schedule;
+    let amount = schedule.withdraw(Clock::get()?.unix_timestamp)?;
+
+    let ledger = &mut ctx.accounts.reward_ledger;
+    ledger.outstanding = ledger
+        .outstanding
+        .checked_sub(amount)
+        .ok_or(DistriAIError::MathOverflow)?;
+    ledger.total_claimed = ledger.total_claimed.saturating_add(amount);
+
+    // Transfer token from reward pool to owner
+    let mint_key = ctx.accounts.mint.key();
+    let signer: &[&[&[u8]]] = &[&[b"reward-pool", mint_key.as_ref(), &[ctx.bumps.reward_pool]]];
+    let cpi_context = CpiContext::new_with_signer(
+        ctx.accounts.token_program.to_account_info(),
+        TransferChecked {
+            from: ctx.accounts.reward_pool.to_account_info(),
+            mint: ctx.accounts.mint.to_account_info(),
+            to: ctx.accounts.owner_ata.to_account_info(),
+            authority: ctx.accounts.reward_pool.to_account_info(),
+        },
+        signer,
+    );
+    transfer_checked(cpi_context, amount, ctx.accounts.mint.decimals)?;
+
+    emit!(VestingEvent {
+        period: schedule.period,
+        owner: schedule.owner,
+        machine_id: schedule.machine_id,
+        total: schedule.total,
+        withdrawn: schedule.withdrawn,
+        accelerated: schedule.accelerated,
+    });
+    Ok(())
+}
+
+/// Vests a schedule in full when its machine has settled at least `accelerate_threshold` order
+/// hours over its lifetime. Orders rented to the machine owner do not count, see `MachineHistory`.
+pub fn accelerate_vesting(ctx: Context<AccelerateVesting>) -> Result<()> {
+    let config = VestingConfig::enabled_of(&ctx.accounts.vesting_config)?
+        .ok_or(DistriAIError::VestingDisabled)?;
+    require!(
+        config.can_accelerate(ctx.accounts.machine_history.settled_hours),
+        DistriAIError::AccelerationNotEligible
+    );
+
+    let schedule = &mut ctx.accounts.vesting_schedule;
+    require!(!schedule.accelerated, DistriAIError::IncorrectStatus);
+    schedule.accelerated = true;
+
+    emit!(VestingEvent {
+        period: schedule.period,
+        owner: schedule.owner,
+        machine_id: schedule.machine_id,
+        total: schedule.total,
+        withdrawn: schedule.withdrawn,
+        accelerated: schedule.accelerated,
+    });
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct SetVestingConfig<'info> {
+    #[account(
+        seeds = [b"protocol-config"],
+        bump
+    )]
+    pub config: Box<Account<'info, ProtocolConfig>>,
+
+    #[account(
+        init_if_needed,
+        seeds = [b"vesting-config"],
+        bump,
+        payer = admin,
+        space = 8 + VestingConfig::INIT_SPACE
+    )]
+    pub vesting_config: Account<'info, VestingConfig>,
+
+    #[account(
+        mut,
+        address = config.authority @ DistriAIError::Unauthorized
+    )]
+    pub admin: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(period: u32)]
+pub struct ClaimVesting<'info> {
+    #[account(
+        seeds = [b"protocol-config"],
+        bump
+    )]
+    pub config: Box<Account<'info, ProtocolConfig>>,
+
+    #[account(
+        mut,
+        has_one = owner
+    )]
+    pub machine: Account<'info, Machine>,
+
+    #[account(
+        mut,
+        seeds = [b"reward-period", period.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub reward_period: Account<'info, RewardPeriod>,
+
+    #[account(
+        mut,
+        seeds = [b"reward-work", period.to_le_bytes().as_ref(), owner.key().as_ref(), machine.uuid.as_ref()],
+        bump
+    )]
+    pub reward_work: Account<'info, RewardWork>,
+
+    /// CHECK: Vesting config, which may not have been created yet.
+    #[account(
+        seeds = [b"vesting-config"],
+        bump
+    )]
+    pub vesting_config: UncheckedAccount<'info>,
+
+    #[account(
+        init,
+        seeds = [b"vesting", period.to_le_bytes().as_ref(), owner.key().as_ref(), machine.uuid.as_ref()],
+        bump,
+        payer = owner,
+        space = 8 + VestingSchedule::INIT_SPACE
+    )]
+    pub vesting_schedule: Account<'info, VestingSchedule>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawVested<'info> {
+    #[account(
+        seeds = [b"protocol-config"],
+        bump
+    )]
+    pub config: Box<Account<'info, ProtocolConfig>>,
+
+    #[account(
+        mut,
+        has_one = owner,
+        seeds = [b"vesting", vesting_schedule.period.to_le_bytes().as_ref(), owner.key().as_ref(), vesting_schedule.machine_id.as_ref()],
+        bump
+    )]
+    pub vesting_schedule: Account<'info, VestingSchedule>,
+
+    #[account(
+        mut,
+        seeds = [b"reward-ledger"],
+        bump
+    )]
+    pub reward_ledger: Account<'info, RewardLedger>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(
+        init_if_needed,
+        payer = owner,
+        associated_token::mint = mint,
+        associated_token::authority = owner
+    )]
+    pub owner_ata: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [b"reward-pool", mint.key().as_ref()],
+        bump
+    )]
+    pub reward_pool: Account<'info, TokenAccount>,
+
+    #[account(
+        address = config.mint
+    )]
+    pub mint: Account<'info, Mint>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct AccelerateVesting<'info> {
+    #[account(
+        has_one = owner
+    )]
+    pub machine: Account<'info, Machine>,
+
+    #[account(
+        mut,
+        has_one = owner,
+        seeds = [b"vesting", vesting_schedule.period.to_le_bytes().as_ref(), owner.key().as_ref(), machine.uuid.as_ref()],
+        bump
+    )]
+    pub vesting_schedule: Account<'info, VestingSchedule>,
+
+    #[account(
+        seeds = [b"machine-history", machine.key().as_ref()],
+        bump
+    )]
+    pub machine_history: Account<'info, MachineHistory>,
+
+    /// CHECK: Vesting config, which may not have been created yet.
+    #[account(
+        seeds = [b"vesting-config"],
+        bump
+    )]
+    pub vesting_config: UncheckedAccount<'info>,
+
+    pub owner: Signer<'info>,
+}
+
+#[event]
+pub struct VestingConfigEvent {
+    pub enabled: bool,
+    pub cliff: i64,
+    pub duration: i64,
+    pub accelerate_threshold: u32,
+}
+
+#[event]
+pub struct VestingEvent {
+    pub period: u32,
+    pub owner: Pubkey,
+    pub machine_id: [u8; 16],
+    pub total: u64,
+    pub withdrawn: u64,
+    pub accelerated: bool,
+}
@@ -0,0 +1,95 @@
+use anchor_lang::AnchorDeserialize;
+use lyzerai_core::client::claim::{claim_batch_instruction, pack_claims, transaction_size, ClaimTarget};
+use lyzerai_core::utils::reward::ClaimEntry;
+use solana_sdk::packet::PACKET_DATA_SIZE;
+use solana_sdk::pubkey::Pubkey;
+
+const FIXED_ACCOUNTS: usize = 10;
+
+fn targets(periods: u32, machines: usize) -> Vec<ClaimTarget> {
+    let machines: Vec<(Pubkey, [u8; 16])> = (0..machines)
+        .map(|i| (Pubkey::new_unique(), [i as u8; 16]))
+        .collect();
+    (0..periods)
+        .flat_map(|period| {
+            machines.iter().map(move |(machine, machine_id)| ClaimTarget {
+                period,
+                machine: *machine,
+                machine_id: *machine_id,
+            })
+        })
+        .collect()
+}
+
+fn packed_pairs(instructions: &[solana_sdk::instruction::Instruction]) -> usize {
+    instructions
+        .iter()
+        .map(|instruction| {
+            // Each entry adds one period account; every pair adds two accounts.
+            let data = &instruction.data[8..];
+            let entries = u32::from_le_bytes(data[..4].try_into().unwrap()) as usize;
+            (instruction.accounts.len() - FIXED_ACCOUNTS - entries) / 2
+        })
+        .sum()
+}
+
+#[test]
+fn test_every_batch_fits_in_a_transaction() {
+    let owner = Pubkey::new_unique();
+    let mint = Pubkey::new_unique();
+    let instructions = pack_claims(&owner, &mint, &targets(8, 30));
+
+    assert!(instructions.len() > 1);
+    for instruction in &instructions {
+        assert!(transaction_size(&owner, instruction) <= PACKET_DATA_SIZE);
+    }
+}
+
+#[test]
+fn test_every_target_is_packed_once() {
+    let owner = Pubkey::new_unique();
+    let mint = Pubkey::new_unique();
+    let mut all = targets(5, 12);
+    all.extend(all.clone());
+
+    let instructions = pack_claims(&owner, &mint, &all);
+    assert_eq!(packed_pairs(&instructions), 5 * 12);
+}
+
+#[test]
+fn test_small_claim_uses_one_transaction() {
+    let owner = Pubkey::new_unique();
+    let mint = Pubkey::new_unique();
+    let instructions = pack_claims(&owner, &mint, &targets(2, 3));
+
+    assert_eq!(instructions.len(), 1);
+    assert_eq!(packed_pairs(&instructions), 6);
+}
+
+#[test]
+fn test_no_targets_produce_no_instructions() {
+    let owner = Pubkey::new_unique();
+    let mint = Pubkey::new_unique();
+    assert!(pack_claims(&owner, &mint, &[]).is_empty());
+}
+
+#[test]
+fn test_packed_periods_strictly_increase() {
+    let owner = Pubkey::new_unique();
+    let mint = Pubkey::new_unique();
+    let instructions = pack_claims(&owner, &mint, &targets(3, 300));
+
+    for instruction in &instructions {
+        let entries = Vec::<ClaimEntry>::deserialize(&mut &instruction.data[8..]).unwrap();
+        assert!(entries.windows(2).all(|pair| pair[0].period < pair[1].period));
+    }
+    assert_eq!(packed_pairs(&instructions), 3 * 300);
+}
+
+#[test]
+#[should_panic]
+fn test_instruction_refuses_more_machines_than_an_entry_holds() {
+    let owner = Pubkey::new_unique();
+    let mint = Pubkey::new_unique();
+    claim_batch_instruction(&owner, &mint, &targets(1, 256));
+}
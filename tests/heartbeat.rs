@@ -0,0 +1,86 @@
+use lyzerai_core::state::heartbeat::MachineHeartbeat;
+use lyzerai_core::state::protocol_config::ProtocolParams;
+use proptest::prelude::*;
+use solana_sdk::pubkey::Pubkey;
+
+const START: i64 = 1_700_000_040;
+const INTERVAL: i64 = 60;
+const MAX_MISSED: i64 = 5;
+
+fn params() -> ProtocolParams {
+    ProtocolParams {
+        order_cancel_window: 300,
+        duration_unit: 3_600,
+        reward_genesis_time: START,
+        reward_period_duration: 86_400,
+        reward_initial_pool: 1_000_000,
+        reward_halving_periods: 0,
+        heartbeat_interval: INTERVAL,
+        heartbeat_max_missed: MAX_MISSED,
+    }
+}
+
+fn heartbeat() -> MachineHeartbeat {
+    MachineHeartbeat {
+        machine: Pubkey::default(),
+        first_seen: 0,
+        last_seen: 0,
+        beats: 0,
+    }
+}
+
+#[test]
+fn goes_offline_after_missed_heartbeats() {
+    let mut heartbeat = heartbeat();
+    assert!(!heartbeat.is_online(START, &params()));
+
+    heartbeat.record(Pubkey::new_unique(), START, &params());
+    let limit = START + INTERVAL * MAX_MISSED;
+    assert!(heartbeat.is_online(limit, &params()));
+    assert!(!heartbeat.is_online(limit + 1, &params()));
+}
+
+#[test]
+fn offline_limit_follows_the_configured_interval() {
+    let mut heartbeat = heartbeat();
+    heartbeat.record(Pubkey::new_unique(), START, &params());
+    let slower = ProtocolParams {
+        heartbeat_interval: 2 * INTERVAL,
+        ..params()
+    };
+    let limit = START + INTERVAL * MAX_MISSED;
+    assert!(!heartbeat.is_online(limit + 1, &params()));
+    assert!(heartbeat.is_online(limit + 1, &slower));
+}
+
+#[test]
+fn counts_one_heartbeat_per_interval() {
+    let mut heartbeat = heartbeat();
+    assert!(heartbeat.record(Pubkey::new_unique(), START, &params()));
+    assert!(!heartbeat.record(Pubkey::new_unique(), START + 1, &params()));
+    assert!(heartbeat.record(Pubkey::new_unique(), START + INTERVAL, &params()));
+    assert_eq!(heartbeat.beats, 2);
+    assert_eq!(heartbeat.uptime_bps(START + INTERVAL, &params()), 10_000);
+}
+
+#[test]
+fn uptime_drops_with_missed_intervals() {
+    let mut heartbeat = heartbeat();
+    heartbeat.record(Pubkey::new_unique(), START, &params());
+    heartbeat.record(Pubkey::new_unique(), START + 3 * INTERVAL, &params());
+    assert_eq!(heartbeat.uptime_bps(START + 3 * INTERVAL, &params()), 5_000);
+}
+
+proptest! {
+    #[test]
+    fn test_uptime_never_exceeds_full(offsets in prop::collection::vec(0i64..600, 1..50)) {
+        let mut heartbeat = heartbeat();
+        let mut now = START;
+        for offset in offsets {
+            now += offset;
+            heartbeat.record(Pubkey::new_unique(), now, &params());
+            prop_assert!(heartbeat.uptime_bps(now, &params()) <= 10_000);
+            prop_assert!(heartbeat.beats <= heartbeat.expected_beats(now, &params()));
+        }
+    }
+}
@@ -0,0 +1,91 @@
+use lyzerai_core::ai::ai_model::ModelVersion;
+use lyzerai_core::errors::DistriAIError;
+use lyzerai_core::state::job::{JobBinding, JobKind};
+use solana_sdk::pubkey::Pubkey;
+
+const BIND_TIME: i64 = 1_700_000_000;
+
+fn binding(ai_model: Pubkey) -> JobBinding {
+    JobBinding {
+        ai_model,
+        model_version: Pubkey::new_unique(),
+        job: Pubkey::new_unique(),
+        kind: JobKind::Order,
+        requester: Pubkey::new_unique(),
+        machine_id: [1; 16],
+        output_version: Pubkey::default(),
+        bind_time: BIND_TIME,
+        complete_time: 0,
+    }
+}
+
+fn version(ai_model: Pubkey, create_time: i64) -> ModelVersion {
+    ModelVersion {
+        ai_model,
+        major: 1,
+        minor: 0,
+        patch: 0,
+        content_hash: [7; 32],
+        uri: "https://models.example/weights.bin".to_string(),
+        size: 13,
+        changelog: String::new(),
+        create_time,
+    }
+}
+
+#[test]
+fn test_complete_records_output_version_of_bound_model() {
+    let ai_model = Pubkey::new_unique();
+    let mut job_binding = binding(ai_model);
+    let address = Pubkey::new_unique();
+    let output = version(ai_model, BIND_TIME + 60);
+
+    job_binding.complete(Some((address, &output)), BIND_TIME + 120).unwrap();
+    assert_eq!(job_binding.output_version, address);
+    assert_eq!(job_binding.complete_time, BIND_TIME + 120);
+}
+
+#[test]
+fn test_complete_without_output_version() {
+    let mut job_binding = binding(Pubkey::new_unique());
+    job_binding.complete(None, BIND_TIME + 120).unwrap();
+    assert_eq!(job_binding.output_version, Pubkey::default());
+    assert_eq!(job_binding.complete_time, BIND_TIME + 120);
+}
+
+#[test]
+fn test_complete_rejects_version_of_another_model() {
+    let mut job_binding = binding(Pubkey::new_unique());
+    let output = version(Pubkey::new_unique(), BIND_TIME + 60);
+
+    let err = job_binding
+        .complete(Some((Pubkey::new_unique(), &output)), BIND_TIME + 120)
+        .unwrap_err();
+    assert_eq!(err, DistriAIError::InvalidVersion.into());
+    assert_eq!(job_binding.output_version, Pubkey::default());
+    assert_eq!(job_binding.complete_time, 0);
+}
+
+#[test]
+fn test_complete_rejects_version_published_before_binding() {
+    let ai_model = Pubkey::new_unique();
+    let mut job_binding = binding(ai_model);
+    let orphan = version(ai_model, BIND_TIME - 1);
+
+    let err = job_binding
+        .complete(Some((Pubkey::new_unique(), &orphan)), BIND_TIME + 120)
+        .unwrap_err();
+    assert_eq!(err, DistriAIError::InvalidVersion.into());
+}
+
+#[test]
+fn test_model_version_must_postdate_the_model() {
+    let ai_model = Pubkey::new_unique();
+    let model_create_time = BIND_TIME - 3_600;
+    let current = version(ai_model, model_create_time);
+    assert!(JobBinding::check_version(&ai_model, model_create_time, &current).is_ok());
+
+    let orphan = version(ai_model, model_create_time - 1);
+    let err = JobBinding::check_version(&ai_model, model_create_time, &orphan).unwrap_err();
+    assert_eq!(err, DistriAIError::InvalidVersion.into());
+}
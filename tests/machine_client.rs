@@ -0,0 +1,69 @@
+use anchor_lang::AnchorDeserialize;
+use lyzerai_core::client::machine::{
+    add_machine_instruction, heartbeat_pda, machine_pda, machine_price_pda, machine_spec_pda,
+    make_offer_instruction, remove_machine_instruction, stake_pda,
+};
+use lyzerai_core::client::queue::queue_pda;
+use solana_sdk::hash::hash;
+use solana_sdk::pubkey::Pubkey;
+
+#[test]
+fn add_machine_encodes_uuid_and_metadata() {
+    let owner = Pubkey::new_unique();
+    let uuid = [7; 16];
+    let instruction = add_machine_instruction(&owner, uuid, "{\"gpu\":\"A100\"}".to_string());
+
+    assert_eq!(&instruction.data[..8], &hash(b"global:add_machine").to_bytes()[..8]);
+    let (decoded_uuid, metadata) =
+        <([u8; 16], String)>::try_from_slice(&instruction.data[8..]).unwrap();
+    assert_eq!(decoded_uuid, uuid);
+    assert_eq!(metadata, "{\"gpu\":\"A100\"}");
+
+    assert_eq!(instruction.accounts[0].pubkey, machine_pda(&owner, &uuid));
+    assert!(instruction.accounts[0].is_writable);
+    assert_eq!(instruction.accounts[1].pubkey, owner);
+    assert!(instruction.accounts[1].is_signer);
+}
+
+#[test]
+fn make_offer_passes_stake_of_the_machine() {
+    let owner = Pubkey::new_unique();
+    let uuid = [1; 16];
+    let instruction = make_offer_instruction(&owner, &uuid, 500, 24, 100);
+
+    let (price, max_duration, disk) =
+        <(u64, u32, u32)>::try_from_slice(&instruction.data[8..]).unwrap();
+    assert_eq!((price, max_duration, disk), (500, 24, 100));
+    assert_eq!(instruction.accounts[1].pubkey, stake_pda(&owner, &uuid));
+    assert!(!instruction.accounts[1].is_writable);
+}
+
+#[test]
+fn remove_machine_returns_rent_to_owner() {
+    let owner = Pubkey::new_unique();
+    let uuid = [2; 16];
+    let instruction = remove_machine_instruction(&owner, &uuid, &[]);
+
+    assert_eq!(instruction.data, hash(b"global:remove_machine").to_bytes()[..8].to_vec());
+    let owner_meta = instruction.accounts.last().unwrap();
+    assert_eq!(owner_meta.pubkey, owner);
+    assert!(owner_meta.is_signer && owner_meta.is_writable);
+}
+
+#[test]
+fn remove_machine_closes_spec_heartbeat_and_prices_and_checks_queue() {
+    let owner = Pubkey::new_unique();
+    let uuid = [2; 16];
+    let mint = Pubkey::new_unique();
+    let machine = machine_pda(&owner, &uuid);
+    let instruction = remove_machine_instruction(&owner, &uuid, &[mint]);
+
+    assert_eq!(instruction.accounts[2].pubkey, machine_spec_pda(&machine));
+    assert_eq!(instruction.accounts[3].pubkey, heartbeat_pda(&machine));
+    assert_eq!(instruction.accounts[4].pubkey, queue_pda(&machine));
+    assert!(!instruction.accounts[4].is_writable);
+    assert_eq!(instruction.accounts[5].pubkey, owner);
+    let price_meta = &instruction.accounts[6];
+    assert_eq!(price_meta.pubkey, machine_price_pda(&machine, &mint));
+    assert!(price_meta.is_writable && !price_meta.is_signer);
+}
@@ -0,0 +1,171 @@
+use lyzerai_core::services::machine_index::{
+    HeartbeatRecord, MachineIndex, MachineListing, MachineQuery, MachineSort, SpecRecord,
+};
+use lyzerai_core::state::protocol_config::ProtocolParams;
+
+const NOW: i64 = 1_700_000_000;
+
+fn listing(address: &str, price: u64, score: u8, spec: Option<SpecRecord>) -> MachineListing {
+    MachineListing {
+        address: address.to_string(),
+        owner: "owner".to_string(),
+        uuid: address.to_string(),
+        status: "ForRent".to_string(),
+        price,
+        max_duration: 24,
+        disk: 100,
+        score,
+        completed_count: 0,
+        failed_count: 0,
+        spec,
+        heartbeat: None,
+        online: false,
+        uptime_bps: 0,
+    }
+}
+
+fn spec(gpu_model: &str, vram_gb: u32, region: &str) -> SpecRecord {
+    SpecRecord {
+        gpu_model: gpu_model.to_string(),
+        gpu_count: 1,
+        vram_gb,
+        cpu_cores: 16,
+        ram_gb: 64,
+        disk_gb: 500,
+        region: region.to_string(),
+    }
+}
+
+fn params() -> ProtocolParams {
+    ProtocolParams {
+        order_cancel_window: 300,
+        duration_unit: 3_600,
+        reward_genesis_time: NOW,
+        reward_period_duration: 86_400,
+        reward_initial_pool: 1_000_000,
+        reward_halving_periods: 0,
+        heartbeat_interval: 60,
+        heartbeat_max_missed: 5,
+    }
+}
+
+fn index() -> MachineIndex {
+    let index = MachineIndex::default();
+    index.set_params(params());
+    index.insert(listing("a", 300, 90, Some(spec("NVIDIA A100", 80, "us-east"))));
+    index.insert(listing("b", 100, 70, Some(spec("NVIDIA RTX 4090", 24, "eu-west"))));
+    index.insert(listing("c", 200, 80, Some(spec("NVIDIA A100", 40, "eu-west"))));
+    index.insert(listing("d", 50, 95, None));
+    index
+}
+
+fn addresses(index: &MachineIndex, query: &MachineQuery) -> Vec<String> {
+    index
+        .search(query, NOW)
+        .machines
+        .into_iter()
+        .map(|listing| listing.address)
+        .collect()
+}
+
+#[test]
+fn filters_by_hardware_spec() {
+    let index = index();
+    let query = MachineQuery {
+        gpu: Some("a100".to_string()),
+        min_vram_gb: Some(48),
+        ..MachineQuery::default()
+    };
+    assert_eq!(addresses(&index, &query), vec!["a"]);
+
+    let query = MachineQuery {
+        region: Some("EU-WEST".to_string()),
+        ..MachineQuery::default()
+    };
+    assert_eq!(addresses(&index, &query), vec!["b", "c"]);
+}
+
+#[test]
+fn machines_without_spec_only_match_without_hardware_filters() {
+    let index = index();
+    assert_eq!(addresses(&index, &MachineQuery::default()).len(), 4);
+
+    let query = MachineQuery {
+        min_cpu_cores: Some(1),
+        ..MachineQuery::default()
+    };
+    assert!(!addresses(&index, &query).contains(&"d".to_string()));
+}
+
+#[test]
+fn sorts_by_price_and_score() {
+    let index = index();
+    let query = MachineQuery {
+        sort: Some(MachineSort::PriceAsc),
+        ..MachineQuery::default()
+    };
+    assert_eq!(addresses(&index, &query), vec!["d", "b", "c", "a"]);
+
+    let query = MachineQuery {
+        sort: Some(MachineSort::ScoreDesc),
+        max_price: Some(250),
+        ..MachineQuery::default()
+    };
+    assert_eq!(addresses(&index, &query), vec!["d", "c", "b"]);
+}
+
+#[test]
+fn paginates_results() {
+    let index = index();
+    let query = MachineQuery {
+        sort: Some(MachineSort::PriceDesc),
+        page: Some(2),
+        page_size: Some(3),
+        ..MachineQuery::default()
+    };
+    let page = index.search(&query, NOW);
+    assert_eq!(page.total, 4);
+    assert_eq!(page.page, 2);
+    assert_eq!(page.machines.len(), 1);
+    assert_eq!(page.machines[0].address, "d");
+
+    let query = MachineQuery {
+        page_size: Some(10_000),
+        ..MachineQuery::default()
+    };
+    assert_eq!(index.search(&query, NOW).page_size, MachineQuery::MAX_PAGE_SIZE);
+}
+
+#[test]
+fn filters_by_liveness() {
+    let index = index();
+    let mut alive = listing("e", 100, 50, None);
+    alive.heartbeat = Some(HeartbeatRecord {
+        first_seen: NOW - 600,
+        last_seen: NOW - 30,
+        beats: 11,
+    });
+    index.insert(alive);
+    let mut stale = listing("f", 100, 50, None);
+    stale.heartbeat = Some(HeartbeatRecord {
+        first_seen: NOW - 6_000,
+        last_seen: NOW - 3_000,
+        beats: 50,
+    });
+    index.insert(stale);
+
+    let query = MachineQuery {
+        online: Some(true),
+        ..MachineQuery::default()
+    };
+    let page = index.search(&query, NOW);
+    assert_eq!(page.machines.len(), 1);
+    assert_eq!(page.machines[0].address, "e");
+    assert_eq!(page.machines[0].uptime_bps, 10_000);
+
+    let query = MachineQuery {
+        min_uptime_bps: Some(5_000),
+        ..MachineQuery::default()
+    };
+    assert_eq!(addresses(&index, &query), vec!["e"]);
+}
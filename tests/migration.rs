@@ -0,0 +1,248 @@
+use lyzerai_core::{migrate_account, status_table};
+use solana_sdk::pubkey::Pubkey;
+
+// Mirrors of the on-chain account layouts. The `migrate_account!` calls below repeat the field
+// lists of `migration::machine` and `migration::order` by hand, so they check the macro itself
+// rather than the program's migrations.
+
+#[allow(dead_code)]
+#[derive(Clone, Debug, PartialEq)]
+enum MachineStatus {
+    Idle,
+    ForRent,
+    Renting,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct Machine {
+    owner: Pubkey,
+    uuid: [u8; 16],
+    metadata: String,
+    status: MachineStatus,
+    price: u64,
+    max_duration: u32,
+    disk: u32,
+    completed_count: u32,
+    failed_count: u32,
+    score: u8,
+    claimed_periodic_rewards: u64,
+    claimed_task_rewards: u64,
+    order_pda: Pubkey,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct MachineNew {
+    owner: Pubkey,
+    uuid: [u8; 16],
+    metadata: String,
+    status: MachineStatus,
+    price: u64,
+    max_duration: u32,
+    disk: u32,
+    completed_count: u32,
+    failed_count: u32,
+    score: u8,
+    claimed_periodic_rewards: u64,
+    claimed_task_rewards: u64,
+    order_pda: Pubkey,
+}
+
+#[allow(dead_code)]
+#[derive(Clone, Debug, PartialEq)]
+enum OrderStatus {
+    Preparing,
+    Training,
+    Completed,
+    Failed,
+    Refunded,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct Order {
+    order_id: [u8; 16],
+    buyer: Pubkey,
+    seller: Pubkey,
+    machine_id: [u8; 16],
+    price: u64,
+    duration: u32,
+    total: u64,
+    metadata: String,
+    status: OrderStatus,
+    order_time: i64,
+    start_time: i64,
+    refund_time: i64,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct OrderNew {
+    order_id: [u8; 16],
+    buyer: Pubkey,
+    seller: Pubkey,
+    machine_id: [u8; 16],
+    price: u64,
+    duration: u32,
+    total: u64,
+    metadata: String,
+    status: OrderStatus,
+    order_time: i64,
+    start_time: i64,
+    refund_time: i64,
+}
+
+fn machine() -> Machine {
+    Machine {
+        owner: Pubkey::new_unique(),
+        uuid: [1; 16],
+        metadata: "{\"gpu\":\"A100\"}".to_string(),
+        status: MachineStatus::Renting,
+        price: 5,
+        max_duration: 48,
+        disk: 512,
+        completed_count: 7,
+        failed_count: 1,
+        score: 90,
+        claimed_periodic_rewards: 1_000,
+        claimed_task_rewards: 200,
+        order_pda: Pubkey::new_unique(),
+    }
+}
+
+fn order(status: OrderStatus) -> Order {
+    Order {
+        order_id: [2; 16],
+        buyer: Pubkey::new_unique(),
+        seller: Pubkey::new_unique(),
+        machine_id: [1; 16],
+        price: 5,
+        duration: 3,
+        total: 15,
+        metadata: "{}".to_string(),
+        status,
+        order_time: 1_700_000_000,
+        start_time: 1_700_000_600,
+        refund_time: 0,
+    }
+}
+
+#[test]
+fn test_machine_round_trip_keeps_every_field() {
+    let before = machine();
+    let new = migrate_account!(&before, Machine => MachineNew {
+        copy {
+            owner, uuid, metadata, status, price, max_duration, disk, completed_count,
+            failed_count, score, claimed_periodic_rewards, claimed_task_rewards, order_pda,
+        },
+    });
+    assert_eq!(new.order_pda, before.order_pda);
+
+    let after = migrate_account!(&new, MachineNew => Machine {
+        copy {
+            owner, uuid, metadata, status, price, max_duration, disk, completed_count,
+            failed_count, score, claimed_periodic_rewards, claimed_task_rewards, order_pda,
+        },
+    });
+    assert_eq!(after, before);
+}
+
+#[test]
+fn test_order_round_trip_keeps_start_time() {
+    let before = order(OrderStatus::Training);
+    let new = migrate_account!(&before, Order => OrderNew {
+        copy {
+            order_id, buyer, seller, machine_id, price, duration, total, metadata, status,
+            order_time, start_time, refund_time,
+        },
+    });
+    assert_eq!(new.start_time, before.start_time);
+    assert_ne!(new.start_time, new.order_time);
+
+    let after = migrate_account!(&new, OrderNew => Order {
+        copy {
+            order_id, buyer, seller, machine_id, price, duration, total, metadata, status,
+            order_time, start_time, refund_time,
+        },
+    });
+    assert_eq!(after, before);
+}
+
+#[test]
+fn test_map_drop_and_init_sections() {
+    #[allow(dead_code)]
+    #[derive(Clone)]
+    struct Before {
+        owner: Pubkey,
+        legacy: u8,
+        status: OrderStatus,
+    }
+    #[derive(Debug, PartialEq)]
+    struct After {
+        owner: Pubkey,
+        status: OrderStatus,
+        data: u32,
+    }
+
+    let before = Before {
+        owner: Pubkey::new_unique(),
+        legacy: 9,
+        status: OrderStatus::Completed,
+    };
+    let after = migrate_account!(&before, Before => After {
+        copy { owner },
+        map {
+            status: match status {
+                OrderStatus::Refunded => OrderStatus::Failed,
+                other => other,
+            },
+        },
+        drop { legacy },
+        init { data: 42 },
+    });
+    assert_eq!(
+        after,
+        After {
+            owner: before.owner,
+            status: OrderStatus::Completed,
+            data: 42,
+        }
+    );
+}
+
+status_table! {
+    fn keep_status(OrderStatus) {
+        Preparing => Preparing,
+        Training => Training,
+        Completed => Completed,
+        Failed => Failed,
+        Refunded => Refunded,
+    }
+}
+
+status_table! {
+    fn settle_status(OrderStatus) {
+        Preparing => Refunded,
+        Training => Failed,
+        Completed => Completed,
+        Failed => Failed,
+        Refunded => Refunded,
+    }
+}
+
+#[test]
+fn test_status_tables() {
+    let statuses = [
+        OrderStatus::Preparing,
+        OrderStatus::Training,
+        OrderStatus::Completed,
+        OrderStatus::Failed,
+        OrderStatus::Refunded,
+    ];
+    for status in &statuses {
+        assert_eq!(&keep_status(status), status);
+    }
+
+    let changed: Vec<&OrderStatus> = statuses
+        .iter()
+        .filter(|status| &settle_status(status) != *status)
+        .collect();
+    assert_eq!(changed, vec![&OrderStatus::Preparing, &OrderStatus::Training]);
+}
@@ -0,0 +1,106 @@
+use lyzerai_core::client::migration::{
+    run, Checkpoint, MachineNewMigration, MigrationStep, RunOptions, UnverifiedAccount,
+};
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::signature::Keypair;
+use std::path::PathBuf;
+
+fn checkpoint_path(name: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("lyzerai-{}-{}.json", name, std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    path
+}
+
+#[test]
+fn test_parse_steps() {
+    for step in [
+        MigrationStep::MachineNew,
+        MigrationStep::MachineRename,
+        MigrationStep::OrderNew,
+        MigrationStep::OrderRename,
+    ] {
+        assert_eq!(step.name().parse::<MigrationStep>(), Ok(step));
+    }
+    assert!("machine".parse::<MigrationStep>().is_err());
+}
+
+#[test]
+fn test_checkpoint_round_trip() {
+    let path = checkpoint_path("round-trip");
+    let mut checkpoint = Checkpoint::load(&path, MigrationStep::OrderNew).unwrap();
+    assert!(checkpoint.completed.is_empty());
+
+    checkpoint.completed.insert("11111111111111111111111111111111".to_string());
+    checkpoint.save(&path).unwrap();
+
+    let resumed = Checkpoint::load(&path, MigrationStep::OrderNew).unwrap();
+    assert_eq!(resumed, checkpoint);
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_checkpoint_keeps_unverified_accounts() {
+    let path = checkpoint_path("unverified");
+    let mut checkpoint = Checkpoint::load(&path, MigrationStep::MachineNew).unwrap();
+    checkpoint.unverified.insert(
+        "11111111111111111111111111111111".to_string(),
+        UnverifiedAccount {
+            target: "SysvarC1ock11111111111111111111111111111111".to_string(),
+            before: vec![1, 2, 3],
+        },
+    );
+    checkpoint.save(&path).unwrap();
+
+    let resumed = Checkpoint::load(&path, MigrationStep::MachineNew).unwrap();
+    assert_eq!(resumed, checkpoint);
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_checkpoint_without_unverified_accounts_loads() {
+    let path = checkpoint_path("legacy");
+    std::fs::write(
+        &path,
+        r#"{"step":"order-new","completed":["11111111111111111111111111111111"]}"#,
+    )
+    .unwrap();
+
+    let checkpoint = Checkpoint::load(&path, MigrationStep::OrderNew).unwrap();
+    assert_eq!(checkpoint.completed.len(), 1);
+    assert!(checkpoint.unverified.is_empty());
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_checkpoint_of_another_step_is_ignored() {
+    let path = checkpoint_path("other-step");
+    let mut checkpoint = Checkpoint::load(&path, MigrationStep::MachineNew).unwrap();
+    checkpoint.completed.insert("11111111111111111111111111111111".to_string());
+    checkpoint.save(&path).unwrap();
+
+    let other = Checkpoint::load(&path, MigrationStep::MachineRename).unwrap();
+    assert_eq!(other.step, "machine-rename");
+    assert!(other.completed.is_empty());
+    std::fs::remove_file(&path).unwrap();
+}
+
+/// Needs a local validator with the program deployed: `cargo test -- --ignored`.
+#[test]
+#[ignore]
+fn test_dry_run_against_local_validator() {
+    let url = std::env::var("SOLANA_RPC_URL").unwrap_or_else(|_| "http://127.0.0.1:8899".to_string());
+    let client = RpcClient::new(url);
+    let path = checkpoint_path("local-validator");
+    let options = RunOptions {
+        dry_run: true,
+        allow_status_change: false,
+        concurrency: 4,
+        checkpoint: path.clone(),
+    };
+    let mut checkpoint = Checkpoint::load(&path, MigrationStep::MachineNew).unwrap();
+
+    let report = run::<MachineNewMigration>(&client, &Keypair::new(), &options, &mut checkpoint).unwrap();
+    assert_eq!(report.planned.len(), report.total);
+    assert!(report.planned.iter().all(|(_, diffs)| diffs.is_empty()));
+    assert!(!path.exists());
+}
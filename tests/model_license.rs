@@ -0,0 +1,112 @@
+use lyzerai_core::ai::ai_model::{LicenseReceipt, LicenseType};
+use lyzerai_core::services::licensing::{invocation_message, LicenseChecker, LicenseError, SignatureLog};
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signer};
+
+const NOW: i64 = 1_700_000_000;
+
+fn receipt(license: LicenseType, calls_remaining: u64, expire_time: i64) -> LicenseReceipt {
+    LicenseReceipt {
+        ai_model: Pubkey::new_unique(),
+        buyer: Pubkey::new_unique(),
+        license,
+        mint: Pubkey::new_unique(),
+        total_paid: 100,
+        calls_remaining,
+        expire_time,
+        purchase_time: NOW - 10,
+    }
+}
+
+#[test]
+fn test_per_call_receipt_needs_remaining_calls() {
+    assert!(receipt(LicenseType::PaidPerCall, 1, 0).is_valid(LicenseType::PaidPerCall, NOW));
+    assert!(!receipt(LicenseType::PaidPerCall, 0, 0).is_valid(LicenseType::PaidPerCall, NOW));
+}
+
+#[test]
+fn test_subscription_receipt_expires() {
+    let subscription = receipt(LicenseType::PaidSubscription, 0, NOW + 60);
+    assert!(subscription.is_valid(LicenseType::PaidSubscription, NOW));
+    assert!(!subscription.is_valid(LicenseType::PaidSubscription, NOW + 60));
+}
+
+#[test]
+fn test_receipt_for_another_license_is_invalid() {
+    let subscription = receipt(LicenseType::PaidSubscription, 5, NOW + 60);
+    assert!(!subscription.is_valid(LicenseType::PaidPerCall, NOW));
+}
+
+#[test]
+fn test_authenticate_invocation_signature() {
+    let wallet = Keypair::new();
+    let ai_model = Pubkey::new_unique();
+    let signature = wallet.sign_message(invocation_message(&ai_model, NOW).as_bytes());
+    let checker = LicenseChecker::new("http://localhost:8899", None);
+
+    let authenticated = checker.authenticate(
+        &ai_model,
+        &wallet.pubkey().to_string(),
+        NOW,
+        &signature.to_string(),
+        NOW + 10,
+    );
+    assert_eq!(authenticated.unwrap(), wallet.pubkey());
+
+    let other_model = checker.authenticate(
+        &Pubkey::new_unique(),
+        &wallet.pubkey().to_string(),
+        NOW,
+        &signature.to_string(),
+        NOW + 10,
+    );
+    assert!(matches!(other_model, Err(LicenseError::Unauthenticated)));
+
+    let stale = checker.authenticate(
+        &ai_model,
+        &wallet.pubkey().to_string(),
+        NOW,
+        &signature.to_string(),
+        NOW + 3_600,
+    );
+    assert!(matches!(stale, Err(LicenseError::Unauthenticated)));
+}
+
+#[test]
+fn test_invocation_signature_cannot_be_replayed() {
+    let wallet = Keypair::new();
+    let ai_model = Pubkey::new_unique();
+    let signature = wallet.sign_message(invocation_message(&ai_model, NOW).as_bytes());
+    let checker = LicenseChecker::new("http://localhost:8899", None);
+
+    let first = checker.authenticate(
+        &ai_model,
+        &wallet.pubkey().to_string(),
+        NOW,
+        &signature.to_string(),
+        NOW + 10,
+    );
+    assert_eq!(first.unwrap(), wallet.pubkey());
+
+    let replayed = checker.authenticate(
+        &ai_model,
+        &wallet.pubkey().to_string(),
+        NOW,
+        &signature.to_string(),
+        NOW + 20,
+    );
+    assert!(matches!(replayed, Err(LicenseError::Unauthenticated)));
+}
+
+#[test]
+fn test_signature_log_forgets_expired_signatures() {
+    let wallet = Keypair::new();
+    let log = SignatureLog::default();
+    let first = wallet.sign_message(b"first");
+    let second = wallet.sign_message(b"second");
+
+    assert!(log.record(first, NOW, NOW));
+    assert!(!log.record(first, NOW, NOW + 1));
+    assert!(log.record(second, NOW + 400, NOW + 400));
+    assert!(log.record(first, NOW, NOW + 400));
+}
@@ -0,0 +1,120 @@
+use anchor_lang::Event;
+use base64::{engine::general_purpose::STANDARD, Engine};
+use lyzerai_core::ai::ai_model::LicenseType;
+use lyzerai_core::services::model_registry::{ModelQuery, ModelRegistry};
+use lyzerai_core::utils::ai_model::{AiModelEvent, AiModelRemovedEvent};
+use solana_sdk::pubkey::Pubkey;
+
+fn transaction(events: &[Vec<u8>]) -> Vec<String> {
+    let program_id = lyzerai_core::ID;
+    let mut logs = vec![format!("Program {} invoke [1]", program_id)];
+    logs.extend(
+        events
+            .iter()
+            .map(|data| format!("Program data: {}", STANDARD.encode(data))),
+    );
+    logs.push(format!("Program {} success", program_id));
+    logs
+}
+
+fn model(
+    owner: Pubkey,
+    name: &str,
+    framework: u8,
+    license: LicenseType,
+    tags: &str,
+) -> AiModelEvent {
+    AiModelEvent {
+        owner,
+        name: name.to_string(),
+        framework,
+        license,
+        type1: 1,
+        type2: 2,
+        tags: tags.to_string(),
+        update_time: 1_700_000_000,
+    }
+}
+
+fn registry() -> (ModelRegistry, Pubkey) {
+    let owner = Pubkey::new_unique();
+    let registry = ModelRegistry::default();
+    registry.apply_logs(
+        &lyzerai_core::ID,
+        &transaction(&[
+            model(owner, "llama-ft", 1, LicenseType::Open, "llm, chat").data(),
+            model(owner, "resnet", 2, LicenseType::NonCommercial, "vision").data(),
+            model(Pubkey::new_unique(), "mistral", 1, LicenseType::NonCommercial, "LLM").data(),
+        ]),
+    );
+    (registry, owner)
+}
+
+#[test]
+fn test_search_by_framework_and_license() {
+    let (registry, _) = registry();
+
+    let query = ModelQuery {
+        framework: Some(1),
+        ..ModelQuery::default()
+    };
+    let names: Vec<String> = registry.search(&query).into_iter().map(|m| m.name).collect();
+    assert_eq!(names, vec!["llama-ft", "mistral"]);
+
+    let query = ModelQuery {
+        framework: Some(1),
+        license: Some(1),
+        ..ModelQuery::default()
+    };
+    let names: Vec<String> = registry.search(&query).into_iter().map(|m| m.name).collect();
+    assert_eq!(names, vec!["mistral"]);
+}
+
+#[test]
+fn test_search_by_tag_ignores_case_and_spacing() {
+    let (registry, _) = registry();
+
+    let query = ModelQuery {
+        tag: Some("llm".to_string()),
+        ..ModelQuery::default()
+    };
+    assert_eq!(registry.search(&query).len(), 2);
+}
+
+#[test]
+fn test_update_and_remove_events() {
+    let (registry, owner) = registry();
+    registry.apply_logs(
+        &lyzerai_core::ID,
+        &transaction(&[
+            model(owner, "resnet", 3, LicenseType::NonCommercial, "vision,classification").data(),
+            AiModelRemovedEvent {
+                owner,
+                name: "llama-ft".to_string(),
+            }
+            .data(),
+        ]),
+    );
+
+    let models = registry.search(&ModelQuery::default());
+    let names: Vec<&str> = models.iter().map(|m| m.name.as_str()).collect();
+    assert_eq!(names, vec!["mistral", "resnet"]);
+    assert_eq!(models[1].framework, 3);
+    assert_eq!(models[1].tags, vec!["vision", "classification"]);
+}
+
+#[test]
+fn test_events_from_other_programs_are_ignored() {
+    let registry = ModelRegistry::default();
+    let other = Pubkey::new_unique();
+    let logs = vec![
+        format!("Program {} invoke [1]", other),
+        format!(
+            "Program data: {}",
+            STANDARD.encode(model(Pubkey::new_unique(), "spoof", 1, LicenseType::Open, "").data())
+        ),
+        format!("Program {} success", other),
+    ];
+    registry.apply_logs(&lyzerai_core::ID, &logs);
+    assert!(registry.search(&ModelQuery::default()).is_empty());
+}
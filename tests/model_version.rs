@@ -0,0 +1,79 @@
+use lyzerai_core::ai::ai_model::ModelVersion;
+use lyzerai_core::ai::neural_network::{NeuralNetwork, TrendModel, TrendRule};
+use solana_sdk::hash::hash;
+use solana_sdk::pubkey::Pubkey;
+
+fn version_of(artifact: &[u8]) -> ModelVersion {
+    ModelVersion {
+        ai_model: Pubkey::new_unique(),
+        major: 1,
+        minor: 2,
+        patch: 0,
+        content_hash: hash(artifact).to_bytes(),
+        uri: "https://models.example/weights.bin".to_string(),
+        size: artifact.len() as u64,
+        changelog: String::new(),
+        create_time: 1_700_000_000,
+    }
+}
+
+#[test]
+fn test_verify_matching_artifact() {
+    let artifact = b"model weights".to_vec();
+    let version = version_of(&artifact);
+    assert!(NeuralNetwork::verify_artifact(&artifact, &version).is_ok());
+    assert_eq!(version.version(), "1.2.0");
+}
+
+#[test]
+fn test_parse_version_round_trips() {
+    let version = version_of(b"model weights");
+    assert_eq!(ModelVersion::parse_version(&version.version()), Some((1, 2, 0)));
+    assert_eq!(ModelVersion::parse_version("1.2"), None);
+    assert_eq!(ModelVersion::parse_version("1.2.0.1"), None);
+    assert_eq!(ModelVersion::parse_version("1.x.0"), None);
+    assert_eq!(ModelVersion::parse_version("70000.0.0"), None);
+}
+
+#[test]
+fn test_reject_tampered_artifact() {
+    let version = version_of(b"model weights");
+    assert!(NeuralNetwork::verify_artifact(b"model weighta", &version).is_err());
+}
+
+#[test]
+fn test_reject_truncated_artifact() {
+    let version = version_of(b"model weights");
+    assert!(NeuralNetwork::verify_artifact(b"model weight", &version).is_err());
+}
+
+#[test]
+fn test_run_verified_refuses_unverified_artifact() {
+    let version = version_of(b"model weights");
+    let result = NeuralNetwork::run_verified(b"other weights", &version, "bullish trend");
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_run_verified_uses_the_artifact_model() {
+    let model = TrendModel {
+        rules: vec![TrendRule {
+            keyword: "sideways".to_string(),
+            trend: "Neutral".to_string(),
+        }],
+    };
+    let artifact = serde_json::to_vec(&model).unwrap();
+    let version = version_of(&artifact);
+
+    let prediction = NeuralNetwork::run_verified(&artifact, &version, "sideways market").unwrap();
+    assert_eq!(prediction.predicted_trend, "Neutral");
+    // The built-in rules are not part of this artifact
+    assert!(NeuralNetwork::run_verified(&artifact, &version, "bullish trend").is_err());
+}
+
+#[test]
+fn test_run_verified_rejects_malformed_artifact() {
+    let artifact = b"model weights".to_vec();
+    let version = version_of(&artifact);
+    assert!(NeuralNetwork::run_verified(&artifact, &version, "bullish trend").is_err());
+}
@@ -0,0 +1,75 @@
+use anchor_lang::AnchorDeserialize;
+use lyzerai_core::client::claim::{reward_period_pda, reward_work_pda};
+use lyzerai_core::client::order::{order_completed_instruction, order_pda};
+use solana_sdk::pubkey::Pubkey;
+
+/// Returns the text between `open` and the matching closing parenthesis after it.
+fn parenthesized<'a>(source: &'a str, open: &str) -> &'a str {
+    let start = source.find(open).expect("declaration is present") + open.len();
+    let rest = &source[start..];
+    let mut depth = 1;
+    for (index, c) in rest.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => {
+                depth -= 1;
+                if depth == 0 {
+                    return &rest[..index];
+                }
+            }
+            _ => {}
+        }
+    }
+    panic!("unbalanced parentheses after {}", open);
+}
+
+fn normalize(args: &str) -> String {
+    args.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+#[test]
+fn instruction_attribute_matches_handler_arguments() {
+    let source = include_str!("../src/utils/order.rs");
+    let handler = parenthesized(source, "pub fn order_completed(");
+    let (_, handler_args) = handler
+        .split_once(',')
+        .expect("order_completed takes arguments after the context");
+    let accounts = source
+        .split_once("pub struct OrderCompleted<")
+        .map(|(before, _)| before)
+        .expect("OrderCompleted is declared");
+    let attribute = parenthesized(&accounts[accounts.rfind("#[instruction").unwrap()..], "#[instruction(");
+
+    assert_eq!(normalize(attribute), normalize(handler_args.trim_end_matches(',')));
+}
+
+#[test]
+fn encoded_period_derives_the_account_seeds() {
+    let seller = Pubkey::new_unique();
+    let buyer = Pubkey::new_unique();
+    let mint = Pubkey::new_unique();
+    let machine_id = [7u8; 16];
+    let order = order_pda(&buyer, &[1u8; 16]);
+    let period = 42;
+
+    let instruction = order_completed_instruction(
+        &seller,
+        &order,
+        &machine_id,
+        &mint,
+        "done".to_string(),
+        period,
+        None,
+    );
+
+    // Decode the arguments the way the `#[instruction]` attribute of `OrderCompleted` declares them
+    let (metadata, decoded_period) =
+        <(String, u32)>::deserialize(&mut &instruction.data[8..]).unwrap();
+    assert_eq!(metadata, "done");
+    assert_eq!(decoded_period, period);
+    assert_eq!(instruction.accounts[5].pubkey, reward_period_pda(decoded_period));
+    assert_eq!(
+        instruction.accounts[6].pubkey,
+        reward_work_pda(decoded_period, &seller, &machine_id)
+    );
+}
@@ -0,0 +1,86 @@
+use anchor_lang::Event;
+use base64::{engine::general_purpose::STANDARD, Engine};
+use lyzerai_core::services::events::{parse_order_events, OrderLifecycleEvent};
+use lyzerai_core::state::order::OrderStatus;
+use lyzerai_core::utils::order::{OrderCompletedEvent, OrderPlacedEvent};
+use solana_sdk::pubkey::Pubkey;
+
+fn program_data(event: &impl Event) -> String {
+    format!("Program data: {}", STANDARD.encode(event.data()))
+}
+
+fn placed(order_id: [u8; 16]) -> OrderPlacedEvent {
+    OrderPlacedEvent {
+        order_id,
+        buyer: Pubkey::new_unique(),
+        seller: Pubkey::new_unique(),
+        machine_id: [7; 16],
+        status: OrderStatus::Preparing,
+        mint: Pubkey::new_unique(),
+        price: 100,
+        duration: 3,
+        total: 300,
+        order_time: 1_700_000_000,
+    }
+}
+
+#[test]
+fn test_parse_order_events() {
+    let program_id = lyzerai_core::ID;
+    let completed = OrderCompletedEvent {
+        order_id: [2; 16],
+        buyer: Pubkey::new_unique(),
+        seller: Pubkey::new_unique(),
+        machine_id: [7; 16],
+        status: OrderStatus::Completed,
+        mint: Pubkey::new_unique(),
+        seller_amount: 300,
+        start_time: 1_700_000_100,
+        complete_time: 1_700_010_900,
+    };
+    let logs = vec![
+        format!("Program {} invoke [1]", program_id),
+        "Program log: Instruction: PlaceOrder".to_string(),
+        program_data(&placed([1; 16])),
+        format!("Program {} success", program_id),
+        format!("Program {} invoke [1]", program_id),
+        program_data(&completed),
+        format!("Program {} success", program_id),
+    ];
+
+    let events = parse_order_events(&program_id, &logs);
+
+    assert_eq!(events.len(), 2);
+    match &events[0] {
+        OrderLifecycleEvent::Placed(event) => {
+            assert_eq!(event.order_id, [1; 16]);
+            assert_eq!(event.total, 300);
+        }
+        other => panic!("Unexpected event: {:?}", other),
+    }
+    match &events[1] {
+        OrderLifecycleEvent::Completed(event) => {
+            assert_eq!(event.seller_amount, 300);
+        }
+        other => panic!("Unexpected event: {:?}", other),
+    }
+}
+
+#[test]
+fn test_parse_ignores_other_programs() {
+    let program_id = lyzerai_core::ID;
+    let other_program = Pubkey::new_unique();
+    let logs = vec![
+        format!("Program {} invoke [1]", program_id),
+        format!("Program {} invoke [2]", other_program),
+        program_data(&placed([3; 16])),
+        format!("Program {} success", other_program),
+        program_data(&placed([4; 16])),
+        format!("Program {} success", program_id),
+    ];
+
+    let events = parse_order_events(&program_id, &logs);
+
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].order_id(), [4; 16]);
+}
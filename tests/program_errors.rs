@@ -0,0 +1,63 @@
+use anchor_lang::error::ERROR_CODE_OFFSET;
+use lyzerai_core::client::errors::{program_error, TransactionFailure, PROGRAM_ERRORS};
+use lyzerai_core::errors::DistriAIError;
+use solana_sdk::instruction::InstructionError;
+use solana_sdk::transaction::TransactionError;
+
+#[test]
+fn table_matches_program_error_codes() {
+    for (index, error) in PROGRAM_ERRORS.iter().enumerate() {
+        let code = u32::from(*error);
+        assert_eq!(code, ERROR_CODE_OFFSET + index as u32, "{:?}", error);
+        assert_eq!(program_error(code).map(u32::from), Some(code));
+    }
+    assert!(program_error(ERROR_CODE_OFFSET - 1).is_none());
+    assert!(program_error(ERROR_CODE_OFFSET + PROGRAM_ERRORS.len() as u32).is_none());
+}
+
+#[test]
+fn table_lists_every_variant_in_declaration_order() {
+    let source = include_str!("../src/errors.rs");
+    let body = source
+        .split_once("pub enum DistriAIError {")
+        .and_then(|(_, rest)| rest.split_once('}'))
+        .map(|(body, _)| body)
+        .expect("DistriAIError is declared in src/errors.rs");
+    let declared: Vec<&str> = body
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#') && !line.starts_with("//"))
+        .map(|line| line.trim_end_matches(','))
+        .collect();
+    let listed: Vec<String> = PROGRAM_ERRORS.iter().map(|error| format!("{:?}", error)).collect();
+    assert_eq!(listed, declared);
+}
+
+#[test]
+fn decodes_program_error_from_failed_transaction() {
+    let code = u32::from(DistriAIError::OrderNotCancelable);
+    let error = TransactionError::InstructionError(1, InstructionError::Custom(code));
+    let failure = TransactionFailure::from_transaction_error(&error);
+
+    assert!(matches!(
+        failure,
+        TransactionFailure::Program {
+            instruction: 1,
+            error: DistriAIError::OrderNotCancelable
+        }
+    ));
+    assert_eq!(failure.code(), Some(code));
+    assert_eq!(failure.name().as_deref(), Some("OrderNotCancelable"));
+}
+
+#[test]
+fn keeps_unknown_codes_and_other_failures() {
+    let constraint = TransactionError::InstructionError(0, InstructionError::Custom(2003));
+    let failure = TransactionFailure::from_transaction_error(&constraint);
+    assert!(matches!(failure, TransactionFailure::Custom { instruction: 0, code: 2003 }));
+    assert_eq!(failure.name(), None);
+
+    let failure = TransactionFailure::from_transaction_error(&TransactionError::AccountNotFound);
+    assert!(matches!(failure, TransactionFailure::Other(_)));
+    assert_eq!(failure.code(), None);
+}
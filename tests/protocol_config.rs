@@ -0,0 +1,146 @@
+use lyzerai_core::errors::DistriAIError;
+use lyzerai_core::state::protocol_config::{ProtocolConfig, ProtocolParams};
+use solana_sdk::pubkey::Pubkey;
+
+const GENESIS: i64 = 1_735_689_600;
+const DAY: i64 = 86_400;
+
+fn params() -> ProtocolParams {
+    ProtocolParams {
+        order_cancel_window: 300,
+        duration_unit: 3_600,
+        reward_genesis_time: GENESIS,
+        reward_period_duration: DAY,
+        reward_initial_pool: 1_000_000,
+        reward_halving_periods: 10,
+        heartbeat_interval: 60,
+        heartbeat_max_missed: 5,
+    }
+}
+
+fn config(params: ProtocolParams) -> ProtocolConfig {
+    ProtocolConfig {
+        authority: Pubkey::new_unique(),
+        pending_authority: Pubkey::default(),
+        guardian: Pubkey::new_unique(),
+        paused: 0,
+        mint: Pubkey::new_unique(),
+        params,
+        metering_authority: Pubkey::default(),
+        payment_mints: Vec::new(),
+    }
+}
+
+#[test]
+fn periods_follow_the_configured_schedule() {
+    let config = config(params());
+    assert_eq!(config.current_period(GENESIS).unwrap(), 0);
+    assert_eq!(config.current_period(GENESIS + DAY - 1).unwrap(), 0);
+    assert_eq!(config.current_period(GENESIS + 3 * DAY).unwrap(), 3);
+    assert!(config.current_period(GENESIS - 1).is_err());
+    assert_eq!(config.period_start_time(3), GENESIS + 3 * DAY);
+}
+
+#[test]
+fn pool_halves_every_halving_interval() {
+    let config = config(params());
+    assert_eq!(config.period_pool(0), 1_000_000);
+    assert_eq!(config.period_pool(9), 1_000_000);
+    assert_eq!(config.period_pool(10), 500_000);
+    assert_eq!(config.period_pool(25), 250_000);
+    assert_eq!(config.period_pool(10 * 64), 0);
+
+    let constant = config(ProtocolParams {
+        reward_halving_periods: 0,
+        ..params()
+    });
+    assert_eq!(constant.period_pool(1_000), 1_000_000);
+}
+
+#[test]
+fn rejects_zero_durations() {
+    assert!(params().validate().is_ok());
+    let invalid = ProtocolParams {
+        reward_period_duration: 0,
+        ..params()
+    };
+    assert!(invalid.validate().is_err());
+    let invalid = ProtocolParams {
+        duration_unit: 0,
+        ..params()
+    };
+    assert!(invalid.validate().is_err());
+    let invalid = ProtocolParams {
+        heartbeat_max_missed: 0,
+        ..params()
+    };
+    assert!(invalid.validate().is_err());
+}
+
+#[test]
+fn order_duration_uses_the_configured_unit() {
+    let config = config(params());
+    assert_eq!(config.duration_seconds(2).unwrap(), 7_200);
+}
+
+#[test]
+fn paused_flags_only_block_their_instructions() {
+    let mut config = config(params());
+    assert!(config.require_active(ProtocolConfig::PAUSE_PLACE_ORDER).is_ok());
+
+    config.paused = ProtocolConfig::PAUSE_PLACE_ORDER | ProtocolConfig::PAUSE_CLAIM;
+    assert!(config.require_active(ProtocolConfig::PAUSE_PLACE_ORDER).is_err());
+    assert!(config.require_active(ProtocolConfig::PAUSE_CLAIM).is_err());
+    assert!(config.require_active(ProtocolConfig::PAUSE_STAKE_DEPOSIT).is_ok());
+
+    config.paused = ProtocolConfig::PAUSE_ALL;
+    for flag in 0..8 {
+        assert!(config.is_paused(1 << flag));
+    }
+    assert!(!config.is_paused(1 << 8));
+    assert!(config.require_active(ProtocolConfig::PAUSE_COMPLETE_ORDER).is_err());
+    assert!(config.require_active(ProtocolConfig::PAUSE_PURCHASE_LICENSE).is_err());
+}
+
+#[test]
+fn only_listed_mints_are_accepted_for_model_payments() {
+    let mut config = config(params());
+    let mint = Pubkey::new_unique();
+    assert!(!config.accepts_payment_mint(&mint));
+    config.payment_mints = vec![config.mint, mint];
+    assert!(config.accepts_payment_mint(&mint));
+    assert!(!config.accepts_payment_mint(&Pubkey::new_unique()));
+}
+
+#[test]
+fn update_keeps_the_duration_unit_and_period_schedule() {
+    let current = params();
+    let tuned = ProtocolParams {
+        order_cancel_window: 600,
+        reward_initial_pool: 2_000_000,
+        heartbeat_interval: 30,
+        ..current
+    };
+    assert!(current.validate_update(&tuned).is_ok());
+
+    let frozen = [
+        ProtocolParams {
+            duration_unit: 60,
+            ..current
+        },
+        ProtocolParams {
+            reward_genesis_time: GENESIS + DAY,
+            ..current
+        },
+        ProtocolParams {
+            reward_period_duration: 2 * DAY,
+            ..current
+        },
+    ];
+    for new in frozen {
+        assert_eq!(
+            current.validate_update(&new).unwrap_err(),
+            DistriAIError::InvalidConfig.into()
+        );
+    }
+}
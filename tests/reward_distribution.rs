@@ -0,0 +1,80 @@
+use lyzerai_core::utils::distribution::{
+    machine_share, settled_order_hours, work_weight, MAX_SHARE_BPS, MAX_WORK_UNITS, TASK_UNITS,
+};
+use proptest::prelude::*;
+use solana_sdk::pubkey::Pubkey;
+
+fn machine_work() -> impl Strategy<Value = (u32, u32, u8)> {
+    (0u32..5_000, 0u32..2_000, any::<u8>())
+}
+
+proptest! {
+    #[test]
+    fn test_pool_is_never_over_distributed(
+        pool in any::<u64>(),
+        machines in prop::collection::vec(machine_work(), 1..200),
+    ) {
+        let weights: Vec<u64> = machines
+            .iter()
+            .map(|&(tasks, hours, score)| work_weight(tasks, hours, score))
+            .collect();
+        let total_weight: u64 = weights.iter().sum();
+
+        let distributed: u128 = weights
+            .iter()
+            .map(|&weight| u128::from(machine_share(pool, weight, total_weight)))
+            .sum();
+
+        prop_assert!(distributed <= u128::from(pool));
+    }
+
+    #[test]
+    fn test_share_is_capped_per_machine(
+        pool in any::<u64>(),
+        weight in 1u64..u64::MAX,
+        others in 0u64..1_000_000,
+    ) {
+        let share = machine_share(pool, weight, weight.saturating_add(others));
+        let cap = u128::from(pool) * u128::from(MAX_SHARE_BPS) / 10_000;
+        prop_assert!(u128::from(share) <= cap);
+    }
+
+    #[test]
+    fn test_more_tasks_never_lowers_weight(
+        (tasks, hours, score) in machine_work(),
+        extra in 1u32..100,
+    ) {
+        prop_assert!(work_weight(tasks + extra, hours, score) >= work_weight(tasks, hours, score));
+    }
+}
+
+#[test]
+fn test_weight_scales_with_work() {
+    assert_eq!(work_weight(0, 0, 100), 0);
+    assert!(work_weight(1_000, 0, 80) > work_weight(1, 0, 80));
+    assert!(work_weight(10, 0, 100) > work_weight(10, 0, 0));
+}
+
+#[test]
+fn test_work_units_are_capped() {
+    let capped_tasks = (MAX_WORK_UNITS / TASK_UNITS) as u32;
+    assert_eq!(work_weight(capped_tasks, 0, 50), work_weight(capped_tasks * 10, 0, 50));
+}
+
+#[test]
+fn test_share_with_no_work() {
+    assert_eq!(machine_share(1_000, 0, 0), 0);
+    assert_eq!(machine_share(1_000, 0, 10), 0);
+}
+
+#[test]
+fn test_only_hours_settled_by_other_buyers_count() {
+    let owner = Pubkey::new_unique();
+    let buyer = Pubkey::new_unique();
+    assert_eq!(settled_order_hours(&buyer, &owner, 500, 100), 5);
+    // Partially paid hours are not credited
+    assert_eq!(settled_order_hours(&buyer, &owner, 199, 100), 1);
+    // Renting one's own machine earns no work
+    assert_eq!(settled_order_hours(&owner, &owner, 500, 100), 0);
+    assert_eq!(settled_order_hours(&buyer, &owner, 500, 0), 0);
+}
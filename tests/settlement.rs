@@ -0,0 +1,268 @@
+use lyzerai_core::errors::DistriAIError;
+use lyzerai_core::utils::pricing::{order_total, refund_split, renewal, used_duration};
+use proptest::prelude::*;
+
+const HOUR: i64 = 3_600;
+
+struct OpenOrder {
+    price: u64,
+    duration: u32,
+    total: u64,
+    /// `None` while the order is `Preparing`, the start time once it is `Training`.
+    start_time: Option<i64>,
+}
+
+struct QueuedOrder {
+    price: u64,
+    duration: u32,
+    total: u64,
+    expire_time: i64,
+}
+
+/// Token flows of the order vault, following the settlement rules of the program.
+///
+/// The vault holds the escrow of open orders as well as of orders waiting in a machine queue.
+#[derive(Default)]
+struct Vault {
+    inflows: u64,
+    outflows: u64,
+    orders: Vec<OpenOrder>,
+    queue: Vec<QueuedOrder>,
+}
+
+impl Vault {
+    fn place(&mut self, price: u64, duration: u32) {
+        let total = order_total(price, duration).unwrap();
+        self.inflows += total;
+        self.orders.push(OpenOrder { price, duration, total, start_time: None });
+    }
+
+    fn start(&mut self, index: usize, now: i64) {
+        let order = &mut self.orders[index];
+        if order.start_time.is_none() {
+            order.start_time = Some(now);
+        }
+    }
+
+    fn renew(&mut self, index: usize, duration: u32) {
+        let order = &mut self.orders[index];
+        let (new_duration, new_total, amount) =
+            renewal(order.duration, order.total, order.price, duration).unwrap();
+        order.duration = new_duration;
+        order.total = new_total;
+        self.inflows += amount;
+    }
+
+    fn refund(&mut self, index: usize, now: i64) {
+        let order = &self.orders[index];
+        let Some(start_time) = order.start_time else {
+            // The machine never started, so the buyer gets the whole escrow back
+            self.outflows += order.total;
+            self.orders.remove(index);
+            return;
+        };
+        let used = used_duration(start_time, now, HOUR).unwrap();
+        if used >= order.duration {
+            return;
+        }
+        let (seller_amount, buyer_amount) = refund_split(order.total, order.price, used).unwrap();
+        assert_eq!(seller_amount + buyer_amount, order.total);
+        self.outflows += seller_amount + buyer_amount;
+        self.orders.remove(index);
+    }
+
+    fn complete(&mut self, index: usize) {
+        let order = self.orders.remove(index);
+        self.outflows += order.total;
+    }
+
+    fn enqueue(&mut self, price: u64, duration: u32, expire_time: i64) {
+        let total = order_total(price, duration).unwrap();
+        self.inflows += total;
+        self.queue.push(QueuedOrder { price, duration, total, expire_time });
+    }
+
+    fn cancel_queued(&mut self, index: usize) {
+        let queued = self.queue.remove(index);
+        self.outflows += queued.total;
+    }
+
+    /// Activates the head of the queue, or pops it with a refund once it has expired.
+    fn activate_or_skip(&mut self, now: i64) {
+        if self.queue.is_empty() {
+            return;
+        }
+        let queued = self.queue.remove(0);
+        if now >= queued.expire_time {
+            self.outflows += queued.total;
+        } else {
+            // The escrow stays in the vault and moves over to the new order
+            self.orders.push(OpenOrder {
+                price: queued.price,
+                duration: queued.duration,
+                total: queued.total,
+                start_time: None,
+            });
+        }
+    }
+
+    fn open_totals(&self) -> u64 {
+        let open: u64 = self.orders.iter().map(|order| order.total).sum();
+        let queued: u64 = self.queue.iter().map(|queued| queued.total).sum();
+        open + queued
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Step {
+    Place { price: u64, duration: u32 },
+    Start { order: usize },
+    Renew { order: usize, duration: u32 },
+    Refund { order: usize },
+    Complete { order: usize },
+    Enqueue { price: u64, duration: u32, expires_in: i64 },
+    CancelQueued { order: usize },
+    ActivateOrSkip,
+    Wait { seconds: i64 },
+}
+
+fn step() -> impl Strategy<Value = Step> {
+    prop_oneof![
+        (1u64..1_000_000_000, 1u32..100).prop_map(|(price, duration)| Step::Place { price, duration }),
+        any::<usize>().prop_map(|order| Step::Start { order }),
+        (any::<usize>(), 1u32..100).prop_map(|(order, duration)| Step::Renew { order, duration }),
+        any::<usize>().prop_map(|order| Step::Refund { order }),
+        any::<usize>().prop_map(|order| Step::Complete { order }),
+        (1u64..1_000_000_000, 1u32..100, 1i64..10 * HOUR).prop_map(|(price, duration, expires_in)| {
+            Step::Enqueue { price, duration, expires_in }
+        }),
+        any::<usize>().prop_map(|order| Step::CancelQueued { order }),
+        Just(Step::ActivateOrSkip),
+        (0i64..10 * HOUR).prop_map(|seconds| Step::Wait { seconds }),
+    ]
+}
+
+#[test]
+fn test_overflow_is_an_error() {
+    let error = order_total(u64::MAX, 2).unwrap_err();
+    assert_eq!(error, DistriAIError::MathOverflow.into());
+    assert!(renewal(u32::MAX, 100, 1, 1).is_err());
+    assert!(renewal(1, u64::MAX, 1, 1).is_err());
+    assert!(refund_split(100, 200, 1).is_err());
+}
+
+#[test]
+fn test_used_duration_counts_the_current_hour() {
+    assert_eq!(used_duration(0, 0, HOUR).unwrap(), 1);
+    assert_eq!(used_duration(0, HOUR - 1, HOUR).unwrap(), 1);
+    assert_eq!(used_duration(0, HOUR, HOUR).unwrap(), 2);
+    assert!(used_duration(HOUR, 0, HOUR).is_err());
+    assert!(used_duration(0, HOUR, 0).is_err());
+}
+
+#[test]
+fn test_renewal_is_priced_at_order_price() {
+    let order_price = 100;
+    let (duration, total) = (2, order_total(order_price, 2).unwrap());
+
+    // `renew_order` passes the price locked into the order, so a later machine price is ignored
+    let machine_price = 2 * order_price;
+    let (new_duration, new_total, amount) = renewal(duration, total, order_price, 3).unwrap();
+
+    assert_eq!(amount, 300);
+    assert_ne!(amount, order_total(machine_price, 3).unwrap());
+    assert_eq!(new_duration, 5);
+    assert_eq!(new_total, 500);
+    assert_eq!(new_total, order_total(order_price, new_duration).unwrap());
+}
+
+#[test]
+fn test_repeated_renewals_keep_total_consistent() {
+    let price = 250;
+    let mut duration = 1;
+    let mut total = order_total(price, duration).unwrap();
+    let mut paid = total;
+    for extra in [2, 1, 4] {
+        let (new_duration, new_total, amount) = renewal(duration, total, price, extra).unwrap();
+        duration = new_duration;
+        total = new_total;
+        paid += amount;
+    }
+
+    assert_eq!(duration, 8);
+    assert_eq!(total, paid);
+    assert_eq!(total, order_total(price, duration).unwrap());
+}
+
+#[test]
+fn test_queued_escrow_is_returned_or_carried_over() {
+    let mut vault = Vault::default();
+    vault.enqueue(100, 2, HOUR);
+    vault.enqueue(100, 3, HOUR);
+    vault.enqueue(100, 4, 2 * HOUR);
+
+    vault.cancel_queued(1);
+    assert_eq!(vault.outflows, 300);
+
+    // The head is still valid and becomes a preparing order that is refunded in full
+    vault.activate_or_skip(0);
+    assert_eq!(vault.orders.len(), 1);
+    vault.refund(0, HOUR);
+    assert_eq!(vault.outflows, 500);
+
+    // The remaining queued order has expired and is skipped with a refund
+    vault.activate_or_skip(2 * HOUR);
+    assert!(vault.orders.is_empty());
+    assert_eq!(vault.inflows, vault.outflows);
+}
+
+proptest! {
+    #[test]
+    fn test_vault_inflows_equal_outflows(steps in prop::collection::vec(step(), 1..100)) {
+        let mut vault = Vault::default();
+        let mut now = 1_735_689_600;
+        for step in steps {
+            match step {
+                Step::Place { price, duration } => vault.place(price, duration),
+                Step::Start { order } if !vault.orders.is_empty() => {
+                    vault.start(order % vault.orders.len(), now)
+                }
+                Step::Renew { order, duration } if !vault.orders.is_empty() => {
+                    vault.renew(order % vault.orders.len(), duration)
+                }
+                Step::Refund { order } if !vault.orders.is_empty() => {
+                    vault.refund(order % vault.orders.len(), now)
+                }
+                Step::Complete { order } if !vault.orders.is_empty() => {
+                    vault.complete(order % vault.orders.len())
+                }
+                Step::Enqueue { price, duration, expires_in } => {
+                    vault.enqueue(price, duration, now + expires_in)
+                }
+                Step::CancelQueued { order } if !vault.queue.is_empty() => {
+                    vault.cancel_queued(order % vault.queue.len())
+                }
+                Step::ActivateOrSkip => vault.activate_or_skip(now),
+                Step::Wait { seconds } => now += seconds,
+                _ => {}
+            }
+            prop_assert_eq!(vault.inflows, vault.outflows + vault.open_totals());
+        }
+
+        while !vault.queue.is_empty() {
+            vault.cancel_queued(0);
+        }
+        while !vault.orders.is_empty() {
+            vault.complete(0);
+        }
+        prop_assert_eq!(vault.inflows, vault.outflows);
+    }
+
+    #[test]
+    fn test_refund_split_adds_up(price in 1u64..u64::MAX / 1_000, duration in 1u32..1_000, used in 0u32..1_000) {
+        let total = order_total(price, duration).unwrap();
+        let used = used.min(duration);
+        let (seller_amount, buyer_amount) = refund_split(total, price, used).unwrap();
+        prop_assert_eq!(seller_amount + buyer_amount, total);
+    }
+}
@@ -0,0 +1,78 @@
+use lyzerai_core::errors::DistriAIError;
+use lyzerai_core::state::stake::MachineStake;
+use solana_sdk::pubkey::Pubkey;
+
+const NOW: i64 = 1_735_689_600;
+
+fn stake(amount: u64) -> MachineStake {
+    MachineStake {
+        owner: Pubkey::new_unique(),
+        machine_id: [1; 16],
+        amount,
+        active_orders: 0,
+        slashed: 0,
+        last_active_time: NOW,
+    }
+}
+
+#[test]
+fn slashes_ten_percent_of_the_stake() {
+    assert_eq!(
+        stake(MachineStake::MIN_FOR_RENT).slash_amount(u64::MAX),
+        MachineStake::MIN_FOR_RENT / 10
+    );
+    assert_eq!(stake(9).slash_amount(u64::MAX), 0);
+    assert_eq!(stake(u64::MAX).slash_amount(u64::MAX), u64::MAX / 10_000);
+}
+
+#[test]
+fn slash_is_capped_by_the_order_total() {
+    let stake = stake(MachineStake::MIN_FOR_RENT);
+    assert_eq!(stake.slash_amount(0), 0);
+    assert_eq!(stake.slash_amount(1), MachineStake::SLASH_MAX_TOTAL_MULTIPLE);
+    assert_eq!(stake.slash_amount(500), 500 * MachineStake::SLASH_MAX_TOTAL_MULTIPLE);
+}
+
+#[test]
+fn withdraw_waits_for_the_cooldown() {
+    let stake = stake(500);
+    let unlocked = NOW + MachineStake::WITHDRAW_COOLDOWN;
+    assert_eq!(stake.withdrawable_time(), unlocked);
+    assert_eq!(
+        stake.remaining_after_withdraw(100, unlocked - 1, false).unwrap_err(),
+        DistriAIError::StakeLocked.into()
+    );
+    assert_eq!(stake.remaining_after_withdraw(100, unlocked, false).unwrap(), 400);
+}
+
+#[test]
+fn withdraw_refused_while_orders_are_active() {
+    let mut stake = stake(500);
+    stake.active_orders = 1;
+    let later = NOW + 10 * MachineStake::WITHDRAW_COOLDOWN;
+    assert_eq!(
+        stake.remaining_after_withdraw(100, later, false).unwrap_err(),
+        DistriAIError::StakeLocked.into()
+    );
+
+    stake.active_orders = 0;
+    assert_eq!(stake.remaining_after_withdraw(500, later, false).unwrap(), 0);
+}
+
+#[test]
+fn withdraw_keeps_minimum_stake_for_rent() {
+    let stake = stake(MachineStake::MIN_FOR_RENT + 100);
+    let later = NOW + MachineStake::WITHDRAW_COOLDOWN;
+    assert_eq!(
+        stake.remaining_after_withdraw(101, later, true).unwrap_err(),
+        DistriAIError::InsufficientStake.into()
+    );
+    assert_eq!(
+        stake.remaining_after_withdraw(100, later, true).unwrap(),
+        MachineStake::MIN_FOR_RENT
+    );
+    assert_eq!(
+        stake.remaining_after_withdraw(MachineStake::MIN_FOR_RENT + 101, later, false).unwrap_err(),
+        DistriAIError::InsufficientStake.into()
+    );
+}
@@ -0,0 +1,86 @@
+use lyzerai_core::errors::DistriAIError;
+use lyzerai_core::state::reward_period::RewardPeriod;
+use lyzerai_core::state::verifier::{TaskProof, TaskProofStatus};
+use solana_sdk::pubkey::Pubkey;
+
+const NOW: i64 = 1_735_689_600;
+const RESULT: [u8; 32] = [7; 32];
+
+fn task_proof() -> TaskProof {
+    TaskProof {
+        task: Pubkey::new_unique(),
+        owner: Pubkey::new_unique(),
+        machine_id: [1; 16],
+        period: 3,
+        result_hash: RESULT,
+        status: TaskProofStatus::Pending,
+        verifier: Pubkey::default(),
+        submit_time: NOW,
+        verify_time: 0,
+    }
+}
+
+fn reward_period(closed: bool) -> RewardPeriod {
+    RewardPeriod {
+        period: 3,
+        machine_num: 1,
+        total_weight: 0,
+        reserved: 0,
+        claimed: 0,
+        closed,
+        expired: false,
+        close_time: 0,
+    }
+}
+
+#[test]
+fn attests_tasks_of_past_periods_until_closed() {
+    let verifier = Pubkey::new_unique();
+    let mut proof = task_proof();
+    // The task period has ended but `close_period` has not run yet
+    proof.attest(verifier, RESULT, &reward_period(false), NOW + 30 * 86_400).unwrap();
+    assert_eq!(proof.status, TaskProofStatus::Verified);
+    assert_eq!(proof.verifier, verifier);
+
+    let mut proof = task_proof();
+    assert_eq!(
+        proof.attest(verifier, RESULT, &reward_period(true), NOW).unwrap_err(),
+        DistriAIError::PeriodClosed.into()
+    );
+    assert_eq!(proof.status, TaskProofStatus::Pending);
+}
+
+#[test]
+fn attest_requires_matching_result_and_pending_status() {
+    let verifier = Pubkey::new_unique();
+    let mut proof = task_proof();
+    assert_eq!(
+        proof.attest(verifier, [0; 32], &reward_period(false), NOW).unwrap_err(),
+        DistriAIError::ResultHashMismatch.into()
+    );
+
+    proof.attest(verifier, RESULT, &reward_period(false), NOW).unwrap();
+    assert_eq!(
+        proof.attest(verifier, RESULT, &reward_period(false), NOW).unwrap_err(),
+        DistriAIError::IncorrectStatus.into()
+    );
+}
+
+#[test]
+fn challenge_reports_whether_the_task_counted() {
+    let mut pending = task_proof();
+    assert!(!pending.challenge(&reward_period(false), NOW).unwrap());
+    assert_eq!(pending.status, TaskProofStatus::Invalidated);
+    assert_eq!(
+        pending.challenge(&reward_period(false), NOW).unwrap_err(),
+        DistriAIError::IncorrectStatus.into()
+    );
+
+    let mut verified = task_proof();
+    verified.attest(Pubkey::new_unique(), RESULT, &reward_period(false), NOW).unwrap();
+    assert_eq!(
+        verified.challenge(&reward_period(true), NOW).unwrap_err(),
+        DistriAIError::PeriodClosed.into()
+    );
+    assert!(verified.challenge(&reward_period(false), NOW).unwrap());
+}
@@ -0,0 +1,122 @@
+use lyzerai_core::errors::DistriAIError;
+use lyzerai_core::state::machine_history::MachineHistory;
+use lyzerai_core::state::vesting::{VestingConfig, VestingSchedule};
+use solana_sdk::pubkey::Pubkey;
+
+const START: i64 = 1_735_689_600;
+const DAY: i64 = 86_400;
+
+fn schedule(total: u64) -> VestingSchedule {
+    VestingSchedule {
+        owner: Pubkey::new_unique(),
+        machine_id: [1; 16],
+        period: 4,
+        total,
+        withdrawn: 0,
+        start_time: START,
+        cliff: 30 * DAY,
+        duration: 120 * DAY,
+        accelerated: false,
+    }
+}
+
+#[test]
+fn nothing_vests_before_the_cliff() {
+    let schedule = schedule(1_200);
+    assert_eq!(schedule.vested_amount(START), 0);
+    assert_eq!(schedule.vested_amount(START + 30 * DAY - 1), 0);
+    // At the cliff the linear amount since the start is released at once
+    assert_eq!(schedule.vested_amount(START + 30 * DAY), 300);
+}
+
+#[test]
+fn everything_vests_at_the_end() {
+    let schedule = schedule(1_200);
+    assert_eq!(schedule.vested_amount(START + 120 * DAY - 1), 1_199);
+    assert_eq!(schedule.vested_amount(START + 120 * DAY), 1_200);
+    assert_eq!(schedule.vested_amount(START + 1_000 * DAY), 1_200);
+}
+
+#[test]
+fn accelerated_schedules_vest_immediately() {
+    let mut schedule = schedule(1_200);
+    schedule.accelerated = true;
+    assert_eq!(schedule.vested_amount(START), 1_200);
+}
+
+#[test]
+fn withdrawals_never_exceed_the_total() {
+    let mut schedule = schedule(1_200);
+    assert_eq!(
+        schedule.withdraw(START + DAY).unwrap_err(),
+        DistriAIError::NothingVested.into()
+    );
+
+    assert_eq!(schedule.withdraw(START + 60 * DAY).unwrap(), 600);
+    assert_eq!(
+        schedule.withdraw(START + 60 * DAY).unwrap_err(),
+        DistriAIError::NothingVested.into()
+    );
+    assert_eq!(schedule.withdraw(START + 200 * DAY).unwrap(), 600);
+    assert_eq!(
+        schedule.withdraw(START + 300 * DAY).unwrap_err(),
+        DistriAIError::NothingVested.into()
+    );
+    assert_eq!(schedule.withdrawn, schedule.total);
+}
+
+#[test]
+fn config_rejects_invalid_terms() {
+    let config = |cliff, duration| VestingConfig {
+        enabled: true,
+        cliff,
+        duration,
+        accelerate_threshold: 10,
+    };
+    assert!(config(0, 0).validate().is_ok());
+    assert!(config(30 * DAY, 120 * DAY).validate().is_ok());
+    assert_eq!(config(-1, 10).validate().unwrap_err(), DistriAIError::InvalidConfig.into());
+    assert_eq!(config(20, 10).validate().unwrap_err(), DistriAIError::InvalidConfig.into());
+}
+
+#[test]
+fn config_rejects_zero_acceleration_threshold() {
+    let config = VestingConfig {
+        enabled: true,
+        cliff: 0,
+        duration: 120 * DAY,
+        accelerate_threshold: 0,
+    };
+    assert_eq!(config.validate().unwrap_err(), DistriAIError::InvalidConfig.into());
+    assert!(!config.can_accelerate(u64::MAX));
+}
+
+#[test]
+fn acceleration_follows_lifetime_settled_hours() {
+    let config = VestingConfig {
+        enabled: true,
+        cliff: 0,
+        duration: 120 * DAY,
+        accelerate_threshold: 100,
+    };
+    let machine = Pubkey::new_unique();
+    let mut history = MachineHistory {
+        machine: Pubkey::default(),
+        settled_orders: 0,
+        settled_hours: 0,
+    };
+
+    // Orders rented to the owner settle no hours and build no history
+    history.record(machine, 0);
+    assert_eq!(history.settled_orders, 0);
+    for _ in 0..3 {
+        history.record(machine, 33);
+    }
+    assert_eq!(history.machine, machine);
+    assert_eq!(history.settled_orders, 3);
+    assert!(!config.can_accelerate(history.settled_hours));
+
+    history.record(machine, 1);
+    assert_eq!(history.settled_hours, 100);
+    assert!(config.can_accelerate(history.settled_hours));
+}